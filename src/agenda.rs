@@ -0,0 +1,427 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+use icalendar::{CalendarDateTime, Component, DatePerhapsTime};
+
+use crate::{
+    app::{IsekCalendars, IsekEvent, IsekTodo},
+    helper::{
+        from_local, ical_datetime_to_chrono, ical_deadline_to_chrono, param_value,
+        parse_ical_datetime, parse_ical_duration, property_datetime, to_local,
+    },
+    recurrence::RecurrenceRule,
+};
+
+/// Days shown in the agenda
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AgendaRange {
+    #[default]
+    Today,
+    Week,
+}
+
+impl AgendaRange {
+    /// Number of days starting today
+    pub fn days(&self) -> u32 {
+        match self {
+            AgendaRange::Today => 1,
+            AgendaRange::Week => 7,
+        }
+    }
+
+    /// Switch between today and the next 7 days
+    pub fn toggle(&self) -> Self {
+        match self {
+            AgendaRange::Today => AgendaRange::Week,
+            AgendaRange::Week => AgendaRange::Today,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            AgendaRange::Today => "Today",
+            AgendaRange::Week => "Next 7 days",
+        }
+    }
+}
+
+/// Event occurrence or task listed on a day of the agenda
+#[derive(Debug)]
+pub struct AgendaEntry<'a> {
+    /// Day the entry is listed on
+    pub date: NaiveDate,
+    /// Whether the entry lasts the whole day (events on dates, tasks due on a date)
+    pub all_day: bool,
+    /// Local start time (the due time for tasks)
+    pub start: NaiveDateTime,
+    /// Local end time (the due time for tasks)
+    pub end: NaiveDateTime,
+    pub item: AgendaItem<'a>,
+}
+
+#[derive(Debug)]
+pub enum AgendaItem<'a> {
+    Event(IsekEvent<'a>),
+    Task(IsekTodo<'a>),
+}
+
+/// Single occurrence of an event as instants
+struct Occurrence<'e, 'a> {
+    event: &'e IsekEvent<'a>,
+    all_day: bool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// Events and open tasks due in the given days, sorted chronologically per day
+///
+/// Recurring events are expanded, events spanning several days are listed on each of them.
+/// Overdue tasks are listed on the first day.
+pub fn agenda<'a>(
+    calendars: &'a IsekCalendars,
+    from: NaiveDate,
+    days: u32,
+) -> Vec<AgendaEntry<'a>> {
+    let window_start = from_local(from.and_time(NaiveTime::default()));
+    let dates: Vec<NaiveDate> = from.iter_days().take(days as usize).collect();
+    let Some(last) = dates.last() else {
+        return vec![];
+    };
+    let window_end = from_local((*last + TimeDelta::days(1)).and_time(NaiveTime::default()));
+
+    let events = calendars.get_events();
+    let mut entries = vec![];
+
+    for occurrence in occurrences(&events, window_start, window_end) {
+        for date in &dates {
+            let day_start = from_local(date.and_time(NaiveTime::default()));
+            let day_end = from_local((*date + TimeDelta::days(1)).and_time(NaiveTime::default()));
+
+            if occurrence.overlaps(day_start, day_end) {
+                entries.push(AgendaEntry {
+                    date: *date,
+                    all_day: occurrence.all_day
+                        || (occurrence.start <= day_start && occurrence.end >= day_end),
+                    start: to_local(&occurrence.start),
+                    end: to_local(&occurrence.end),
+                    item: AgendaItem::Event(occurrence.event.clone()),
+                });
+            }
+        }
+    }
+
+    for todo in calendars.get_todos(None, None) {
+        if todo.is_closed() {
+            continue;
+        }
+        let Some(due) = todo.get().get_due() else {
+            continue;
+        };
+
+        let all_day = matches!(due, DatePerhapsTime::Date(_));
+        let instant = ical_deadline_to_chrono(due);
+        if instant >= window_end {
+            continue;
+        }
+
+        let local = to_local(&instant);
+        entries.push(AgendaEntry {
+            // Overdue tasks are listed on the first day
+            date: local.date().max(from),
+            all_day,
+            start: local,
+            end: local,
+            item: AgendaItem::Task(todo),
+        });
+    }
+
+    entries.sort_by(cmp_entries);
+    entries
+}
+
+/// Order by day, all day entries first, then by time (events before tasks) and summary
+fn cmp_entries(a: &AgendaEntry, b: &AgendaEntry) -> Ordering {
+    let is_task = |e: &AgendaEntry| matches!(e.item, AgendaItem::Task(_));
+
+    a.date
+        .cmp(&b.date)
+        .then(b.all_day.cmp(&a.all_day))
+        .then_with(|| match a.all_day {
+            true => Ordering::Equal,
+            false => a.start.cmp(&b.start),
+        })
+        .then(is_task(a).cmp(&is_task(b)))
+        .then_with(|| a.summary().cmp(&b.summary()))
+}
+
+impl AgendaEntry<'_> {
+    /// Summary of the event or task
+    pub fn summary(&self) -> String {
+        match &self.item {
+            AgendaItem::Event(e) => e.property("SUMMARY").unwrap_or_default().to_owned(),
+            AgendaItem::Task(t) => t.get().get_summary().unwrap_or_default().to_owned(),
+        }
+    }
+}
+
+/// Occurrences of all events overlapping the window
+fn occurrences<'e, 'a>(
+    events: &'e [IsekEvent<'a>],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<Occurrence<'e, 'a>> {
+    // Occurrences of recurring events which were modified, by calendar and UID
+    let mut overrides: HashMap<(&str, &str), Vec<DateTime<Utc>>> = HashMap::new();
+    for event in events {
+        if let (Some(uid), Some(id)) = (event.property("UID"), recurrence_id(event)) {
+            overrides
+                .entry((event.cal_name.as_str(), uid))
+                .or_default()
+                .push(id);
+        }
+    }
+
+    events
+        .iter()
+        .filter(|e| e.property("STATUS") != Some("CANCELLED"))
+        .flat_map(|event| {
+            let overridden = match (event.property("UID"), recurrence_id(event)) {
+                (Some(uid), None) => overrides.get(&(event.cal_name.as_str(), uid)),
+                _ => None,
+            };
+
+            expand(event, window_start, window_end)
+                .into_iter()
+                .filter(move |o| overridden.is_none_or(|ids| !ids.contains(&o.start)))
+        })
+        .filter(|o| o.overlaps(window_start, window_end))
+        .collect()
+}
+
+impl Occurrence<'_, '_> {
+    /// Check if the occurrence takes place between `start` and `end` (exclusive)
+    ///
+    /// Occurrences without duration overlap if they start in the range.
+    fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.start < end && (self.end > start || (self.start == self.end && self.start >= start))
+    }
+}
+
+/// Instant of the RECURRENCE-ID of an event (only set for modified occurrences)
+fn recurrence_id(event: &IsekEvent) -> Option<DateTime<Utc>> {
+    event
+        .properties("RECURRENCE-ID")
+        .next()
+        .and_then(property_datetime)
+        .map(ical_datetime_to_chrono)
+}
+
+/// Occurrences of an event starting before `before`
+///
+/// Recurring events are only expanded from occurrences which can still last until `after`.
+fn expand<'e, 'a>(
+    event: &'e IsekEvent<'a>,
+    after: DateTime<Utc>,
+    before: DateTime<Utc>,
+) -> Vec<Occurrence<'e, 'a>> {
+    let Some(start) = event
+        .properties("DTSTART")
+        .next()
+        .and_then(property_datetime)
+    else {
+        return vec![];
+    };
+
+    let all_day = matches!(start, DatePerhapsTime::Date(_));
+    let first = ical_datetime_to_chrono(start.clone());
+
+    // DTEND, DURATION or the default duration (one day for dates, none for times)
+    let duration = event
+        .properties("DTEND")
+        .next()
+        .and_then(property_datetime)
+        .map(|end| ical_datetime_to_chrono(end) - first)
+        .or_else(|| event.property("DURATION").and_then(parse_ical_duration))
+        .unwrap_or(match all_day {
+            true => TimeDelta::days(1),
+            false => TimeDelta::zero(),
+        })
+        .max(TimeDelta::zero());
+
+    let rule = match event.property("RRULE").and_then(RecurrenceRule::parse) {
+        Some(rule) if !event.properties("RECURRENCE-ID").any(|_| true) => rule,
+        _ => {
+            return vec![Occurrence {
+                event,
+                all_day,
+                start: first,
+                end: first + duration,
+            }];
+        }
+    };
+
+    // Recurrence is expanded in the timezone of DTSTART to keep the time of day across DST
+    let (naive, rebuild): (NaiveDateTime, Box<dyn Fn(NaiveDateTime) -> DatePerhapsTime>) =
+        match start {
+            DatePerhapsTime::Date(date) => (
+                date.and_time(NaiveTime::default()),
+                Box::new(|dt: NaiveDateTime| DatePerhapsTime::Date(dt.date())),
+            ),
+            DatePerhapsTime::DateTime(CalendarDateTime::Floating(dt)) => {
+                (dt, Box::new(|dt| CalendarDateTime::Floating(dt).into()))
+            }
+            DatePerhapsTime::DateTime(CalendarDateTime::Utc(dt)) => (
+                dt.naive_utc(),
+                Box::new(|dt: NaiveDateTime| CalendarDateTime::Utc(dt.and_utc()).into()),
+            ),
+            DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, tzid }) => (
+                date_time,
+                Box::new(move |dt| {
+                    CalendarDateTime::WithTimezone {
+                        date_time: dt,
+                        tzid: tzid.clone(),
+                    }
+                    .into()
+                }),
+            ),
+        };
+
+    // Timezones differ by less than two days, the exact limits are checked as instants
+    let earliest = (after - duration).naive_utc() - TimeDelta::days(2);
+    let limit = before.naive_utc() + TimeDelta::days(2);
+    let until = rule.until.clone().map(ical_deadline_to_chrono);
+
+    let mut starts: Vec<DateTime<Utc>> = rule
+        .occurrences(naive, earliest, limit)
+        .into_iter()
+        .map(|dt| ical_datetime_to_chrono(rebuild(dt)))
+        .filter(|dt| until.is_none_or(|until| *dt <= until))
+        .collect();
+
+    // Additional occurrences (RDATE) and excluded ones (EXDATE)
+    starts.extend(date_list(event, "RDATE"));
+    let excluded = date_list(event, "EXDATE");
+    starts.retain(|dt| !excluded.contains(dt));
+
+    starts.sort();
+    starts.dedup();
+
+    starts
+        .into_iter()
+        .filter(|dt| *dt < before)
+        .map(|start| Occurrence {
+            event,
+            all_day,
+            start,
+            end: start + duration,
+        })
+        .collect()
+}
+
+/// Instants of a property with comma separated dates (e.g. EXDATE)
+fn date_list(event: &IsekEvent, name: &str) -> Vec<DateTime<Utc>> {
+    event
+        .properties(name)
+        .flat_map(|p| {
+            let tzid = param_value(p, "TZID");
+
+            p.value
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(move |v| parse_ical_datetime(v, tzid))
+                .map(ical_datetime_to_chrono)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::Datelike;
+    use chrono_tz::Tz;
+
+    use super::*;
+    use crate::{
+        config::{CalendarConfig, CalendarType, IndexConfig},
+        helper::set_local_timezone,
+    };
+
+    /// Calendar directory with an event for every (UID, properties) pair
+    fn calendars(test: &str, events: &[(&str, &str)]) -> IsekCalendars {
+        let dir = std::env::temp_dir().join(format!("isek-agenda-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("displayname"), "Events").unwrap();
+        fs::write(dir.join("color"), "#3A7BD5").unwrap();
+
+        for (uid, properties) in events {
+            let data = format!(
+                "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:test\r\nBEGIN:VEVENT\r\nUID:{uid}\r\n\
+                 SUMMARY:{uid}\r\n{}\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+                properties.trim().replace('\n', "\r\n")
+            );
+            fs::write(dir.join(format!("{uid}.ics")), data).unwrap();
+        }
+
+        let cfg = CalendarConfig {
+            kind: CalendarType::VDIR,
+            path: dir.to_str().unwrap().to_owned(),
+            display_name: None,
+            color: None,
+            credentials: None,
+            command: None,
+            refresh: None,
+        };
+        let calendars = IsekCalendars::from_config(vec![cfg], IndexConfig::default()).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+
+        calendars
+    }
+
+    /// Days of the month with an occurrence of the event with the given summary
+    fn days(entries: &[AgendaEntry], summary: &str) -> Vec<u32> {
+        entries
+            .iter()
+            .filter(|e| e.summary() == summary)
+            .map(|e| e.date.day())
+            .collect()
+    }
+
+    #[test]
+    fn recurring_events_end_with_until_and_skip_excluded_dates() {
+        set_local_timezone(Tz::Europe__Berlin);
+        let calendars = calendars(
+            "until",
+            &[
+                // UNTIL is the start of the last occurrence (inclusive)
+                (
+                    "included",
+                    "DTSTART;TZID=Europe/Berlin:20250301T090000
+DURATION:PT1H
+RRULE:FREQ=DAILY;UNTIL=20250305T080000Z
+EXDATE;TZID=Europe/Berlin:20250303T090000",
+                ),
+                (
+                    "excluded",
+                    "DTSTART;TZID=Europe/Berlin:20250301T090000
+RRULE:FREQ=DAILY;UNTIL=20250305T075959Z
+EXDATE:20250302T080000Z,20250303T080000Z",
+                ),
+                // Expanded from the window, not from 25 years ago
+                (
+                    "old",
+                    "DTSTART;VALUE=DATE:20000101
+RRULE:FREQ=DAILY;INTERVAL=2",
+                ),
+            ],
+        );
+
+        let from = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let entries = agenda(&calendars, from, 7);
+        assert_eq!(days(&entries, "included"), [1, 2, 4, 5]);
+        assert_eq!(days(&entries, "excluded"), [1, 4]);
+        assert_eq!(days(&entries, "old"), [2, 4, 6]);
+    }
+}
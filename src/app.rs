@@ -1,41 +1,128 @@
 use std::{
-    cmp::Ordering,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::{self, File},
     io::BufReader,
     path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, Utc};
 use color_eyre::eyre::Result;
-use colors_transform::{Color, Rgb};
+use colors_transform::Rgb;
 use eyre::{Context, ContextCompat, eyre};
 use ical::{
-    IcalParser, generator::Emitter, parser::ical::component::IcalCalendar, property::Property,
+    IcalParser,
+    generator::Emitter,
+    parser::ical::component::{IcalAlarm, IcalCalendar, IcalEvent, IcalTimeZone, IcalTodo},
+    property::Property,
 };
-use icalendar::{Calendar, CalendarComponent, Component, Todo};
+use icalendar::{Calendar, CalendarComponent, Component, DatePerhapsTime, Todo, TodoStatus};
 use ratatui::{
-    style::{self, Stylize},
-    text::Span,
+    layout::Rect,
+    style::{Style, Styled},
+    text::{Line, Span},
     widgets::ListState,
 };
 
 use crate::{
+    agenda::AgendaRange,
+    caldav::{CalDav, conflict_error},
     config::{
-        CalendarConfig, CalendarType, DisplayOptions, FilterConfig, IsekConfig, ShowDoneOptions,
-        SortingConfig, SortingVariant,
+        CalendarConfig, CalendarType, DisplayOptions, FilterConfig, IndexConfig, IsekConfig,
+        LayoutVariant, PriorityDisplay, ShowDoneOptions, SortingConfig, SortingVariant,
+        TableColumnConfig,
     },
     helper::{
-        ICAL_UTC_DATE_TIME_FORMAT, calculate_index, format_ical_datetime, ical_datetime_to_chrono,
+        DEFAULT_CALENDAR_COLOR, ICAL_UTC_DATE_TIME_FORMAT, date_property, format_ical_datetime,
+        format_ical_datetime_relative, get_property, ical_datetime_to_chrono,
+        ical_deadline_to_chrono, local_today, lower_priority, parse_date_input,
+        property_local_date, raise_priority, references_timezone, remove_property,
+        replace_property, set_property, shift_date_property, to_local,
     },
+    history::{FileChange, History},
+    ics_file::IcsFile,
+    index::IndexScore,
+    line_format::{Field, FieldFormat, LineFormat},
+    remind::AlarmTrigger,
+    sort::sort_todos,
+    subscription::Subscription,
+    theme::Theme,
+    timezone,
+    view::Views,
 };
 
+/// Entry of a single file calendar with its events (`/` can't be part of a task's file name)
+const FILE_EVENTS_ENTRY: &str = "/events";
+
+/// Maximum time between two clicks on the same task to count as double click
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+
+/// Properties changed when completing a task and the X- properties storing their previous value
+const COMPLETION_BACKUP_PROPERTIES: [(&str, &str); 2] = [
+    ("STATUS", "X-ISEK-PREVIOUS-STATUS"),
+    ("PERCENT-COMPLETE", "X-ISEK-PREVIOUS-PERCENT-COMPLETE"),
+];
+
 #[derive(Debug)]
 pub enum State {
     Normal,
     Interactive,
     ConfigSort,
     ConfigFilter,
+    /// Selecting a range of tasks starting at the given index
+    Visual(usize),
+    /// Choosing a batch operation for the marked tasks
+    Batch,
+    /// Choosing how long the marked (or all overdue) tasks are snoozed
+    Snooze {
+        overdue: bool,
+    },
+    /// Choosing the calendar the marked tasks are moved (or copied) to
+    MoveTarget {
+        copy: bool,
+    },
+    /// Waiting for text input
+    Prompt(Prompt),
+}
+
+/// Period shown in the calendar grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CalendarMode {
+    #[default]
+    Month,
+    Week,
+}
+
+/// Values which can be entered by the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prompt {
+    Priority,
+    Due,
+    Start,
+    Tag,
+    Snooze {
+        overdue: bool,
+    },
+    Alarm,
+    /// Summary of a new task
+    New,
+}
+
+impl Prompt {
+    /// Text displayed in front of the input
+    pub fn title(&self) -> &'static str {
+        match self {
+            Prompt::Priority => "Priority (0-9)",
+            Prompt::Due => "Due date (YYYY-MM-DD [HH:MM], today, tomorrow, +3d)",
+            Prompt::Start => {
+                "Start date (YYYY-MM-DD [HH:MM], today, tomorrow, +3d, empty to remove)"
+            }
+            Prompt::Tag => "Tag",
+            Prompt::Snooze { .. } => "Snooze until (YYYY-MM-DD, tomorrow, friday, +3d)",
+            Prompt::Alarm => "Alarm (15m, 2h, 1d before due or YYYY-MM-DD HH:MM)",
+            Prompt::New => "New task",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -45,6 +132,15 @@ pub enum CalData {
     VDIR(HashMap<String, (IcalCalendar, Calendar)>),
 }
 
+/// Identifies a task across re-sorting and reloading
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TaskId {
+    /// Name of the calendar containing the task
+    pub calendar: String,
+    /// UID of the task
+    pub uid: String,
+}
+
 /// Representation of a ToDo item
 #[derive(Debug)]
 pub struct IsekTodo<'a> {
@@ -55,52 +151,197 @@ pub struct IsekTodo<'a> {
 }
 
 impl<'a> IsekTodo<'a> {
+    /// Task of the calendar with the given name, display name and color
+    pub fn new(
+        cal_name: &'a String,
+        cal_display_name: &'a Option<String>,
+        color: &'a Rgb,
+        data: &'a Todo,
+    ) -> Self {
+        Self {
+            cal_name,
+            cal_display_name,
+            color,
+            data,
+        }
+    }
+
     pub fn get(&self) -> &Todo {
         self.data
     }
 
-    pub fn format(&self, state: &App) -> Vec<Span> {
-        let t = self;
+    /// Identifier of this task (None if the task has no UID)
+    pub fn id(&self) -> Option<TaskId> {
+        Some(TaskId {
+            calendar: self.cal_name.clone(),
+            uid: self.get().get_uid()?.to_owned(),
+        })
+    }
+
+    /// Tags (CATEGORIES) of the task
+    pub fn tags(&self) -> Vec<String> {
+        self.get()
+            .multi_properties()
+            .get("CATEGORIES")
+            .into_iter()
+            .flatten()
+            .flat_map(|p| p.value().split(','))
+            .map(|tag| tag.trim().to_owned())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+
+    /// Check if the task is not completed and its due date has passed
+    pub fn is_overdue(&self, now: &DateTime<Utc>) -> bool {
+        let t = self.get();
+
+        t.get_completed().is_none()
+            && t.get_due()
+                .is_some_and(|dt| ical_deadline_to_chrono(dt) < *now)
+    }
+
+    /// Check if the task is completed or cancelled
+    pub fn is_closed(&self) -> bool {
+        let t = self.get();
+
+        t.get_completed().is_some()
+            || matches!(
+                t.get_status(),
+                Some(TodoStatus::Completed | TodoStatus::Cancelled)
+            )
+    }
+
+    /// Local date the task is due on
+    pub fn due_date(&self) -> Option<NaiveDate> {
+        self.get()
+            .get_due()
+            .map(|dt| to_local(&ical_deadline_to_chrono(dt)).date())
+    }
+
+    /// Check if the start date (DTSTART) of the task is in the future
+    ///
+    /// Tasks starting on a date (without time) are deferred until that day begins.
+    pub fn is_deferred(&self, now: &DateTime<Utc>) -> bool {
+        match self.get().get_start() {
+            Some(DatePerhapsTime::Date(date)) => date > to_local(now).date(),
+            Some(dt) => ical_datetime_to_chrono(dt) > *now,
+            None => false,
+        }
+    }
 
-        vec![
-            match t.get().get_completed() {
-                Some(_) => state.display.progress.done.clone(),
-                None => state.display.progress.none.clone(),
+    /// Progress string and style based on the state of the task
+    fn progress<'s>(&self, state: &'s App) -> (&'s String, Style) {
+        let theme = &state.theme;
+
+        match (self.get().get_completed(), self.get().get_status()) {
+            (Some(_), _) => (&state.display.progress.done, theme.done),
+            (None, Some(TodoStatus::InProcess)) => {
+                (&state.display.progress.in_progress, theme.in_progress)
             }
-            .into(),
-            format!(
-                " {} ",
-                match t.cal_display_name {
-                    Some(n) => n,
-                    None => t.cal_name,
+            (None, _) => (&state.display.progress.none, theme.pending),
+        }
+    }
+
+    /// Render a single field of the task
+    pub fn field(&self, format: &FieldFormat, state: &App) -> Span<'static> {
+        let t = self.get();
+        let theme = &state.theme;
+        let (progress, state_style) = self.progress(state);
+
+        let format_date = |dt: DatePerhapsTime| match format.relative {
+            true => format_ical_datetime_relative(dt, &Utc::now()),
+            false => format_ical_datetime(
+                dt,
+                &state.display.date_format.date,
+                &state.display.date_format.datetime,
+            ),
+        };
+
+        match format.field {
+            Field::Progress => progress.clone().set_style(state_style),
+            Field::Priority => {
+                let (marks, letter, style) = match t.get_priority() {
+                    Some(1..=4) => ("!!!", "H", theme.priority_high),
+                    Some(5) => ("!!", "M", theme.priority_medium),
+                    Some(6..=9) => ("!", "L", theme.priority_low),
+                    _ => return "".into(),
+                };
+
+                match state.display.priority {
+                    PriorityDisplay::Marks => marks.to_owned(),
+                    PriorityDisplay::Letters => letter.to_owned(),
+                    PriorityDisplay::Number => t.get_priority().unwrap_or_default().to_string(),
                 }
-            )
-            .bg(style::Color::Rgb(
-                t.color.get_red() as u8,
-                t.color.get_green() as u8,
-                t.color.get_blue() as u8,
-            )),
-            " ".into(),
-            t.get()
+                .set_style(style)
+            }
+            Field::Summary => t
                 .get_summary()
                 .wrap_err_with(|| {
-                    format!("No summary (e.g. title) for some ToDo in {}", t.cal_name)
+                    format!("No summary (e.g. title) for some ToDo in {}", self.cal_name)
                 })
                 .unwrap()
+                .to_owned()
+                .set_style(state_style),
+            Field::Tags => self
+                .tags()
+                .iter()
+                .map(|tag| format!("#{}", tag))
+                .collect::<Vec<String>>()
+                .join(" ")
+                .into(),
+            Field::Due => match t.get_due() {
+                Some(dt) => {
+                    let overdue = self.is_overdue(&Utc::now());
+
+                    format_date(dt).set_style(if overdue {
+                        theme.date_overdue
+                    } else {
+                        theme.date
+                    })
+                }
+                None => "".into(),
+            },
+            Field::Start => match t.get_start() {
+                Some(dt) => format_date(dt).set_style(theme.date),
+                None => "".into(),
+            },
+            Field::Calendar => format!(
+                " {} ",
+                match self.cal_display_name {
+                    Some(n) => n,
+                    None => self.cal_name,
+                }
+            )
+            .set_style(theme.calendar_chip(self.color)),
+            Field::Location => t
+                .property_value("LOCATION")
+                .unwrap_or_default()
+                .to_owned()
                 .into(),
-            match t.get().get_due() {
-                Some(dt) => format!(
-                    " {}",
-                    format_ical_datetime(
-                        dt,
-                        &state.display.date_format.date,
-                        &state.display.date_format.datetime
-                    )
-                )
-                .fg(style::Color::Blue),
+            Field::Percent => match t.get_percent_complete() {
+                Some(percent) => format!("{}%", percent).into(),
+                None => "".into(),
+            },
+            Field::Status => match t.get_status() {
+                Some(TodoStatus::NeedsAction) => "NEEDS-ACTION".into(),
+                Some(TodoStatus::InProcess) => "IN-PROCESS".into(),
+                Some(TodoStatus::Completed) => "COMPLETED".into(),
+                Some(TodoStatus::Cancelled) => "CANCELLED".into(),
                 None => "".into(),
             },
-        ]
+            Field::Index => format!(
+                "{:.2}",
+                IndexScore::new(self, &state.calendars.index, &Utc::now()).total()
+            )
+            .into(),
+        }
+    }
+
+    /// Render the task as a line with at most `width` columns based on the configured line format
+    pub fn format(&self, state: &App, width: usize) -> Line<'static> {
+        state
+            .line_format
+            .render(width, |format| self.field(format, state))
     }
 
     // pub fn get_mut(&mut self) -> &mut Todo {
@@ -137,6 +378,31 @@ impl<'a> IsekTodo<'a> {
     // }
 }
 
+/// Representation of an event (VEVENT)
+#[derive(Debug, Clone)]
+pub struct IsekEvent<'a> {
+    pub cal_name: &'a String,
+    pub cal_display_name: &'a Option<String>,
+    pub color: &'a Rgb,
+    data: &'a IcalEvent,
+}
+
+impl IsekEvent<'_> {
+    pub fn get(&self) -> &IcalEvent {
+        self.data
+    }
+
+    /// Value of a property
+    pub fn property(&self, name: &str) -> Option<&str> {
+        get_property(&self.data.properties, name)
+    }
+
+    /// All properties with the given name (e.g. EXDATE)
+    pub fn properties<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s Property> {
+        self.data.properties.iter().filter(move |p| p.name == name)
+    }
+}
+
 /// Representation of a calendar with its configuration and data
 #[derive(Debug)]
 pub struct IsekCalendar {
@@ -148,146 +414,302 @@ pub struct IsekCalendar {
     pub color: Rgb,
     /// Parsed iCalendar data containing todos and events
     data: CalData,
+    /// Files modified (or removed) since the last save
+    dirty: HashSet<String>,
+    /// Server and local cache of CalDAV calendars
+    remote: Option<CalDav>,
+    /// File of single file calendars (split into one entry per task in `data`)
+    file: Option<IcsFile>,
+    /// Source and cache of read-only subscriptions (split like single file calendars)
+    subscription: Option<Subscription>,
+    /// Last problem while syncing with the server (e.g. offline), not shown yet
+    sync_problem: Option<String>,
 }
 
 impl IsekCalendar {
     /// Create a new calendar instance from configuration
     pub fn from_config(cfg: CalendarConfig) -> Result<Self> {
-        match cfg.kind {
-            CalendarType::VDIR => {
-                // Read all .ics files in directory
-                let dir_path = Path::new(&cfg.path);
-                let entries = fs::read_dir(dir_path)?;
-                let mut cal: HashMap<String, (IcalCalendar, Calendar)> = HashMap::new();
-
-                // Get calendar display name from file
-                let name_path = dir_path.join("displayname");
-                let name = fs::read_to_string(name_path)?;
-
-                // Get and parse color configuration
-                let color_path = dir_path.join("color");
-                let color_string = fs::read_to_string(color_path)?;
-                let color = Rgb::from_hex_str(&color_string).map_err(|err| {
-                    eyre!(
-                        "Unable to read color for calendar '{}': {}",
-                        name,
-                        err.message
-                    )
-                })?;
-
-                // Process all .ics files in the directory
-                for entry in entries.flatten() {
-                    let path = entry.path();
-
-                    // Check if it is a file and has .ics extension
-                    if path.is_file()
-                        && path
-                            .extension()
-                            .and_then(|s| s.to_str())
-                            .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
-                    {
-                        match File::open(&path) {
-                            Ok(f) => {
-                                let buf = BufReader::new(f);
-                                let ical = IcalParser::new(buf).last().and_then(|res| res.ok());
-
-                                match ical {
-                                    Some(ical) => {
-                                        if ical.todos.is_empty() {
-                                            // Skip files without Todos
-                                            continue;
-                                        }
-
-                                        match fs::read_to_string(&path) {
-                                            Ok(content) => {
-                                                match content.parse() {
-                                                    Ok(parsed_calendar) => {
-                                                        cal.insert(
-                                                            String::from(
-                                                                path.file_name()
-                                                                    .unwrap()
-                                                                    .to_str()
-                                                                    .unwrap(),
-                                                            )
-                                                            .trim_end_matches(".ics")
-                                                            .to_owned(), // WARN: File name should always exist and be valid
-                                                            (ical, parsed_calendar),
-                                                        );
-                                                    }
-                                                    Err(e) => {
-                                                        eprintln!(
-                                                            "Error parsing file {}: {}",
-                                                            path.display(),
-                                                            e
-                                                        )
-                                                    }
-                                                }
-                                            }
-                                            Err(e) => eprintln!(
-                                                "Error reading file {}: {}",
-                                                path.display(),
-                                                e
-                                            ),
-                                        }
+        let mut remote = None;
+        let mut file = None;
+        let mut subscription = None;
+        let mut sync_problem = None;
+
+        let (name, color, data) = match cfg.kind {
+            CalendarType::VDIR => Self::read_vdir(Path::new(&cfg.path))?,
+            CalendarType::CalDAV => {
+                // Works with the cache if the server can't be reached
+                let caldav = remote.insert(CalDav::open(&cfg)?);
+                sync_problem = caldav.sync().err().map(|e| format!("{e:#}"));
+                Self::read_vdir(caldav.dir())?
+            }
+            CalendarType::File => {
+                let (ics, ical) = IcsFile::read(Path::new(&cfg.path))?;
+                file = Some(ics);
+
+                let name = configured_name(&cfg, &ical);
+                let color = parse_color(DEFAULT_CALENDAR_COLOR, &name)?;
+
+                (name, color, Self::split_file(ical)?)
+            }
+            CalendarType::Subscription => {
+                // Works with the cache if the data can't be fetched
+                let feed = subscription.insert(Subscription::open(&cfg)?);
+                if feed.next_refresh().is_zero() {
+                    sync_problem = feed.fetch().err().map(|e| format!("{e:#}"));
+                }
+
+                // Empty until the data was fetched once
+                let ical = match feed.cache().exists() {
+                    true => IcsFile::read(feed.cache())?.1,
+                    false => IcalCalendar::new(),
+                };
+
+                let name = configured_name(&cfg, &ical);
+                let color = parse_color(DEFAULT_CALENDAR_COLOR, &name)?;
+
+                (name, color, Self::split_file(ical)?)
+            }
+        };
+
+        let color = match &cfg.color {
+            Some(color) => parse_color(color, &name)?,
+            None => color,
+        };
+
+        Ok(Self {
+            name,
+            color,
+            config: cfg,
+            data,
+            dirty: HashSet::new(),
+            remote,
+            file,
+            subscription,
+            sync_problem,
+        })
+    }
+
+    /// Read the name, color and all .ics files of a calendar directory
+    fn read_vdir(dir_path: &Path) -> Result<(String, Rgb, CalData)> {
+        // Read all .ics files in directory
+        let entries = fs::read_dir(dir_path)?;
+        let mut cal: HashMap<String, (IcalCalendar, Calendar)> = HashMap::new();
+
+        // Get calendar display name from file
+        let name_path = dir_path.join("displayname");
+        let name = fs::read_to_string(name_path)?;
+
+        // Get and parse color configuration
+        let color_path = dir_path.join("color");
+        let color_string = fs::read_to_string(color_path)?;
+        let color = parse_color(&color_string, &name)?;
+
+        // Process all .ics files in the directory
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            // Check if it is a file and has .ics extension
+            if path.is_file()
+                && path
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
+            {
+                match File::open(&path) {
+                    Ok(f) => {
+                        let buf = BufReader::new(f);
+                        let ical = IcalParser::new(buf).last().and_then(|res| res.ok());
+
+                        match ical {
+                            Some(mut ical) => {
+                                if ical.todos.is_empty() && ical.events.is_empty() {
+                                    // Skip files without Todos and Events
+                                    continue;
+                                }
+
+                                timezone::scope_timezones(&mut ical);
+                                match ical.generate().parse() {
+                                    Ok(parsed_calendar) => {
+                                        cal.insert(
+                                            String::from(
+                                                path.file_name().unwrap().to_str().unwrap(),
+                                            )
+                                            .trim_end_matches(".ics")
+                                            .to_owned(), // WARN: File name should always exist and be valid
+                                            (ical, parsed_calendar),
+                                        );
                                     }
-                                    None => {
-                                        eprintln!(
-                                            "IcalParser: Error parsing file {}",
-                                            path.display(),
-                                        )
+                                    Err(e) => {
+                                        eprintln!("Error parsing file {}: {}", path.display(), e)
                                     }
                                 }
                             }
-                            Err(e) => eprintln!("Error reading file {}: {}", path.display(), e),
+                            None => {
+                                eprintln!("IcalParser: Error parsing file {}", path.display(),)
+                            }
                         }
                     }
+                    Err(e) => eprintln!("Error reading file {}: {}", path.display(), e),
                 }
+            }
+        }
 
-                Ok(Self {
-                    name,
-                    color,
-                    config: cfg,
-                    data: CalData::VDIR(cal),
-                })
+        Ok((name, color, CalData::VDIR(cal)))
+    }
+
+    /// Split the content of a single file calendar into one entry per task
+    ///
+    /// The events are kept in a separate entry (see [`FILE_EVENTS_ENTRY`]).
+    fn split_file(ical: IcalCalendar) -> Result<CalData> {
+        let mut entries: Vec<(String, IcalCalendar)> = vec![];
+        for todo in &ical.todos {
+            let name = file_name_for_uid(get_property(&todo.properties, "UID").unwrap_or("task"));
+
+            let mut file = name.clone();
+            let mut counter = 1;
+            while entries.iter().any(|(f, _)| *f == file) {
+                counter += 1;
+                file = format!("{name}-{counter}");
             }
+
+            entries.push((
+                file,
+                standalone_todo(&ical.properties, &ical.timezones, todo.clone()),
+            ));
+        }
+
+        if !ical.events.is_empty() {
+            entries.push((
+                FILE_EVENTS_ENTRY.to_owned(),
+                IcalCalendar {
+                    properties: ical.properties,
+                    timezones: ical.timezones,
+                    events: ical.events,
+                    ..Default::default()
+                },
+            ));
         }
+
+        let mut cal = HashMap::new();
+        for (file, ical) in entries {
+            let calendar = ical
+                .generate()
+                .parse()
+                .map_err(|e| eyre!("Could not update iCalendar from ical representation: {}", e))?;
+            cal.insert(file, (ical, calendar));
+        }
+
+        Ok(CalData::VDIR(cal))
     }
 
-    // Save changes to file
+    /// Save changes to file
+    ///
+    /// Only files modified since the last save are written, removed files are deleted.
+    /// CalDAV calendars write to their cache and upload the changes afterwards, changes which
+    /// can't be uploaded are kept for the next sync (see [`IsekCalendar::take_sync_problem`]).
+    /// Files which can't be written stay modified, so the next save writes them again.
     pub fn save(&mut self) -> Result<()> {
-        match self.config.kind {
-            CalendarType::VDIR => {
-                let dir_path = Path::new(&self.config.path);
+        let mut written = HashSet::new();
+        let res = self.write_files(&mut written);
+
+        // Changes which couldn't be written are kept for the next save
+        self.dirty.retain(|id| !written.contains(id));
 
-                if !dir_path.exists() {
-                    return Err(eyre!("Calendar path doesn't exist"));
+        if let Some(remote) = &mut self.remote {
+            remote.add_pending(written)?;
+
+            if res.is_ok() {
+                self.sync_problem = match remote.upload() {
+                    Ok(conflicts) if conflicts.is_empty() => None,
+                    Ok(conflicts) => Some(conflict_error(&conflicts).to_string()),
+                    Err(e) => Some(format!("Changes are uploaded later, {e:#}")),
                 };
+            }
+        }
+
+        res
+    }
+
+    /// Write the modified files, the names of the written (or deleted) ones are added to `written`
+    fn write_files(&self, written: &mut HashSet<String>) -> Result<()> {
+        if self.is_read_only() && !self.dirty.is_empty() {
+            return Err(eyre!("Calendar '{}' is read-only", self.name));
+        }
+        let dir_path = self.dir();
 
-                match &mut self.data {
-                    CalData::VDIR(cal) => {
-                        // Save each calendar as an .ics file
-                        for (id, (ical, calendar)) in cal {
-                            let filename = format!("{id}.ics");
-                            let ics_path: PathBuf = dir_path.join(filename);
+        if !dir_path.exists() {
+            return Err(eyre!("Calendar path doesn't exist"));
+        };
 
-                            let content = ical.generate();
-                            fs::write(&ics_path, content.clone()).with_context(|| {
+        match (&self.data, &self.file) {
+            // All tasks are written back into the single file
+            (CalData::VDIR(cal), Some(file)) => {
+                if !self.dirty.is_empty() {
+                    file.write(cal.values().map(|(ical, _)| ical))?;
+                    written.extend(self.dirty.iter().cloned());
+                }
+            }
+            (CalData::VDIR(cal), None) => {
+                // Save each modified calendar as an .ics file
+                for id in &self.dirty {
+                    let filename = format!("{id}.ics");
+                    let ics_path: PathBuf = dir_path.join(filename);
+
+                    match cal.get(id) {
+                        Some((ical, _)) => {
+                            let mut ical = ical.clone();
+                            timezone::unscope_timezones(&mut ical);
+                            fs::write(&ics_path, ical.generate()).with_context(|| {
                                 format!("Failed to write iCalendar file at {}", ics_path.display())
                             })?;
-
-                            *calendar = content.parse().map_err(|e| {
-                                eyre!("Could not update iCalendar from ical representation: {}", e)
-                            })?
                         }
-
-                        Ok(())
+                        None if ics_path.exists() => {
+                            fs::remove_file(&ics_path).with_context(|| {
+                                format!("Failed to remove iCalendar file at {}", ics_path.display())
+                            })?;
+                        }
+                        None => {}
                     }
+                    written.insert(id.clone());
                 }
             }
         }
+
+        Ok(())
+    }
+
+    /// Directory with the .ics files (the cache for CalDAV calendars, the file itself for single
+    /// file calendars)
+    fn dir(&self) -> PathBuf {
+        match &self.remote {
+            Some(remote) => remote.dir().to_owned(),
+            None => PathBuf::from(&self.config.path),
+        }
+    }
+
+    /// Check if the tasks of this calendar can't be changed (subscriptions)
+    pub fn is_read_only(&self) -> bool {
+        self.subscription.is_some()
+    }
+
+    /// URL or command a subscription is fetched from
+    pub fn subscription_source(&self) -> Option<String> {
+        self.subscription.as_ref().map(Subscription::source)
+    }
+
+    /// Problem of the last sync with the server (only returned once)
+    pub fn take_sync_problem(&mut self) -> Option<String> {
+        self.sync_problem
+            .take()
+            .map(|problem| format!("{}: {problem}", self.name))
+    }
+
+    /// Check if there are changes which were not saved yet
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
     }
 
-    pub fn get_todos(&self) -> Vec<IsekTodo> {
+    pub fn get_todos(&self) -> Vec<IsekTodo<'_>> {
         match &self.data {
             CalData::VDIR(cals) => cals
                 .iter()
@@ -312,6 +734,22 @@ impl IsekCalendar {
         }
     }
 
+    /// All events (including overridden occurrences of recurring events)
+    pub fn get_events(&self) -> Vec<IsekEvent<'_>> {
+        match &self.data {
+            CalData::VDIR(cals) => cals
+                .values()
+                .flat_map(|(ical, _)| &ical.events)
+                .map(|e| IsekEvent {
+                    cal_name: &self.name,
+                    cal_display_name: &self.config.display_name,
+                    color: &self.color,
+                    data: e,
+                })
+                .collect(),
+        }
+    }
+
     pub fn get_todo(&self, id: &str) -> Option<&Todo> {
         match &self.data {
             CalData::VDIR(cals) => cals.get(id).and_then(|(_, cals)| {
@@ -326,218 +764,502 @@ impl IsekCalendar {
         }
     }
 
-    pub fn toggle_done(&mut self, id: &str) -> Option<()> {
-        match &mut self.data {
-            CalData::VDIR(cals) => {
-                let (ical, cal) = cals.get_mut(id)?;
-                let todo = ical.todos.get_mut(0)?;
-
-                let completed_idx = todo.properties.iter().position(|p| p.name == "COMPLETED");
-                match completed_idx {
-                    // Task is marked complete => undo
-                    Some(idx) => {
-                        todo.properties.swap_remove(idx);
+    /// Name of the file containing the task with the given UID
+    pub fn file_of(&self, uid: &str) -> Option<String> {
+        match &self.data {
+            CalData::VDIR(cals) => cals
+                .iter()
+                .find(|(_, (ical, _))| ical.todos.iter().any(|t| has_uid(&t.properties, uid)))
+                .map(|(file, _)| file.clone()),
+        }
+    }
 
-                        if let Some(idx) = todo.properties.iter().position(|p| p.name == "STATUS") {
-                            todo.properties.swap_remove(idx);
-                        }
+    /// Current content of a file
+    pub fn file_snapshot(&self, file: &str) -> Option<IcalCalendar> {
+        match &self.data {
+            CalData::VDIR(cals) => cals.get(file).map(|(ical, _)| ical.clone()),
+        }
+    }
 
-                        if let Some(idx) = todo
-                            .properties
-                            .iter()
-                            .position(|p| p.name == "PERCENT-COMPLETE")
-                        {
-                            todo.properties.swap_remove(idx);
-                        }
+    /// Replace the content of a file (in memory, see [`IsekCalendar::save`])
+    ///
+    /// The file is removed if `content` is `None`
+    pub fn set_file(&mut self, file: &str, content: Option<IcalCalendar>) -> Result<()> {
+        match &mut self.data {
+            CalData::VDIR(cals) => {
+                match content {
+                    Some(ical) => {
+                        let calendar = ical.generate().parse().map_err(|e| {
+                            eyre!("Could not update iCalendar from ical representation: {}", e)
+                        })?;
+                        cals.insert(file.to_owned(), (ical, calendar));
                     }
-
-                    // Task is NOT marked complete => mark complete
                     None => {
-                        todo.properties.push(Property {
-                            name: String::from("COMPLETED"),
-                            params: None,
-                            value: Some(Utc::now().format(ICAL_UTC_DATE_TIME_FORMAT).to_string()),
-                        });
-
-                        match todo.properties.iter().position(|p| p.name == "STATUS") {
-                            Some(idx) => {
-                                todo.properties[idx].value = Some(String::from("COMPLETED"))
-                            }
-                            None => {
-                                todo.properties.push(Property {
-                                    name: String::from("STATUS"),
-                                    params: None,
-                                    value: Some(String::from("COMPLETED")),
-                                });
-                            }
-                        }
-
-                        match todo
-                            .properties
-                            .iter()
-                            .position(|p| p.name == "PERCENT-COMPLETE")
-                        {
-                            Some(idx) => todo.properties[idx].value = Some(100.to_string()),
-                            None => {
-                                todo.properties.push(Property {
-                                    name: String::from("PERCENT-COMPLETE"),
-                                    params: None,
-                                    value: Some(100.to_string()),
-                                });
-                            }
-                        }
+                        cals.remove(file);
                     }
                 }
 
-                // Update iCalendar Representation
-                *cal = ical.generate().parse().ok()?;
-
-                Some(())
+                self.dirty.insert(file.to_owned());
+                Ok(())
             }
         }
     }
-}
-
-/// Representation of all calendars
-#[derive(Debug)]
-pub struct IsekCalendars {
-    data: HashMap<String, IsekCalendar>,
-}
 
-impl IsekCalendars {
-    fn from_config(cfg: Vec<CalendarConfig>) -> Result<Self> {
-        Ok(Self {
-            data: cfg
-                .into_iter()
-                .map(IsekCalendar::from_config)
-                .map(|cal| cal.map(|cal| (cal.name.clone(), cal)))
-                .collect::<Result<HashMap<String, IsekCalendar>>>()?,
-        })
+    /// Modify the properties of the task with the given UID (in memory, see [`IsekCalendar::save`])
+    pub fn modify_todo<F>(&mut self, uid: &str, f: F) -> Option<()>
+    where
+        F: FnOnce(&mut Vec<Property>),
+    {
+        self.modify_ical_todo(uid, |todo| f(&mut todo.properties))
     }
 
-    pub fn get_todos(
-        &self,
-        sort: Option<&SortingConfig>,
-        filter: Option<&FilterConfig>,
-    ) -> Vec<IsekTodo> {
-        let mut todos: Vec<IsekTodo> = self
-            .data
-            .iter()
-            .flat_map(|(_, cal)| cal.get_todos())
-            .collect();
+    /// Modify the task with the given UID including its alarms (see [`IsekCalendar::modify_todo`])
+    pub fn modify_ical_todo<F>(&mut self, uid: &str, f: F) -> Option<()>
+    where
+        F: FnOnce(&mut IcalTodo),
+    {
+        let file = self.file_of(uid)?;
 
-        if let Some(filter) = filter {
-            match filter.show_done {
-                ShowDoneOptions::Hide => {
-                    todos.retain(|t| t.get().get_completed().is_none());
-                }
-                ShowDoneOptions::Some => {
-                    todos.retain(|t| {
-                        let completed = t.get().get_completed();
+        match &mut self.data {
+            CalData::VDIR(cals) => {
+                let (ical, cal) = cals.get_mut(&file)?;
+                let todo = ical
+                    .todos
+                    .iter_mut()
+                    .find(|t| has_uid(&t.properties, uid))?;
 
-                        if let Some(dt) = completed {
-                            let diff = Utc::now() - dt;
+                f(todo);
 
-                            diff.num_days() < filter.show_done_for as i64
-                        } else {
-                            true
-                        }
-                    });
-                }
-                ShowDoneOptions::Show => {}
+                // Update iCalendar Representation
+                *cal = ical.generate().parse().ok()?;
+                self.dirty.insert(file);
+
+                Some(())
             }
         }
+    }
 
-        if let Some(sort) = sort {
-            match sort.by {
-                SortingVariant::Date => {
-                    todos.sort_by(|a, b| {
-                        let a_due = a.get().get_due();
-                        let b_due = b.get().get_due();
-
-                        if a.get().get_completed().is_some() {
-                            if b.get().get_completed().is_some() {
-                                return Ordering::Equal;
-                            };
-
-                            return Ordering::Greater;
-                        }
+    /// Alarms (VALARM) of the task with the given UID
+    pub fn get_alarms(&self, uid: &str) -> Vec<&IcalAlarm> {
+        let Some(file) = self.file_of(uid) else {
+            return vec![];
+        };
 
-                        if b.get().get_completed().is_some() {
-                            return Ordering::Less;
-                        }
+        match &self.data {
+            CalData::VDIR(cals) => cals
+                .get(&file)
+                .into_iter()
+                .flat_map(|(ical, _)| &ical.todos)
+                .filter(|t| has_uid(&t.properties, uid))
+                .flat_map(|t| &t.alarms)
+                .collect(),
+        }
+    }
 
-                        if let Some(a_due) = a_due {
-                            if let Some(b_due) = b_due {
-                                return a_due.date_naive().cmp(&b_due.date_naive());
-                            };
+    /// Copy of the task with the given UID as standalone calendar
+    ///
+    /// The calendar contains the task and the timezones it references.
+    pub fn extract_todo(&self, uid: &str) -> Option<IcalCalendar> {
+        let file = self.file_of(uid)?;
+        let ical = self.file_snapshot(&file)?;
 
-                            return Ordering::Less;
-                        };
+        let todo = ical
+            .todos
+            .into_iter()
+            .find(|t| has_uid(&t.properties, uid))?;
 
-                        if b_due.is_none() {
-                            return Ordering::Greater;
-                        };
+        Some(standalone_todo(&ical.properties, &ical.timezones, todo))
+    }
 
-                        Ordering::Equal
-                    });
-                }
-                SortingVariant::Priority => {
-                    todos.sort_by(|a, b| {
-                        let a_prio = a.get().get_priority().unwrap_or(10);
-                        let b_prio = b.get().get_priority().unwrap_or(10);
+    /// Remove the task with the given UID (in memory, see [`IsekCalendar::save`])
+    ///
+    /// Returns the task (see [`IsekCalendar::extract_todo`]).
+    /// Files without any remaining tasks or events are removed.
+    pub fn take_todo(&mut self, uid: &str) -> Option<IcalCalendar> {
+        let extracted = self.extract_todo(uid)?;
 
-                        if a.get().get_completed().is_some() {
-                            if b.get().get_completed().is_some() {
-                                return Ordering::Equal;
-                            };
+        let file = self.file_of(uid)?;
+        let mut ical = self.file_snapshot(&file)?;
+        ical.todos.retain(|t| !has_uid(&t.properties, uid));
 
-                            return Ordering::Greater;
-                        }
+        let remaining = match ical.todos.is_empty() && ical.events.is_empty() {
+            true => None,
+            false => Some(ical),
+        };
+        self.set_file(&file, remaining).ok()?;
 
-                        if b.get().get_completed().is_some() {
-                            return Ordering::Less;
-                        }
+        Some(extracted)
+    }
 
-                        a_prio.cmp(&b_prio)
-                    });
-                }
-                SortingVariant::Index => {
-                    let now = Utc::now();
-
-                    todos.sort_by(|a, b| {
-                        let a_dt = a
-                            .get()
-                            .get_due()
-                            .map(ical_datetime_to_chrono)
-                            .unwrap_or(Utc::now());
-                        let a_prio = a.get().get_priority().unwrap_or(10);
-
-                        let b_dt = b
-                            .get()
-                            .get_due()
-                            .map(ical_datetime_to_chrono)
-                            .unwrap_or(Utc::now());
-                        let b_prio = b.get().get_priority().unwrap_or(10);
-
-                        calculate_index(&a_prio, &a_dt, &now)
-                            .total_cmp(&calculate_index(&b_prio, &b_dt, &now))
-                    });
-                }
-            };
+    /// Check if a task with the given UID exists in this calendar
+    pub fn contains_uid(&self, uid: &str) -> bool {
+        self.file_of(uid).is_some()
+    }
 
-            if !sort.ascending {
-                todos.reverse();
+    /// File name based on `name` which is not used in this calendar yet
+    pub fn free_file_name(&self, name: &str) -> String {
+        let exists = |file: &str| match &self.data {
+            CalData::VDIR(cals) => {
+                cals.contains_key(file)
+                    || self.dirty.contains(file)
+                    || self.dir().join(format!("{file}.ics")).exists()
             }
         };
 
-        todos
+        let mut file = name.to_owned();
+        let mut counter = 1;
+        while exists(&file) {
+            counter += 1;
+            file = format!("{name}-{counter}");
+        }
+
+        file
     }
 
-    pub fn get_todo(&self, calendar_id: &str, id: &str) -> Option<&Todo> {
-        if let Some(cal) = self.data.get(calendar_id) {
-            cal.get_todo(id)
-        } else {
+    pub fn toggle_done(&mut self, id: &str) -> Option<()> {
+        self.modify_todo(id, toggle_completed)
+    }
+}
+
+/// Check if the properties of a component contain the given UID
+fn has_uid(properties: &[Property], uid: &str) -> bool {
+    get_property(properties, "UID") == Some(uid)
+}
+
+/// Shift DUE and DTSTART of a task so it is due on the given day (see [`App::snooze`])
+fn snooze_todo(props: &mut Vec<Property>, until: NaiveDate) {
+    let reference = ["DUE", "DTSTART"]
+        .iter()
+        .find_map(|name| props.iter().find(|p| p.name == *name))
+        .and_then(property_local_date);
+
+    match reference {
+        Some(date) => {
+            let days = (until - date).num_days();
+            for prop in props
+                .iter_mut()
+                .filter(|p| p.name == "DUE" || p.name == "DTSTART")
+            {
+                shift_date_property(prop, days);
+            }
+        }
+        None => {
+            replace_property(props, date_property("DUE", &DatePerhapsTime::Date(until)));
+        }
+    }
+
+    set_property(
+        props,
+        "LAST-MODIFIED",
+        Utc::now().format(ICAL_UTC_DATE_TIME_FORMAT).to_string(),
+    );
+}
+
+/// Set the priority of a task (0 removes the property as it means undefined)
+fn set_priority(props: &mut Vec<Property>, priority: u32) {
+    match priority {
+        0 => {
+            remove_property(props, "PRIORITY");
+        }
+        p => {
+            set_property(props, "PRIORITY", p.to_string());
+        }
+    }
+}
+
+/// Calendar with the given properties containing only the task and the timezones it references
+fn standalone_todo(
+    properties: &[Property],
+    timezones: &[IcalTimeZone],
+    todo: IcalTodo,
+) -> IcalCalendar {
+    let timezones = timezones
+        .iter()
+        .filter(|tz| {
+            get_property(&tz.properties, "TZID")
+                .is_some_and(|tzid| references_timezone(&todo.properties, tzid))
+        })
+        .cloned()
+        .collect();
+
+    IcalCalendar {
+        properties: properties.to_vec(),
+        timezones,
+        todos: vec![todo],
+        ..Default::default()
+    }
+}
+
+/// Name of a calendar without a VDIR directory
+///
+/// The display name from the config, the name in the data (`X-WR-CALNAME`) or the file name.
+fn configured_name(cfg: &CalendarConfig, ical: &IcalCalendar) -> String {
+    let source = cfg.command.as_ref().unwrap_or(&cfg.path);
+
+    cfg.display_name
+        .clone()
+        .or_else(|| get_property(&ical.properties, "X-WR-CALNAME").map(str::to_owned))
+        .or_else(|| {
+            Path::new(source)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| source.clone())
+}
+
+/// Parse the color of a calendar given as hex string
+fn parse_color(color: &str, calendar: &str) -> Result<Rgb> {
+    Rgb::from_hex_str(color.trim()).map_err(|err| {
+        eyre!(
+            "Unable to read color for calendar '{}': {}",
+            calendar,
+            err.message
+        )
+    })
+}
+
+/// Generate a new unique UID for a task
+fn new_uid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Calendar file with a new task with the given UID and summary
+fn new_todo(uid: &str, summary: &str) -> IcalCalendar {
+    let now = Utc::now().format(ICAL_UTC_DATE_TIME_FORMAT).to_string();
+    // Characters with a special meaning in TEXT values (RFC 5545, 3.3.11)
+    let summary = summary
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,");
+
+    let mut todo = IcalTodo::new();
+    for (name, value) in [
+        ("UID", uid.to_owned()),
+        ("DTSTAMP", now.clone()),
+        ("CREATED", now.clone()),
+        ("LAST-MODIFIED", now),
+        ("SUMMARY", summary),
+        ("STATUS", "NEEDS-ACTION".to_owned()),
+    ] {
+        set_property(&mut todo.properties, name, value);
+    }
+
+    let mut ical = IcalCalendar::new();
+    set_property(&mut ical.properties, "VERSION", "2.0".to_owned());
+    set_property(
+        &mut ical.properties,
+        "PRODID",
+        "-//isek//isek-rs//EN".to_owned(),
+    );
+    ical.todos.push(todo);
+
+    ical
+}
+
+/// File name for a task in a VDIR (characters which are not safe in file names are replaced)
+fn file_name_for_uid(uid: &str) -> String {
+    uid.chars()
+        .map(|c| match c.is_ascii_alphanumeric() || "-_.@".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+/// Mark a task as completed or restore its previous state if it is already completed
+fn toggle_completed(props: &mut Vec<Property>) {
+    let completed = get_property(props, "COMPLETED").is_some();
+    set_completed(props, !completed);
+}
+
+/// Mark a task as completed (remembering the previous state) or restore its previous state
+pub fn set_completed(props: &mut Vec<Property>, completed: bool) {
+    let is_completed = get_property(props, "COMPLETED").is_some();
+
+    match (is_completed, completed) {
+        // Task is marked complete => restore previous state
+        (true, false) => {
+            remove_property(props, "COMPLETED");
+
+            for (name, backup) in COMPLETION_BACKUP_PROPERTIES {
+                match remove_property(props, backup) {
+                    Some(value) => {
+                        set_property(props, name, value);
+                    }
+                    None => {
+                        remove_property(props, name);
+                    }
+                }
+            }
+        }
+
+        // Task is NOT marked complete => mark complete and remember previous state
+        (false, true) => {
+            set_property(
+                props,
+                "COMPLETED",
+                Utc::now().format(ICAL_UTC_DATE_TIME_FORMAT).to_string(),
+            );
+
+            for ((name, backup), value) in COMPLETION_BACKUP_PROPERTIES
+                .into_iter()
+                .zip(["COMPLETED", "100"])
+            {
+                match set_property(props, name, value.to_owned()) {
+                    Some(previous) if previous != value => {
+                        set_property(props, backup, previous);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Representation of all calendars
+#[derive(Debug)]
+pub struct IsekCalendars {
+    data: HashMap<String, IsekCalendar>,
+    /// Scoring model for sorting by index
+    pub index: IndexConfig,
+}
+
+impl IsekCalendars {
+    /// Load all configured calendars
+    pub fn from_config(cfg: Vec<CalendarConfig>, index: IndexConfig) -> Result<Self> {
+        let calendars = Self {
+            index,
+            data: cfg
+                .into_iter()
+                .map(IsekCalendar::from_config)
+                .map(|cal| cal.map(|cal| (cal.name.clone(), cal)))
+                .collect::<Result<HashMap<String, IsekCalendar>>>()?,
+        };
+
+        Ok(calendars)
+    }
+
+    pub fn get_todos(
+        &self,
+        sort: Option<&SortingConfig>,
+        filter: Option<&FilterConfig>,
+    ) -> Vec<IsekTodo<'_>> {
+        let mut todos: Vec<IsekTodo> = self.data.values().flat_map(|cal| cal.get_todos()).collect();
+
+        if let Some(filter) = filter {
+            match filter.show_done {
+                ShowDoneOptions::Hide => {
+                    todos.retain(|t| t.get().get_completed().is_none());
+                }
+                ShowDoneOptions::Some => {
+                    todos.retain(|t| {
+                        let completed = t.get().get_completed();
+
+                        if let Some(dt) = completed {
+                            let diff = Utc::now() - dt;
+
+                            diff.num_days() < filter.show_done_for as i64
+                        } else {
+                            true
+                        }
+                    });
+                }
+                ShowDoneOptions::Show => {}
+            }
+
+            if filter.hide_deferred {
+                let now = Utc::now();
+                todos.retain(|t| !t.is_deferred(&now));
+            }
+        }
+
+        if let Some(sort) = sort {
+            sort_todos(&mut todos, sort, &self.index);
+        }
+
+        todos
+    }
+
+    /// Events of all calendars
+    pub fn get_events(&self) -> Vec<IsekEvent<'_>> {
+        self.data
+            .values()
+            .flat_map(|cal| cal.get_events())
+            .collect()
+    }
+
+    /// Reload all calendars from disk
+    pub fn reload(&mut self) -> Result<()> {
+        let configs = self.data.values().map(|cal| cal.config.clone()).collect();
+        *self = Self::from_config(configs, self.index.clone())?;
+
+        Ok(())
+    }
+
+    /// Time until the next subscription should be fetched again
+    pub fn next_refresh(&self) -> Option<Duration> {
+        self.data
+            .values()
+            .filter_map(|cal| cal.subscription.as_ref())
+            .map(Subscription::next_refresh)
+            .min()
+    }
+
+    /// Fetch the subscriptions which are due and reload them
+    ///
+    /// Subscriptions which can't be fetched keep their data, the problem is reported like sync
+    /// problems (see [`IsekCalendars::take_sync_problem`]).
+    pub fn refresh_subscriptions(&mut self) {
+        let due: Vec<String> = self
+            .data
+            .iter()
+            .filter(|(_, cal)| {
+                cal.subscription
+                    .as_ref()
+                    .is_some_and(|feed| feed.next_refresh().is_zero())
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        for name in due {
+            let Some(mut cal) = self.data.remove(&name) else {
+                continue;
+            };
+
+            let res = cal
+                .subscription
+                .as_mut()
+                .map_or(Ok(()), Subscription::fetch)
+                .and_then(|()| IsekCalendar::from_config(cal.config.clone()));
+
+            match res {
+                Ok(reloaded) => cal = reloaded,
+                Err(e) => cal.sync_problem = Some(format!("{e:#}")),
+            }
+
+            // The name can change with the data (X-WR-CALNAME)
+            self.data.insert(cal.name.clone(), cal);
+        }
+    }
+
+    /// Sync problems of all calendars (see [`IsekCalendar::take_sync_problem`])
+    pub fn take_sync_problem(&mut self) -> Option<String> {
+        let mut problems: Vec<String> = self
+            .data
+            .values_mut()
+            .filter_map(|cal| cal.take_sync_problem())
+            .collect();
+        problems.sort();
+
+        (!problems.is_empty()).then(|| problems.join("; "))
+    }
+
+    pub fn get_todo(&self, calendar_id: &str, id: &str) -> Option<&Todo> {
+        if let Some(cal) = self.data.get(calendar_id) {
+            cal.get_todo(id)
+        } else {
             None
         }
     }
@@ -549,6 +1271,146 @@ impl IsekCalendars {
             None
         }
     }
+
+    /// Name of the file containing a task
+    pub fn file_of(&self, id: &TaskId) -> Option<String> {
+        self.data.get(&id.calendar)?.file_of(&id.uid)
+    }
+
+    /// Current content of a file in a calendar
+    pub fn file_snapshot(&self, calendar_id: &str, file: &str) -> Option<IcalCalendar> {
+        self.data.get(calendar_id)?.file_snapshot(file)
+    }
+
+    /// Replace the content of a file in a calendar (see [`IsekCalendar::set_file`])
+    pub fn set_file(
+        &mut self,
+        calendar_id: &str,
+        file: &str,
+        content: Option<IcalCalendar>,
+    ) -> Result<()> {
+        self.get_calendar_mut(calendar_id)?.set_file(file, content)
+    }
+
+    /// Modify the properties of a task (see [`IsekCalendar::modify_todo`])
+    pub fn modify_todo<F>(&mut self, id: &TaskId, f: F) -> Option<()>
+    where
+        F: FnOnce(&mut Vec<Property>),
+    {
+        self.data.get_mut(&id.calendar)?.modify_todo(&id.uid, f)
+    }
+
+    /// Modify a task including its alarms (see [`IsekCalendar::modify_ical_todo`])
+    pub fn modify_ical_todo<F>(&mut self, id: &TaskId, f: F) -> Option<()>
+    where
+        F: FnOnce(&mut IcalTodo),
+    {
+        self.data
+            .get_mut(&id.calendar)?
+            .modify_ical_todo(&id.uid, f)
+    }
+
+    /// Alarms of a task (see [`IsekCalendar::get_alarms`])
+    pub fn get_alarms(&self, id: &TaskId) -> Vec<&IcalAlarm> {
+        self.data
+            .get(&id.calendar)
+            .map(|cal| cal.get_alarms(&id.uid))
+            .unwrap_or_default()
+    }
+
+    /// Delete a task (in memory, see [`IsekCalendars::save`])
+    pub fn delete_todo(&mut self, id: &TaskId) -> Option<()> {
+        self.data
+            .get_mut(&id.calendar)?
+            .take_todo(&id.uid)
+            .map(|_| ())
+    }
+
+    /// Move or copy a task into another calendar (in memory, see [`IsekCalendars::save`])
+    ///
+    /// A moved task keeps its UID and file name, a copy gets a new UID and DTSTAMP.
+    /// If the UID already exists in the target calendar, the task gets a new one as well.
+    /// The file name is changed if it is already used in the target calendar.
+    ///
+    /// Returns the task in the target calendar and its file name.
+    pub fn transfer_todo(
+        &mut self,
+        id: &TaskId,
+        target: &str,
+        copy: bool,
+    ) -> Result<(TaskId, String)> {
+        if id.calendar == target {
+            return Err(eyre!("Task is already in calendar '{}'", target));
+        }
+
+        let source_file = self.file_of(id).wrap_err("Could not find task")?;
+        let target_cal = self
+            .get_calendar(target)
+            .wrap_err_with(|| format!("Could not find calendar '{}'", target))?;
+
+        let uid = match copy || target_cal.contains_uid(&id.uid) {
+            true => new_uid(),
+            false => id.uid.clone(),
+        };
+        let file = match uid == id.uid {
+            true => target_cal.free_file_name(&source_file),
+            false => target_cal.free_file_name(&file_name_for_uid(&uid)),
+        };
+
+        let source = self.get_calendar_mut(&id.calendar)?;
+        let mut ical = match copy {
+            true => source.extract_todo(&id.uid),
+            false => source.take_todo(&id.uid),
+        }
+        .wrap_err("Could not find task")?;
+
+        if uid != id.uid {
+            let props = &mut ical.todos[0].properties;
+            set_property(props, "UID", uid.clone());
+            set_property(
+                props,
+                "DTSTAMP",
+                Utc::now().format(ICAL_UTC_DATE_TIME_FORMAT).to_string(),
+            );
+        }
+
+        self.set_file(target, &file, Some(ical))?;
+
+        Ok((
+            TaskId {
+                calendar: target.to_owned(),
+                uid,
+            },
+            file,
+        ))
+    }
+
+    /// Write all modified calendars to disk (once per calendar)
+    pub fn save(&mut self) -> Result<()> {
+        for cal in self.data.values_mut().filter(|cal| cal.is_dirty()) {
+            cal.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Names of all calendars (sorted)
+    pub fn names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = self.data.keys().collect();
+        names.sort();
+        names
+    }
+
+    /// Get a calendar by name
+    pub fn get_calendar(&self, calendar_id: &str) -> Option<&IsekCalendar> {
+        self.data.get(calendar_id)
+    }
+
+    fn get_calendar_mut(&mut self, calendar_id: &str) -> Result<&mut IsekCalendar> {
+        self.data
+            .get_mut(calendar_id)
+            .wrap_err_with(|| format!("Could not find calendar '{}'", calendar_id))
+    }
 }
 
 /// Main application state and logic
@@ -560,6 +1422,9 @@ pub struct App {
     /// Indicates the state, the application is in
     pub state: State,
 
+    /// Views shown as tabs
+    pub views: Views,
+
     /// All calendars in isek
     pub calendars: IsekCalendars,
 
@@ -568,20 +1433,106 @@ pub struct App {
 
     /// State for list navigation (selection)
     pub list_state: ListState,
+
+    /// Styles used to render the UI
+    pub theme: Theme,
+
+    /// Parsed template for list entries
+    pub line_format: LineFormat,
+
+    /// Focused column in the table layout
+    pub table_column: usize,
+
+    /// Area the tasks were rendered in last (used to map mouse positions to tasks)
+    pub task_area: Rect,
+
+    /// Area the status bar was rendered in last
+    pub status_bar_area: Rect,
+
+    /// Time and task index of the last click (used to detect double clicks)
+    last_click: Option<(Instant, usize)>,
+
+    /// Undo/redo history of task modifications
+    pub history: History,
+
+    /// Tasks marked for batch operations
+    pub marked: HashSet<TaskId>,
+
+    /// Text entered in the current prompt
+    pub input: String,
+
+    /// Message displayed in the status bar until the next key press
+    pub message: Option<String>,
+
+    /// Days shown in the agenda
+    pub agenda_range: AgendaRange,
+
+    /// State for scrolling the agenda
+    pub agenda_state: ListState,
+
+    /// Period shown in the calendar grid
+    pub calendar_mode: CalendarMode,
+
+    /// Day selected in the calendar grid
+    pub calendar_cursor: NaiveDate,
+
+    /// Selection in the list of tasks due on the selected day
+    pub calendar_list: ListState,
+
+    /// Task which is moved to another day in the calendar grid
+    pub rescheduling: Option<TaskId>,
+
+    /// Lines the task detail is scrolled down
+    pub detail_scroll: u16,
+
+    /// Area the task detail was rendered in last (used to scroll by pages)
+    pub detail_area: Rect,
+
+    /// Lines the help is scrolled down
+    pub help_scroll: u16,
+
+    /// Area the help was rendered in last (used to scroll by pages)
+    pub help_area: Rect,
 }
 
 impl App {
     /// Create and initialize a new application instance
     pub fn new() -> Result<Self> {
-        // Load configuration from file
-        let config: IsekConfig = confy::load("isek", "config")?;
+        Self::from_config(IsekConfig::load()?)
+    }
+
+    /// Create an application instance for the given configuration
+    pub fn from_config(config: IsekConfig) -> Result<Self> {
+        let line_format = config.display.line_format.parse()?;
+        let mut calendars = IsekCalendars::from_config(config.calendars, config.index)?;
 
         Ok(Self {
             exit: false,
             state: State::Normal,
-            calendars: IsekCalendars::from_config(config.calendars)?,
+            views: Views::default(),
+            message: calendars.take_sync_problem(),
+            calendars,
             display: config.display,
             list_state: ListState::default(),
+            theme: Theme::from_config(&config.theme)?,
+            line_format,
+            table_column: 0,
+            task_area: Rect::default(),
+            status_bar_area: Rect::default(),
+            last_click: None,
+            history: History::default(),
+            marked: HashSet::new(),
+            input: String::new(),
+            agenda_range: AgendaRange::default(),
+            agenda_state: ListState::default(),
+            calendar_mode: CalendarMode::default(),
+            calendar_cursor: local_today(),
+            calendar_list: ListState::default(),
+            rescheduling: None,
+            detail_scroll: 0,
+            detail_area: Rect::default(),
+            help_scroll: 0,
+            help_area: Rect::default(),
         })
     }
 
@@ -603,48 +1554,949 @@ impl App {
 
     /// Change sorting config (as long as the program runs) and switch back to normal mode
     pub fn configure_sort(&mut self, sort: SortingConfig) -> Result<()> {
+        let selection = self.selection();
+
         self.display.sort = sort;
-        self.escape();
+        self.state = State::Normal;
 
+        self.restore_selection(selection);
         Ok(())
     }
 
     /// Change filter config (as long as the program runs) and switch back to normal mode
     pub fn configure_filter(&mut self, filter: FilterConfig) -> Result<()> {
+        let selection = self.selection();
+
         self.display.filter = filter;
-        self.escape();
+        self.state = State::Normal;
 
+        self.restore_selection(selection);
+        Ok(())
+    }
+
+    /// Reload all calendars from disk
+    pub fn reload(&mut self) -> Result<()> {
+        let selection = self.selection();
+
+        self.calendars.reload()?;
+
+        self.restore_selection(selection);
+        Ok(())
+    }
+
+    /// Fetch the subscriptions which are due (see [`IsekCalendars::refresh_subscriptions`])
+    pub fn refresh_subscriptions(&mut self) {
+        let selection = self.selection();
+
+        self.calendars.refresh_subscriptions();
+
+        self.restore_selection(selection);
+    }
+
+    /// Identifier and index of the currently selected task
+    pub fn selection(&self) -> Option<(Option<TaskId>, usize)> {
+        let idx = self.list_state.selected()?;
+        let tasks = self
+            .calendars
+            .get_todos(Some(&self.display.sort), Some(&self.display.filter));
+
+        Some((tasks.get(idx).and_then(|t| t.id()), idx))
+    }
+
+    /// Select the task with the given identifier after the list changed
+    ///
+    /// If the task is not displayed anymore the task now at the previous index
+    /// (or the last one) is selected.
+    pub fn restore_selection(&mut self, selection: Option<(Option<TaskId>, usize)>) {
+        let Some((id, idx)) = selection else {
+            return;
+        };
+
+        let tasks = self
+            .calendars
+            .get_todos(Some(&self.display.sort), Some(&self.display.filter));
+
+        let new_idx = id
+            .and_then(|id| tasks.iter().position(|t| t.id().as_ref() == Some(&id)))
+            .or_else(|| match tasks.len() {
+                0 => None,
+                len => Some(idx.min(len - 1)),
+            });
+
+        self.list_state.select(new_idx);
+    }
+
+    /// Switch between list and table layout
+    pub fn toggle_layout(&mut self) {
+        self.display.layout = match self.display.layout {
+            LayoutVariant::List => LayoutVariant::Table,
+            LayoutVariant::Table => LayoutVariant::List,
+        }
+    }
+
+    /// Move the column focus in the table layout to the next or previous column
+    pub fn select_column(&mut self, next: bool) {
+        let count = self.display.table_columns.len();
+
+        if count == 0 {
+            return;
+        }
+
+        self.table_column = match next {
+            true => (self.table_column + 1) % count,
+            false => (self.table_column + count - 1) % count,
+        }
+    }
+
+    /// Change the width of the focused column (columns without fixed width are ignored)
+    pub fn resize_column(&mut self, delta: i16) {
+        if let Some(TableColumnConfig {
+            width: Some(width), ..
+        }) = self.display.table_columns.get_mut(self.table_column)
+        {
+            *width = width.saturating_add_signed(delta).max(1);
+        }
+    }
+
+    /// Sort by the field of a table column
+    /// If the tasks are already sorted by this column the direction is reversed
+    pub fn sort_by_column(&mut self, idx: usize) -> Result<()> {
+        let Some(column) = self.display.table_columns.get(idx) else {
+            return Ok(());
+        };
+
+        let Some(by) = SortingVariant::from_field(column.field) else {
+            return Ok(());
+        };
+
+        self.table_column = idx;
+        self.configure_sort(SortingConfig {
+            ascending: by != self.display.sort.by || !self.display.sort.ascending,
+            by,
+            ..self.display.sort.clone()
+        })
+    }
+
+    /// Select the task at the given index
+    /// Toggles the task on double click or if `toggle` is set
+    pub fn click_task(&mut self, idx: usize, toggle: bool) -> Result<()> {
+        let count = self
+            .calendars
+            .get_todos(Some(&self.display.sort), Some(&self.display.filter))
+            .len();
+
+        if idx >= count {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let double_click = self
+            .last_click
+            .is_some_and(|(time, last)| last == idx && now - time < DOUBLE_CLICK_TIME);
+
+        self.list_state.select(Some(idx));
+
+        if toggle || double_click {
+            self.last_click = None;
+            return self.toggle_done();
+        }
+
+        self.last_click = Some((now, idx));
         Ok(())
     }
 
     /// Mark currently selected task as done
     /// If already completed mark as uncompleted
     pub fn toggle_done(&mut self) -> Result<()> {
-        match self.list_state.selected() {
-            Some(task_idx) => {
-                let tasks = self
-                    .calendars
-                    .get_todos(Some(&self.display.sort), Some(&self.display.filter));
-
-                match tasks.get(task_idx) {
-                    Some(task) => {
-                        let cal_id = task.cal_name.clone();
-                        let task_id = task.get().get_uid().wrap_err("Task has no UID")?.to_owned();
-
-                        self.calendars.toggle_done(&cal_id, &task_id);
-
-                        let cal = self.calendars.data.get_mut(&cal_id).wrap_err(format!(
-                            "Unable to save changes! Could not find calendar '{}'.",
-                            cal_id
-                        ))?;
-                        cal.save()?;
-
-                        Ok(())
-                    }
-                    None => Ok(()),
+        let selection = self.selection();
+
+        let res = self.toggle_selected_done();
+        self.restore_selection(selection);
+
+        res
+    }
+
+    fn toggle_selected_done(&mut self) -> Result<()> {
+        let Some(id) = self.selection().and_then(|(id, _)| id) else {
+            return Ok(());
+        };
+
+        self.modify(std::slice::from_ref(&id), |app| {
+            app.calendars
+                .toggle_done(&id.calendar, &id.uid)
+                .wrap_err("Unable to toggle task")?;
+
+            let cal = app.calendars.data.get_mut(&id.calendar).wrap_err(format!(
+                "Unable to save changes! Could not find calendar '{}'.",
+                id.calendar
+            ))?;
+            cal.save()
+        })
+    }
+
+    /// Apply a modification of the given tasks and record it in the history
+    ///
+    /// The content of the files containing the tasks is saved before and after the
+    /// modification so it can be undone.
+    pub fn modify<F>(&mut self, tasks: &[TaskId], f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Self) -> Result<()>,
+    {
+        if let Some(message) = self.read_only_message(tasks.iter().map(|id| id.calendar.as_str())) {
+            self.message = Some(message);
+            return Ok(());
+        }
+
+        let files = tasks
+            .iter()
+            .filter_map(|id| Some((id.calendar.clone(), self.calendars.file_of(id)?)))
+            .collect::<Vec<(String, String)>>();
+
+        self.modify_files(&files, f)
+    }
+
+    /// Apply a modification of the given files (calendar name and file name) and record it in the history
+    ///
+    /// Failed modifications and modifications which didn't change any file are not recorded.
+    pub fn modify_files<F>(&mut self, files: &[(String, String)], f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Self) -> Result<()>,
+    {
+        // Every file is only recorded once (e.g. if it contains multiple modified tasks)
+        let files = files.iter().fold(vec![], |mut files, file| {
+            if !files.contains(file) {
+                files.push(file.clone());
+            }
+            files
+        });
+
+        let before = files
+            .iter()
+            .map(|(cal, file)| self.calendars.file_snapshot(cal, file))
+            .collect::<Vec<Option<IcalCalendar>>>();
+
+        f(self)?;
+
+        let changes = files
+            .iter()
+            .zip(before)
+            .map(|((cal, file), before)| FileChange {
+                calendar: cal.clone(),
+                file: file.clone(),
+                before,
+                after: self.calendars.file_snapshot(cal, file),
+            })
+            .collect();
+
+        self.history.push(changes);
+        Ok(())
+    }
+
+    /// Message explaining why tasks can't be changed if any of the calendars is read-only
+    fn read_only_message<'a>(
+        &self,
+        mut calendars: impl Iterator<Item = &'a str>,
+    ) -> Option<String> {
+        calendars
+            .find(|name| {
+                self.calendars
+                    .get_calendar(name)
+                    .is_some_and(IsekCalendar::is_read_only)
+            })
+            .map(|name| format!("'{name}' is a read-only subscription, its tasks can't be changed"))
+    }
+
+    /// Revert the last modification
+    pub fn undo(&mut self) -> Result<()> {
+        let Some(changes) = self.history.undo() else {
+            return Ok(());
+        };
+
+        let selection = self.selection();
+        for change in changes.into_iter().rev() {
+            self.calendars
+                .set_file(&change.calendar, &change.file, change.before)?;
+        }
+        self.calendars.save()?;
+        self.restore_selection(selection);
+
+        Ok(())
+    }
+
+    /// Apply the last reverted modification again
+    pub fn redo(&mut self) -> Result<()> {
+        let Some(changes) = self.history.redo() else {
+            return Ok(());
+        };
+
+        let selection = self.selection();
+        for change in changes {
+            self.calendars
+                .set_file(&change.calendar, &change.file, change.after)?;
+        }
+        self.calendars.save()?;
+        self.restore_selection(selection);
+
+        Ok(())
+    }
+
+    /// Mark or unmark the selected task for batch operations
+    pub fn toggle_mark(&mut self) {
+        if let Some((Some(id), _)) = self.selection()
+            && !self.marked.remove(&id)
+        {
+            self.marked.insert(id);
+        }
+    }
+
+    /// Start selecting a range of tasks at the selected task
+    pub fn start_visual(&mut self) {
+        if let Some(idx) = self.list_state.selected() {
+            self.state = State::Visual(idx);
+        }
+    }
+
+    /// Mark all tasks between the start of the range and the selected task
+    pub fn finish_visual(&mut self) {
+        if let (State::Visual(anchor), Some(idx)) = (&self.state, self.list_state.selected()) {
+            let range = *anchor.min(&idx)..=*anchor.max(&idx);
+
+            let tasks = self
+                .calendars
+                .get_todos(Some(&self.display.sort), Some(&self.display.filter));
+            let ids = tasks[..]
+                .get(range)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|t| t.id())
+                .collect::<Vec<TaskId>>();
+
+            self.marked.extend(ids);
+        }
+
+        self.state = State::Normal;
+    }
+
+    /// Mark all tasks matching the current filter
+    pub fn mark_all(&mut self) {
+        let ids = self
+            .calendars
+            .get_todos(Some(&self.display.sort), Some(&self.display.filter))
+            .iter()
+            .filter_map(|t| t.id())
+            .collect::<Vec<TaskId>>();
+
+        self.marked.extend(ids);
+    }
+
+    /// Check if the task at the given index is marked (or inside the current visual range)
+    pub fn is_marked(&self, idx: usize, id: Option<TaskId>) -> bool {
+        if let (State::Visual(anchor), Some(selected)) = (&self.state, self.list_state.selected())
+            && (*anchor.min(&selected)..=*anchor.max(&selected)).contains(&idx)
+        {
+            return true;
+        }
+
+        id.is_some_and(|id| self.marked.contains(&id))
+    }
+
+    /// Tasks affected by batch operations (marked tasks or the selected task if nothing is marked)
+    pub fn targets(&self) -> Vec<TaskId> {
+        if !self.marked.is_empty() {
+            let mut targets: Vec<TaskId> = self.marked.iter().cloned().collect();
+            targets.sort_by(|a, b| (&a.calendar, &a.uid).cmp(&(&b.calendar, &b.uid)));
+            return targets;
+        }
+
+        self.selection()
+            .and_then(|(id, _)| id)
+            .into_iter()
+            .collect()
+    }
+
+    /// Show how the index of the selected task is calculated
+    pub fn explain_index(&mut self) {
+        let todos = self
+            .calendars
+            .get_todos(Some(&self.display.sort), Some(&self.display.filter));
+
+        let explanation = self
+            .list_state
+            .selected()
+            .and_then(|idx| todos.get(idx))
+            .map(|t| IndexScore::new(t, &self.calendars.index, &Utc::now()).to_string());
+
+        self.message = explanation;
+    }
+
+    /// Open a prompt for entering a value
+    pub fn open_prompt(&mut self, prompt: Prompt) {
+        self.input.clear();
+        self.state = State::Prompt(prompt);
+    }
+
+    /// Apply the value entered in the current prompt
+    pub fn submit_prompt(&mut self) -> Result<()> {
+        let State::Prompt(prompt) = self.state else {
+            return Ok(());
+        };
+
+        self.state = State::Normal;
+        let input = std::mem::take(&mut self.input);
+
+        match prompt {
+            Prompt::Priority => match input.trim().parse::<u32>() {
+                Ok(priority @ 0..=9) => self.batch_priority(priority),
+                _ => {
+                    self.message = Some(format!("Invalid priority '{}'", input));
+                    Ok(())
+                }
+            },
+            Prompt::Due => match parse_date_input(&input, local_today()) {
+                Some(due) => self.batch_due(due),
+                None => {
+                    self.message = Some(format!("Invalid date '{}'", input));
+                    Ok(())
+                }
+            },
+            Prompt::Start if input.trim().is_empty() => self.batch_start(None),
+            Prompt::Start => match parse_date_input(&input, local_today()) {
+                Some(start) => self.batch_start(Some(start)),
+                None => {
+                    self.message = Some(format!("Invalid date '{}'", input));
+                    Ok(())
+                }
+            },
+            Prompt::Snooze { overdue } => match parse_date_input(&input, local_today()) {
+                Some(DatePerhapsTime::Date(until)) => self.snooze(until, overdue),
+                Some(until) => {
+                    let until = to_local(&ical_datetime_to_chrono(until));
+                    self.snooze(until.date(), overdue)
+                }
+                None => {
+                    self.message = Some(format!("Invalid date '{}'", input));
+                    Ok(())
+                }
+            },
+            Prompt::Tag => match input.trim() {
+                "" => Ok(()),
+                tag => self.batch_add_tag(tag.to_owned()),
+            },
+            Prompt::Alarm => match AlarmTrigger::parse(&input, local_today()) {
+                Some(trigger) => self.batch_add_alarm(trigger),
+                None => {
+                    self.message = Some(format!("Invalid alarm '{}'", input));
+                    Ok(())
                 }
+            },
+            Prompt::New => match input.trim() {
+                "" => Ok(()),
+                summary => self.create_task(summary),
+            },
+        }
+    }
+
+    /// Create a task with the given summary and select it
+    ///
+    /// The task is added to the calendar of the selected task, or to the first calendar (by
+    /// name) if no task is selected. Read-only subscriptions are skipped.
+    pub fn create_task(&mut self, summary: &str) -> Result<()> {
+        let writable = |name: &String| {
+            self.calendars
+                .get_calendar(name)
+                .is_some_and(|cal| !cal.is_read_only())
+        };
+        let selection = self.selection();
+        let calendar = selection
+            .as_ref()
+            .and_then(|(id, _)| id.as_ref())
+            .map(|id| &id.calendar)
+            .filter(|name| writable(name))
+            .or_else(|| {
+                self.calendars
+                    .names()
+                    .into_iter()
+                    .find(|name| writable(name))
+            })
+            .cloned();
+
+        let Some(calendar) = calendar else {
+            self.message = Some(String::from("No calendar for new tasks"));
+            return Ok(());
+        };
+
+        let uid = new_uid();
+        let file = self
+            .calendars
+            .get_calendar(&calendar)
+            .map(|cal| cal.free_file_name(&file_name_for_uid(&uid)))
+            .unwrap_or_default();
+        let files = [(calendar.clone(), file.clone())];
+
+        let res = self.modify_files(&files, |app| {
+            app.calendars
+                .set_file(&calendar, &file, Some(new_todo(&uid, summary)))?;
+            app.calendars.save()
+        });
+
+        let idx = selection.map(|(_, idx)| idx).unwrap_or_default();
+        self.restore_selection(Some((Some(TaskId { calendar, uid }), idx)));
+
+        res
+    }
+
+    /// Apply a modification to all target tasks, save once per calendar and record it in the history
+    fn batch<F>(&mut self, f: F) -> Result<()>
+    where
+        F: Fn(&mut IsekCalendars, &TaskId),
+    {
+        let targets = self.targets();
+        self.batch_tasks(targets, f)
+    }
+
+    /// Apply a modification to the given tasks (see [`App::batch`])
+    fn batch_tasks<F>(&mut self, targets: Vec<TaskId>, f: F) -> Result<()>
+    where
+        F: Fn(&mut IsekCalendars, &TaskId),
+    {
+        let selection = self.selection();
+
+        let res = self.modify(&targets, |app| {
+            for id in &targets {
+                f(&mut app.calendars, id);
+            }
+
+            app.calendars.save()
+        });
+
+        self.marked.clear();
+        self.state = State::Normal;
+        self.restore_selection(selection);
+
+        res
+    }
+
+    /// Mark all target tasks as completed or not completed
+    pub fn batch_set_completed(&mut self, completed: bool) -> Result<()> {
+        self.batch(|cals, id| {
+            cals.modify_todo(id, |props| set_completed(props, completed));
+        })
+    }
+
+    /// Delete all target tasks
+    pub fn batch_delete(&mut self) -> Result<()> {
+        self.batch(|cals, id| {
+            cals.delete_todo(id);
+        })
+    }
+
+    /// Set the priority of all target tasks (0 removes the priority)
+    pub fn batch_priority(&mut self, priority: u32) -> Result<()> {
+        self.batch(|cals, id| {
+            cals.modify_todo(id, |props| set_priority(props, priority));
+        })
+    }
+
+    /// Raise or lower the priority of the target tasks by one step (see [`raise_priority`])
+    ///
+    /// Unlike batch operations, marks are kept so the priority can be changed repeatedly.
+    pub fn step_priority(&mut self, raise: bool) -> Result<()> {
+        let targets = self.targets();
+        let selection = self.selection();
+
+        let res = self.modify(&targets, |app| {
+            for id in &targets {
+                app.calendars.modify_todo(id, |props| {
+                    let priority = get_property(props, "PRIORITY")
+                        .and_then(|p| p.parse().ok())
+                        .unwrap_or(0);
+
+                    set_priority(
+                        props,
+                        match raise {
+                            true => raise_priority(priority),
+                            false => lower_priority(priority),
+                        },
+                    );
+                });
+            }
+
+            app.calendars.save()
+        });
+
+        self.restore_selection(selection);
+        res
+    }
+
+    /// Set the due date of all target tasks
+    pub fn batch_due(&mut self, due: DatePerhapsTime) -> Result<()> {
+        self.batch(|cals, id| {
+            cals.modify_todo(id, |props| {
+                replace_property(props, date_property("DUE", &due));
+            });
+        })
+    }
+
+    /// Add a display alarm to all target tasks
+    pub fn batch_add_alarm(&mut self, trigger: AlarmTrigger) -> Result<()> {
+        self.batch(|cals, id| {
+            cals.modify_ical_todo(id, |todo| {
+                let summary = get_property(&todo.properties, "SUMMARY").unwrap_or_default();
+                let alarm = trigger.to_alarm(summary);
+                todo.alarms.push(alarm);
+            });
+        })
+    }
+
+    /// Remove all alarms of the target tasks
+    pub fn batch_remove_alarms(&mut self) -> Result<()> {
+        self.batch(|cals, id| {
+            cals.modify_ical_todo(id, |todo| todo.alarms.clear());
+        })
+    }
+
+    /// Postpone the target tasks (or all overdue tasks of writable calendars) so they are due on
+    /// the given day
+    ///
+    /// DUE and DTSTART are shifted by the same number of days, keeping the time of day and the
+    /// duration. Tasks without any date get the day as due date.
+    pub fn snooze(&mut self, until: NaiveDate, overdue: bool) -> Result<()> {
+        let targets = match overdue {
+            true => {
+                // Tasks of read-only subscriptions can't be snoozed
+                let now = Utc::now();
+                self.calendars
+                    .get_todos(None, None)
+                    .iter()
+                    .filter(|t| t.is_overdue(&now))
+                    .filter(|t| {
+                        self.calendars
+                            .get_calendar(t.cal_name)
+                            .is_some_and(|cal| !cal.is_read_only())
+                    })
+                    .filter_map(|t| t.id())
+                    .collect()
+            }
+            false => self.targets(),
+        };
+
+        if overdue && targets.is_empty() {
+            self.message = Some(String::from("No overdue tasks"));
+            self.state = State::Normal;
+            return Ok(());
+        }
+
+        self.batch_tasks(targets, |cals, id| {
+            cals.modify_todo(id, |props| snooze_todo(props, until));
+        })
+    }
+
+    /// Tasks due on a day, sorted and filtered like the task list
+    pub fn tasks_due_on(&self, date: NaiveDate) -> Vec<IsekTodo<'_>> {
+        let mut todos = self
+            .calendars
+            .get_todos(Some(&self.display.sort), Some(&self.display.filter));
+        todos.retain(|t| t.due_date() == Some(date));
+
+        todos
+    }
+
+    /// Move the cursor of the calendar grid and select the first task of the day
+    pub fn move_calendar_cursor(&mut self, date: NaiveDate) {
+        self.calendar_cursor = date;
+        self.calendar_list
+            .select((!self.tasks_due_on(date).is_empty()).then_some(0));
+    }
+
+    /// Pick up the task selected in the calendar side list, or move the picked up task to the
+    /// selected day (keeping the time, the start date is shifted along)
+    pub fn reschedule(&mut self) -> Result<()> {
+        let Some(id) = self.rescheduling.take() else {
+            self.rescheduling = self
+                .calendar_list
+                .selected()
+                .and_then(|idx| self.tasks_due_on(self.calendar_cursor).get(idx)?.id());
+            return Ok(());
+        };
+
+        let date = self.calendar_cursor;
+        let res = self.modify(std::slice::from_ref(&id), |app| {
+            app.calendars
+                .modify_todo(&id, |props| snooze_todo(props, date));
+            app.calendars.save()
+        });
+
+        // Keep the moved task selected
+        let idx = self
+            .tasks_due_on(date)
+            .iter()
+            .position(|t| t.id().as_ref() == Some(&id));
+        self.calendar_list.select(idx);
+
+        res
+    }
+
+    /// Set (or remove) the start date of all target tasks
+    pub fn batch_start(&mut self, start: Option<DatePerhapsTime>) -> Result<()> {
+        self.batch(|cals, id| {
+            cals.modify_todo(id, |props| match &start {
+                Some(start) => {
+                    replace_property(props, date_property("DTSTART", start));
+                }
+                None => {
+                    remove_property(props, "DTSTART");
+                }
+            });
+        })
+    }
+
+    /// Add a tag (CATEGORIES) to all target tasks
+    pub fn batch_add_tag(&mut self, tag: String) -> Result<()> {
+        self.batch(|cals, id| {
+            cals.modify_todo(id, |props| {
+                let exists = props
+                    .iter()
+                    .filter(|p| p.name == "CATEGORIES")
+                    .filter_map(|p| p.value.as_deref())
+                    .flat_map(|v| v.split(','))
+                    .any(|t| t.trim() == tag);
+
+                if !exists {
+                    props.push(Property {
+                        name: String::from("CATEGORIES"),
+                        params: None,
+                        value: Some(tag.clone()),
+                    });
+                }
+            });
+        })
+    }
+
+    /// Move or copy all target tasks to another calendar
+    pub fn batch_transfer(&mut self, target: &str, copy: bool) -> Result<()> {
+        let targets = self.targets();
+        let selection = self.selection();
+
+        // Tasks of subscriptions can only be copied
+        let changed = targets
+            .iter()
+            .filter(|_| !copy)
+            .map(|id| id.calendar.as_str())
+            .chain([target]);
+        if let Some(message) = self.read_only_message(changed) {
+            self.message = Some(message);
+            self.state = State::Normal;
+            return Ok(());
+        }
+
+        // Files in the source calendars (only modified when moving)
+        let sources = targets
+            .iter()
+            .filter_map(|id| Some((id.calendar.clone(), self.calendars.file_of(id)?)))
+            .fold(vec![], |mut files, file| {
+                if !copy && !files.contains(&file) {
+                    files.push(file);
+                }
+                files
+            });
+        let before = sources
+            .iter()
+            .map(|(cal, file)| self.calendars.file_snapshot(cal, file))
+            .collect::<Vec<Option<IcalCalendar>>>();
+
+        let mut created = vec![];
+        let mut errors = vec![];
+        for id in &targets {
+            match self.calendars.transfer_todo(id, target, copy) {
+                Ok((_, file)) => created.push((target.to_owned(), file)),
+                Err(e) => errors.push(e.to_string()),
             }
-            None => Ok(()),
         }
+
+        // Files in the target calendar are always new
+        let changes = sources
+            .into_iter()
+            .zip(before)
+            .chain(created.into_iter().map(|file| (file, None)))
+            .map(|((calendar, file), before)| FileChange {
+                after: self.calendars.file_snapshot(&calendar, &file),
+                calendar,
+                file,
+                before,
+            })
+            .collect();
+        self.history.push(changes);
+
+        if let Some(error) = errors.first() {
+            self.message = Some(match errors.len() {
+                1 => error.clone(),
+                n => format!("{} ({} more errors)", error, n - 1),
+            });
+        }
+
+        let res = self.calendars.save();
+
+        self.marked.clear();
+        self.state = State::Normal;
+        self.restore_selection(selection);
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use chrono_tz::Tz;
+
+    use super::*;
+    use crate::helper::set_local_timezone;
+
+    /// Empty directory for a test
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("isek-app-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Calendar directory with the given name and a task for every (UID, properties) pair
+    fn calendar(dir: &Path, name: &str, tasks: &[(&str, &str)]) -> CalendarConfig {
+        let cal = dir.join(name);
+        fs::create_dir_all(&cal).unwrap();
+        fs::write(cal.join("displayname"), name).unwrap();
+        fs::write(cal.join("color"), "#3A7BD5").unwrap();
+
+        for (uid, properties) in tasks {
+            let data = format!(
+                "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:test\r\nBEGIN:VTODO\r\nUID:{uid}\r\n\
+                 SUMMARY:Task {uid}\r\n{}\r\nEND:VTODO\r\nEND:VCALENDAR\r\n",
+                properties.trim().replace('\n', "\r\n")
+            );
+            fs::write(cal.join(format!("{uid}.ics")), data).unwrap();
+        }
+
+        CalendarConfig {
+            kind: CalendarType::VDIR,
+            path: cal.to_str().unwrap().to_owned(),
+            display_name: None,
+            color: None,
+            credentials: None,
+            command: None,
+            refresh: None,
+        }
+    }
+
+    /// Application with the given calendars
+    fn app(calendars: Vec<CalendarConfig>) -> App {
+        set_local_timezone(Tz::Europe__Berlin);
+
+        App::from_config(IsekConfig {
+            calendars,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    /// DUE of a task (`None` if the task or the property doesn't exist)
+    fn due(app: &App, calendar: &str, uid: &str) -> Option<String> {
+        let CalData::VDIR(cals) = &app.calendars.get_calendar(calendar)?.data;
+        let (ical, _) = cals.get(uid)?;
+        get_property(&ical.todos[0].properties, "DUE").map(str::to_owned)
+    }
+
+    #[test]
+    fn snoozing_overdue_tasks_skips_read_only_calendars() {
+        let dir = temp_dir("snooze");
+        let yesterday = (local_today() - TimeDelta::days(1)).format("%Y%m%d");
+        let overdue = format!("DUE;VALUE=DATE:{yesterday}");
+        let mut app = app(vec![
+            calendar(&dir, "Writable", &[("mine", &overdue)]),
+            calendar(&dir, "Subscribed", &[("theirs", &overdue)]),
+        ]);
+
+        // Tasks of subscriptions are read-only
+        let subscribed = app.calendars.data.get_mut("Subscribed").unwrap();
+        subscribed.subscription =
+            Some(Subscription::open_in(&subscribed.config, &dir.join("cache")).unwrap());
+
+        let until = local_today() + TimeDelta::days(3);
+        app.snooze(until, true).unwrap();
+
+        let until = until.format("%Y%m%d").to_string();
+        assert_eq!(due(&app, "Writable", "mine"), Some(until));
+        assert_eq!(
+            due(&app, "Subscribed", "theirs"),
+            Some(yesterday.to_string())
+        );
+        assert!(app.message.is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn changes_which_cant_be_saved_are_kept() {
+        let dir = temp_dir("save");
+        let cfg = calendar(&dir, "Tasks", &[("task", "")]);
+        let path = PathBuf::from(&cfg.path);
+        let mut calendars = IsekCalendars::from_config(vec![cfg], IndexConfig::default()).unwrap();
+        let id = TaskId {
+            calendar: "Tasks".to_owned(),
+            uid: "task".to_owned(),
+        };
+
+        calendars.modify_todo(&id, |props| {
+            set_property(props, "SUMMARY", "Changed".to_owned());
+        });
+
+        // Missing directory
+        let moved = dir.join("moved");
+        fs::rename(&path, &moved).unwrap();
+        assert!(calendars.save().is_err());
+        assert!(calendars.data["Tasks"].is_dirty());
+
+        fs::rename(&moved, &path).unwrap();
+        calendars.save().unwrap();
+        assert!(!calendars.data["Tasks"].is_dirty());
+        let content = fs::read_to_string(path.join("task.ics")).unwrap();
+        assert!(content.contains("SUMMARY:Changed"));
+
+        // Read-only calendar
+        let cal = calendars.data.get_mut("Tasks").unwrap();
+        cal.subscription = Some(Subscription::open_in(&cal.config, &dir.join("cache")).unwrap());
+        calendars.modify_todo(&id, |props| {
+            set_property(props, "SUMMARY", "Again".to_owned());
+        });
+        assert!(calendars.save().is_err());
+        assert!(calendars.data["Tasks"].is_dirty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn creating_a_task_can_be_undone_and_redone() {
+        let dir = temp_dir("create");
+        let mut app = app(vec![calendar(&dir, "Tasks", &[])]);
+        let path = dir.join("Tasks");
+        let files = || fs::read_dir(&path).unwrap().count();
+
+        app.create_task("Buy milk, eggs").unwrap();
+        let task = app.calendars.get_todos(None, None)[0].id().unwrap();
+        let file = path.join(format!("{}.ics", task.uid));
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content.contains("SUMMARY:Buy milk\\, eggs"), "{content}");
+        assert_eq!(app.selection(), Some((Some(task.clone()), 0)));
+
+        app.undo().unwrap();
+        assert!(app.calendars.get_todos(None, None).is_empty());
+        assert!(!file.exists());
+
+        app.redo().unwrap();
+        assert_eq!(app.calendars.get_todos(None, None)[0].id(), Some(task));
+        assert_eq!(fs::read_to_string(&file).unwrap(), content);
+        // displayname, color and the task
+        assert_eq!(files(), 3);
+
+        fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,1185 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use directories::ProjectDirs;
+use eyre::{Context, ContextCompat, Result, eyre};
+use roxmltree::{Document, Node};
+use ureq::{
+    Agent, Body,
+    http::{Request, Response, Uri},
+};
+
+use crate::{
+    config::{CalendarConfig, CredentialsConfig, PasswordSource},
+    helper::{DEFAULT_CALENDAR_COLOR, cache_name},
+};
+
+/// Time until a request is given up (e.g. when offline)
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// File in the cache with file name, href and ETag of every downloaded resource
+const ETAGS_FILE: &str = ".etags";
+
+/// File in the cache with the resources changed locally which were not uploaded yet
+const PENDING_FILE: &str = ".pending";
+
+/// File in the cache with the URL of the discovered calendar collection
+const COLLECTION_FILE: &str = ".collection";
+
+const NAMESPACE_DAV: &str = "DAV:";
+
+/// Properties of the collection requested with PROPFIND
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:a="http://apple.com/ns/ical/">
+  <d:prop>
+    <d:displayname/>
+    <a:calendar-color/>
+  </d:prop>
+</d:propfind>"#;
+
+/// Properties requested with PROPFIND to find the calendar collections of the user
+const DISCOVERY_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:resourcetype/>
+    <d:displayname/>
+    <d:current-user-principal/>
+    <c:calendar-home-set/>
+    <c:supported-calendar-component-set/>
+  </d:prop>
+</d:propfind>"#;
+
+/// ETags of all resources of the collection (calendar-query REPORT)
+const QUERY_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR"/>
+  </c:filter>
+</c:calendar-query>"#;
+
+/// Downloaded resource of the collection
+#[derive(Debug, Clone)]
+struct Resource {
+    href: String,
+    /// Version of the resource on the server (empty if unknown)
+    etag: String,
+}
+
+/// CalDAV calendar collection mirrored into a local directory with the VDIR layout
+///
+/// Changes are written to the directory and uploaded afterwards. Changes which could not be
+/// uploaded (e.g. while offline) are uploaded with the next sync. If a resource was changed on
+/// the server in the meantime, the version of the server is kept.
+///
+/// If the configured URL isn't a calendar collection, the collection is discovered through the
+/// principal and the calendar home of the user on the first sync (see [`Client::discover`]).
+#[derive(Debug)]
+pub struct CalDav {
+    client: Client,
+    /// Configured name, selects the calendar if the user has several calendars for tasks
+    name: Option<String>,
+    /// Whether the URL of the client is the calendar collection (discovered or read from cache)
+    discovered: bool,
+    /// Directory with the cached resources (one .ics file per resource)
+    dir: PathBuf,
+    /// Downloaded resources by file name (without .ics)
+    resources: HashMap<String, Resource>,
+    /// File names of the resources changed locally which were not uploaded yet
+    pending: HashSet<String>,
+}
+
+impl CalDav {
+    /// Open the cache of a CalDAV calendar (without contacting the server)
+    pub fn open(cfg: &CalendarConfig) -> Result<Self> {
+        let dirs = ProjectDirs::from("rs", "", "isek").context("No cache directory found")?;
+        Self::open_in(
+            cfg,
+            dirs.cache_dir().join("caldav").join(cache_name(&cfg.path)),
+        )
+    }
+
+    /// Open a CalDAV calendar cached in the given directory (see [`CalDav::open`])
+    pub fn open_in(cfg: &CalendarConfig, dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)
+            .wrap_err_with(|| format!("Could not create cache at {}", dir.display()))?;
+
+        // Name and color until they are read from the server
+        let url = cfg.path.trim_end_matches('/');
+        let name = url.rsplit('/').next().unwrap_or(url);
+        for (file, fallback) in [("displayname", name), ("color", DEFAULT_CALENDAR_COLOR)] {
+            if !dir.join(file).exists() {
+                fs::write(dir.join(file), fallback)?;
+            }
+        }
+
+        let resources = fs::read_to_string(dir.join(ETAGS_FILE))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let (file, href) = (fields.next()?, fields.next()?);
+                let etag = fields.next().unwrap_or_default();
+
+                Some((
+                    file.to_owned(),
+                    Resource {
+                        href: href.to_owned(),
+                        etag: etag.to_owned(),
+                    },
+                ))
+            })
+            .collect();
+
+        let pending = fs::read_to_string(dir.join(PENDING_FILE))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_owned)
+            .collect();
+
+        let mut client = Client::new(cfg)?;
+        let collection = fs::read_to_string(dir.join(COLLECTION_FILE)).ok();
+        if let Some(url) = &collection {
+            client.set_url(url.trim())?;
+        }
+
+        Ok(Self {
+            client,
+            name: cfg.display_name.clone(),
+            discovered: collection.is_some(),
+            dir,
+            resources,
+            pending,
+        })
+    }
+
+    /// Directory with the cached resources
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Remember files of the cache which were changed (or removed) locally
+    pub fn add_pending(&mut self, files: impl IntoIterator<Item = String>) -> Result<()> {
+        self.pending.extend(files);
+        self.save_state()
+    }
+
+    /// Upload the local changes, then download the changes on the server into the cache
+    pub fn sync(&mut self) -> Result<()> {
+        let conflicts = self.upload()?;
+        self.download()?;
+
+        match conflicts.is_empty() {
+            true => Ok(()),
+            false => Err(conflict_error(&conflicts)),
+        }
+    }
+
+    /// Upload the local changes (PUT or DELETE)
+    ///
+    /// Resources changed on the server in the meantime are downloaded instead, their file names
+    /// are returned. Changes are kept for the next upload if the server can't be reached.
+    pub fn upload(&mut self) -> Result<Vec<String>> {
+        self.discover()?;
+
+        let mut conflicts = vec![];
+        let mut pending: Vec<String> = self.pending.iter().cloned().collect();
+        pending.sort();
+
+        for file in pending {
+            let path = self.dir.join(format!("{file}.ics"));
+            let resource = self.resources.get(&file).cloned();
+            let href = match &resource {
+                Some(resource) => resource.href.clone(),
+                None => self.client.href_for(&file),
+            };
+
+            let upload = match (fs::read_to_string(&path).ok(), &resource) {
+                (Some(data), _) => {
+                    self.client
+                        .put(&href, data, resource.as_ref().map(|r| r.etag.as_str()))?
+                }
+                (None, Some(resource)) => self.client.delete(&href, &resource.etag)?,
+                (None, None) => Upload::Done(None),
+            };
+
+            match upload {
+                Upload::Done(_) if !path.exists() => {
+                    self.resources.remove(&file);
+                }
+                Upload::Done(etag) => {
+                    let etag = etag.unwrap_or_default();
+                    self.resources.insert(file.clone(), Resource { href, etag });
+                }
+                Upload::Conflict => {
+                    self.replace_with_server_version(&file, &href)?;
+                    conflicts.push(file.clone());
+                }
+            }
+
+            self.pending.remove(&file);
+            self.save_state()?;
+        }
+
+        Ok(conflicts)
+    }
+
+    /// Find the calendar collection before the first sync and remember it in the cache
+    fn discover(&mut self) -> Result<()> {
+        if self.discovered {
+            return Ok(());
+        }
+
+        let url = self.client.discover(self.name.as_deref())?;
+        self.client.set_url(&url)?;
+        fs::write(self.dir.join(COLLECTION_FILE), url)?;
+        self.discovered = true;
+
+        Ok(())
+    }
+
+    /// Download the name, color and all resources which changed on the server
+    fn download(&mut self) -> Result<()> {
+        let (name, color) = self.client.properties()?;
+        if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+            fs::write(self.dir.join("displayname"), name.trim())?;
+        }
+        // Colors may contain an alpha channel (#RRGGBBAA)
+        if let Some(color) = color.filter(|c| c.starts_with('#') && c.len() >= 7) {
+            fs::write(self.dir.join("color"), &color[..7])?;
+        }
+
+        let etags = self.client.etags()?;
+        let files: HashMap<&str, &str> = self
+            .resources
+            .iter()
+            .map(|(file, r)| (r.href.as_str(), file.as_str()))
+            .collect();
+
+        let changed: Vec<String> = etags
+            .iter()
+            .filter(|(href, etag)| match files.get(href.as_str()) {
+                Some(file) if self.pending.contains(*file) => false,
+                Some(file) => {
+                    self.resources[*file].etag.is_empty() || self.resources[*file].etag != **etag
+                }
+                None => true,
+            })
+            .map(|(href, _)| href.clone())
+            .collect();
+
+        // Resources removed on the server
+        let removed: Vec<String> = self
+            .resources
+            .iter()
+            .filter(|(file, r)| !etags.contains_key(&r.href) && !self.pending.contains(*file))
+            .map(|(file, _)| file.clone())
+            .collect();
+
+        for file in removed {
+            let path = self.dir.join(format!("{file}.ics"));
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            self.resources.remove(&file);
+        }
+
+        for (href, etag, data) in self.client.fetch(&changed)? {
+            let file = self
+                .resources
+                .iter()
+                .find(|(_, r)| r.href == href)
+                .map(|(file, _)| file.clone())
+                .unwrap_or_else(|| file_name(&href));
+
+            fs::write(self.dir.join(format!("{file}.ics")), data)?;
+            self.resources.insert(file, Resource { href, etag });
+        }
+
+        self.save_state()
+    }
+
+    /// Replace a cached resource with the version on the server (or remove it if it is gone)
+    fn replace_with_server_version(&mut self, file: &str, href: &str) -> Result<()> {
+        let path = self.dir.join(format!("{file}.ics"));
+
+        match self.client.fetch(&[href.to_owned()])?.pop() {
+            Some((href, etag, data)) => {
+                fs::write(&path, data)?;
+                self.resources
+                    .insert(file.to_owned(), Resource { href, etag });
+            }
+            None => {
+                if path.exists() {
+                    fs::remove_file(&path)?;
+                }
+                self.resources.remove(file);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the ETags and pending changes into the cache
+    fn save_state(&self) -> Result<()> {
+        let etags: String = self
+            .resources
+            .iter()
+            .map(|(file, r)| format!("{file}\t{}\t{}\n", r.href, r.etag))
+            .collect();
+        let pending: String = self
+            .pending
+            .iter()
+            .map(|file| format!("{file}\n"))
+            .collect();
+
+        fs::write(self.dir.join(ETAGS_FILE), etags)?;
+        fs::write(self.dir.join(PENDING_FILE), pending)?;
+
+        Ok(())
+    }
+}
+
+/// Error describing resources whose local changes were replaced by the server version
+pub fn conflict_error(files: &[String]) -> eyre::Report {
+    eyre!(
+        "Changed on the server, local changes replaced by the server version (reload with r): {}",
+        files.join(", ")
+    )
+}
+
+/// File name (without .ics) of a resource in the cache
+fn file_name(href: &str) -> String {
+    match href.trim_end_matches('/').rsplit('/').next() {
+        Some(name) if !name.is_empty() => name.trim_end_matches(".ics").to_owned(),
+        _ => uuid::Uuid::new_v4().to_string(),
+    }
+}
+
+/// Result of an upload
+enum Upload {
+    /// Uploaded, with the new ETag if the server reported it
+    Done(Option<String>),
+    /// Resource was changed on the server since it was downloaded
+    Conflict,
+}
+
+/// Whether the properties are the ones of a calendar collection
+fn is_calendar(props: &HashMap<String, String>) -> bool {
+    props
+        .get("resourcetype")
+        .is_some_and(|types| types.split(' ').any(|t| t == "calendar"))
+}
+
+/// Minimal CalDAV client for a single calendar collection
+#[derive(Debug)]
+struct Client {
+    agent: Agent,
+    /// URL of the calendar collection (the configured URL until the collection is discovered)
+    url: Uri,
+    credentials: Option<CredentialsConfig>,
+    /// Value of the Authorization header (the password is read before the first request)
+    authorization: Option<String>,
+}
+
+impl Client {
+    fn new(cfg: &CalendarConfig) -> Result<Self> {
+        let config = Agent::config_builder()
+            .http_status_as_error(false)
+            .allow_non_standard_methods(true)
+            .timeout_global(Some(TIMEOUT))
+            .build();
+
+        let mut client = Self {
+            agent: Agent::new_with_config(config),
+            url: Uri::default(),
+            credentials: cfg.credentials.clone(),
+            authorization: None,
+        };
+        client.set_url(&cfg.path)?;
+
+        Ok(client)
+    }
+
+    /// Use the collection at the given URL
+    fn set_url(&mut self, url: &str) -> Result<()> {
+        let url = match url.ends_with('/') {
+            true => url.to_owned(),
+            false => format!("{url}/"),
+        };
+        self.url = url
+            .parse()
+            .wrap_err_with(|| format!("Invalid CalDAV URL '{url}'"))?;
+
+        Ok(())
+    }
+
+    /// URL of the calendar collection for the configured URL
+    ///
+    /// If the URL isn't a calendar itself, the calendars for tasks are looked up in the calendar
+    /// home of the URL or of its principal (`current-user-principal`). With several calendars
+    /// for tasks, the one with the given name is used.
+    fn discover(&mut self, name: Option<&str>) -> Result<String> {
+        let href = self.url.path().to_owned();
+        let props = self.discovery_properties(&href)?;
+        if is_calendar(&props) {
+            return Ok(self.url.to_string());
+        }
+
+        let home = match props.get("calendar-home-set") {
+            Some(home) => home.clone(),
+            None => {
+                let principal = props.get("current-user-principal").with_context(|| {
+                    format!(
+                        "No calendar, principal or calendar home found at {}",
+                        self.url
+                    )
+                })?;
+                self.discovery_properties(principal)?
+                    .remove("calendar-home-set")
+                    .with_context(|| {
+                        format!("No calendar home found at {}", self.url_of(principal))
+                    })?
+            }
+        };
+
+        let xml = self.multistatus("PROPFIND", &home, "1", DISCOVERY_BODY.to_owned())?;
+        let mut calendars: Vec<(String, String)> = parse_multistatus(&xml)?
+            .into_iter()
+            .filter(|(_, props)| {
+                is_calendar(props)
+                    && props
+                        .get("supported-calendar-component-set")
+                        .is_none_or(|components| components.split(' ').any(|c| c == "VTODO"))
+            })
+            .map(|(href, mut props)| {
+                let name = props
+                    .remove("displayname")
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| file_name(&href));
+                (href, name)
+            })
+            .collect();
+        calendars.sort();
+
+        let selected = match calendars.as_slice() {
+            [(href, _)] => Some(href),
+            _ => calendars
+                .iter()
+                .find(|(_, calendar)| name.is_some_and(|name| calendar.eq_ignore_ascii_case(name)))
+                .map(|(href, _)| href),
+        };
+
+        match selected {
+            Some(href) => Ok(self.url_of(href)),
+            None if calendars.is_empty() => Err(eyre!(
+                "No calendar for tasks found in {}",
+                self.url_of(&home)
+            )),
+            None => Err(eyre!(
+                "Several calendars for tasks found, set display_name to one of: {}",
+                calendars
+                    .iter()
+                    .map(|(_, name)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Properties used for discovery of a single resource
+    fn discovery_properties(&mut self, href: &str) -> Result<HashMap<String, String>> {
+        let xml = self.multistatus("PROPFIND", href, "0", DISCOVERY_BODY.to_owned())?;
+
+        Ok(parse_multistatus(&xml)?
+            .into_iter()
+            .next()
+            .map(|(_, props)| props)
+            .unwrap_or_default())
+    }
+
+    /// Href of a new resource in the collection
+    fn href_for(&self, file: &str) -> String {
+        format!("{}{file}.ics", self.url.path())
+    }
+
+    /// Absolute URL of an href (usually an absolute path)
+    fn url_of(&self, href: &str) -> String {
+        match (
+            href.starts_with("http://") || href.starts_with("https://"),
+            href.starts_with('/'),
+        ) {
+            (true, _) => href.to_owned(),
+            (false, true) => format!(
+                "{}://{}{href}",
+                self.url.scheme_str().unwrap_or("https"),
+                self.url.authority().map(|a| a.as_str()).unwrap_or_default()
+            ),
+            (false, false) => format!("{}{href}", self.url),
+        }
+    }
+
+    /// Value of the Authorization header, reading the password on first use
+    fn authorization(&mut self) -> Result<Option<String>> {
+        if self.authorization.is_none()
+            && let Some(credentials) = &self.credentials
+        {
+            let login = format!("{}:{}", credentials.username, password(credentials)?);
+            self.authorization = Some(format!("Basic {}", STANDARD.encode(login)));
+        }
+
+        Ok(self.authorization.clone())
+    }
+
+    fn request(
+        &mut self,
+        method: &str,
+        href: &str,
+        headers: &[(&str, &str)],
+        body: Option<String>,
+    ) -> Result<Response<Body>> {
+        let url = self.url_of(href);
+        let mut request = Request::builder().method(method).uri(&url);
+
+        if let Some(authorization) = self.authorization()? {
+            request = request.header("Authorization", authorization);
+        }
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        let response = match body {
+            Some(body) => self.agent.run(request.body(body)?),
+            None => self.agent.run(request.body(())?),
+        }
+        .wrap_err_with(|| format!("Could not reach {url}"))?;
+
+        match response.status().as_u16() {
+            401 | 403 => Err(eyre!("Access to {url} denied ({})", response.status())),
+            _ => Ok(response),
+        }
+    }
+
+    /// Send a PROPFIND or REPORT request and return the multistatus response
+    fn multistatus(
+        &mut self,
+        method: &str,
+        href: &str,
+        depth: &str,
+        body: String,
+    ) -> Result<String> {
+        let headers = [
+            ("Depth", depth),
+            ("Content-Type", "application/xml; charset=utf-8"),
+        ];
+        let mut response = self.request(method, href, &headers, Some(body))?;
+
+        match response.status().as_u16() {
+            207 => Ok(response.body_mut().read_to_string()?),
+            status => Err(eyre!("{method} {} failed ({status})", self.url_of(href))),
+        }
+    }
+
+    /// Display name and color of the collection
+    fn properties(&mut self) -> Result<(Option<String>, Option<String>)> {
+        let href = self.url.path().to_owned();
+        let xml = self.multistatus("PROPFIND", &href, "0", PROPFIND_BODY.to_owned())?;
+        let mut props = parse_multistatus(&xml)?
+            .into_iter()
+            .next()
+            .map(|(_, props)| props)
+            .unwrap_or_default();
+
+        Ok((props.remove("displayname"), props.remove("calendar-color")))
+    }
+
+    /// ETags of all resources in the collection by href
+    fn etags(&mut self) -> Result<HashMap<String, String>> {
+        let href = self.url.path().to_owned();
+        let xml = self.multistatus("REPORT", &href, "1", QUERY_BODY.to_owned())?;
+
+        Ok(parse_multistatus(&xml)?
+            .into_iter()
+            .filter_map(|(href, mut props)| Some((href, props.remove("getetag")?)))
+            .collect())
+    }
+
+    /// Href, ETag and data of the given resources (calendar-multiget REPORT)
+    fn fetch(&mut self, hrefs: &[String]) -> Result<Vec<(String, String, String)>> {
+        if hrefs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let hrefs: String = hrefs
+            .iter()
+            .map(|href| format!("  <d:href>{}</d:href>\n", escape_xml(href)))
+            .collect();
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>
+{hrefs}</c:calendar-multiget>"#
+        );
+
+        let href = self.url.path().to_owned();
+        let xml = self.multistatus("REPORT", &href, "1", body)?;
+
+        Ok(parse_multistatus(&xml)?
+            .into_iter()
+            .filter_map(|(href, mut props)| {
+                let data = props.remove("calendar-data")?;
+                Some((href, props.remove("getetag").unwrap_or_default(), data))
+            })
+            .collect())
+    }
+
+    /// Create or replace a resource
+    ///
+    /// Replacing requires the resource to still have the given ETag (unless it is unknown),
+    /// creating requires that no resource exists at the href.
+    fn put(&mut self, href: &str, data: String, etag: Option<&str>) -> Result<Upload> {
+        let precondition = match etag {
+            Some("") => None,
+            Some(etag) => Some(("If-Match", etag)),
+            None => Some(("If-None-Match", "*")),
+        };
+        let headers: Vec<(&str, &str)> =
+            std::iter::once(("Content-Type", "text/calendar; charset=utf-8"))
+                .chain(precondition)
+                .collect();
+
+        let response = self.request("PUT", href, &headers, Some(data))?;
+        Self::upload_result(response, "PUT", &self.url_of(href))
+    }
+
+    /// Delete a resource if it still has the given ETag
+    fn delete(&mut self, href: &str, etag: &str) -> Result<Upload> {
+        let headers: Vec<(&str, &str)> = match etag {
+            "" => vec![],
+            etag => vec![("If-Match", etag)],
+        };
+
+        let response = self.request("DELETE", href, &headers, None)?;
+        match response.status().as_u16() {
+            // Already deleted on the server
+            404 => Ok(Upload::Done(None)),
+            _ => Self::upload_result(response, "DELETE", &self.url_of(href)),
+        }
+    }
+
+    fn upload_result(response: Response<Body>, method: &str, url: &str) -> Result<Upload> {
+        match response.status().as_u16() {
+            200..=299 => Ok(Upload::Done(
+                response
+                    .headers()
+                    .get("ETag")
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(str::to_owned),
+            )),
+            412 => Ok(Upload::Conflict),
+            status => Err(eyre!("{method} {url} failed ({status})")),
+        }
+    }
+}
+
+/// Password of a login (first line printed by the command or the keyring entry)
+fn password(credentials: &CredentialsConfig) -> Result<String> {
+    let output = match &credentials.password {
+        PasswordSource::Command(command) => Command::new("sh").arg("-c").arg(command).output(),
+        PasswordSource::Keyring => Command::new("secret-tool")
+            .args([
+                "lookup",
+                "service",
+                "isek",
+                "username",
+                &credentials.username,
+            ])
+            .output(),
+    }
+    .wrap_err("Could not run the password command")?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "Could not get the password for '{}': {}",
+            credentials.username,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_owned())
+}
+
+/// Href and properties (by local name, see [`value`]) of every response in a multistatus
+/// document
+///
+/// Only properties with the status 200 are included.
+fn parse_multistatus(xml: &str) -> Result<Vec<(String, HashMap<String, String>)>> {
+    let document = Document::parse(xml).wrap_err("Invalid response from the CalDAV server")?;
+
+    Ok(document
+        .descendants()
+        .filter(|n| n.has_tag_name((NAMESPACE_DAV, "response")))
+        .filter_map(|response| {
+            let href = child(response, "href").map(text)?;
+
+            let props = response
+                .children()
+                .filter(|n| n.has_tag_name((NAMESPACE_DAV, "propstat")))
+                .filter(|propstat| {
+                    child(*propstat, "status").is_none_or(|s| text(s).contains(" 200 "))
+                })
+                .filter_map(|propstat| child(propstat, "prop"))
+                .flat_map(|prop| prop.children().filter(|n| n.is_element()))
+                .map(|property| (property.tag_name().name().to_owned(), value(property)))
+                .collect();
+
+            Some((href.trim().to_owned(), props))
+        })
+        .collect())
+}
+
+/// Value of a property: the first href in it, the names of its child elements (e.g.
+/// `collection calendar` for the resource type, `VEVENT VTODO` for the supported components) or
+/// its text
+fn value(property: Node) -> String {
+    if let Some(href) = child(property, "href") {
+        return text(href).trim().to_owned();
+    }
+
+    let children: Vec<&str> = property
+        .children()
+        .filter(|n| n.is_element())
+        .map(|n| n.attribute("name").unwrap_or(n.tag_name().name()))
+        .collect();
+
+    match children.is_empty() {
+        true => text(property).trim().to_owned(),
+        false => children.join(" "),
+    }
+}
+
+/// First child element in the DAV namespace with the given name
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.has_tag_name((NAMESPACE_DAV, name)))
+}
+
+/// Text content of an element (including CDATA sections)
+fn text(node: Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{
+        config::CalendarType,
+        test_server::{self, TestServer},
+    };
+
+    /// Resources on the mock server by href with ETag and data
+    type Store = Arc<Mutex<HashMap<String, (String, String)>>>;
+
+    const COLLECTION: &str = "/dav/calendars/user/tasks/";
+
+    /// Calendar home of the user on the mock server
+    const HOME: &str = "/dav/calendars/user/";
+
+    /// iCalendar data as it arrives after XML parsing (line endings normalized, trimmed)
+    fn data(uid: &str, summary: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\nVERSION:2.0\nBEGIN:VTODO\nUID:{uid}\nSUMMARY:{summary}\nEND:VTODO\nEND:VCALENDAR"
+        )
+    }
+
+    fn multistatus(responses: impl IntoIterator<Item = (String, String)>) -> test_server::Response {
+        let responses: String = responses
+            .into_iter()
+            .map(|(href, props)| {
+                format!(
+                    "<d:response><d:href>{href}</d:href><d:propstat><d:prop>{props}</d:prop>\
+                     <d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>"
+                )
+            })
+            .collect();
+
+        test_server::Response::new(
+            207,
+            format!(
+                r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:a="http://apple.com/ns/ical/">{responses}</d:multistatus>"#
+            ),
+        )
+    }
+
+    /// Mock CalDAV server with a collection for tasks (and other calendars of the user found
+    /// through the principal at `/dav/`), requiring the login `user:secret`
+    fn server(store: &Store) -> TestServer {
+        let store = store.clone();
+        let version = Mutex::new(0);
+        let authorization = format!("Basic {}", STANDARD.encode("user:secret"));
+
+        TestServer::start(move |request| {
+            if request.header("Authorization") != Some(authorization.as_str()) {
+                return test_server::Response::new(401, "");
+            }
+
+            let mut store = store.lock().unwrap();
+            let etag = store.get(&request.path).map(|(etag, _)| etag.clone());
+
+            match request.method.as_str() {
+                "PROPFIND" => match request.path.as_str() {
+                    "/dav/" => multistatus([(
+                        request.path.clone(),
+                        "<d:current-user-principal><d:href>/dav/principals/user/</d:href>\
+                         </d:current-user-principal>"
+                            .to_owned(),
+                    )]),
+                    "/dav/principals/user/" => multistatus([(
+                        request.path.clone(),
+                        format!(
+                            "<c:calendar-home-set><d:href>{HOME}</d:href></c:calendar-home-set>"
+                        ),
+                    )]),
+                    HOME => multistatus(
+                        [
+                            (HOME, "", &[][..]),
+                            (COLLECTION, "Work", &["VTODO"]),
+                            ("/dav/calendars/user/events/", "Events", &["VEVENT"]),
+                            (
+                                "/dav/calendars/user/chores/",
+                                "Chores",
+                                &["VEVENT", "VTODO"],
+                            ),
+                        ]
+                        .map(|(href, name, components)| {
+                            let props = match components.is_empty() {
+                                true => {
+                                    "<d:resourcetype><d:collection/></d:resourcetype>".to_owned()
+                                }
+                                false => format!(
+                                    "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype>\
+                                     <d:displayname>{name}</d:displayname>\
+                                     <c:supported-calendar-component-set>{}\
+                                     </c:supported-calendar-component-set>",
+                                    components
+                                        .iter()
+                                        .map(|c| format!("<c:comp name=\"{c}\"/>"))
+                                        .collect::<String>()
+                                ),
+                            };
+                            (href.to_owned(), props)
+                        }),
+                    ),
+                    _ => multistatus([(
+                        request.path.clone(),
+                        "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype>\
+                         <d:displayname>Work</d:displayname>\
+                         <a:calendar-color>#3A7BD5FF</a:calendar-color>"
+                            .to_owned(),
+                    )]),
+                },
+                "REPORT" if request.body.contains("calendar-multiget") => {
+                    let hrefs = request
+                        .body
+                        .split("<d:href>")
+                        .skip(1)
+                        .filter_map(|part| part.split_once("</d:href>"))
+                        .filter_map(|(href, _)| Some((href, store.get(href)?)));
+
+                    multistatus(hrefs.map(|(href, (etag, data))| {
+                        let props = format!(
+                            "<d:getetag>{etag}</d:getetag>\
+                             <c:calendar-data><![CDATA[{data}]]></c:calendar-data>"
+                        );
+                        (href.to_owned(), props)
+                    }))
+                }
+                "REPORT" => multistatus(store.iter().map(|(href, (etag, _))| {
+                    (href.clone(), format!("<d:getetag>{etag}</d:getetag>"))
+                })),
+                "PUT" => {
+                    let if_match = request.header("If-Match");
+                    let if_none_match = request.header("If-None-Match");
+                    if if_match.is_some_and(|tag| Some(tag) != etag.as_deref())
+                        || (if_none_match == Some("*") && etag.is_some())
+                    {
+                        return test_server::Response::new(412, "");
+                    }
+
+                    let mut version = version.lock().unwrap();
+                    *version += 1;
+                    let etag = format!("\"put-{version}\"");
+                    let status = if store.contains_key(&request.path) {
+                        204
+                    } else {
+                        201
+                    };
+                    store.insert(request.path.clone(), (etag.clone(), request.body.clone()));
+
+                    test_server::Response::new(status, "").header("ETag", &etag)
+                }
+                "DELETE" => match (etag, request.header("If-Match")) {
+                    (None, _) => test_server::Response::new(404, ""),
+                    (Some(etag), Some(tag)) if etag != tag => test_server::Response::new(412, ""),
+                    _ => {
+                        store.remove(&request.path);
+                        test_server::Response::new(204, "")
+                    }
+                },
+                _ => test_server::Response::new(405, ""),
+            }
+        })
+    }
+
+    fn store(resources: &[(&str, &str, String)]) -> Store {
+        Arc::new(Mutex::new(
+            resources
+                .iter()
+                .map(|(file, etag, data)| {
+                    let href = format!("{COLLECTION}{file}.ics");
+                    (href, (format!("\"{etag}\""), data.clone()))
+                })
+                .collect(),
+        ))
+    }
+
+    fn config(server: &TestServer) -> CalendarConfig {
+        CalendarConfig {
+            kind: CalendarType::CalDAV,
+            path: format!("{}{COLLECTION}", server.url),
+            display_name: None,
+            color: None,
+            credentials: Some(CredentialsConfig {
+                username: "user".to_owned(),
+                password: PasswordSource::Command("echo secret".to_owned()),
+            }),
+            command: None,
+            refresh: None,
+        }
+    }
+
+    /// Empty cache directory for a test
+    fn cache_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("isek-caldav-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn read(dir: &Path, file: &str) -> Option<String> {
+        fs::read_to_string(dir.join(file)).ok()
+    }
+
+    #[test]
+    fn sync_mirrors_the_collection() {
+        let store = store(&[("a", "1", data("a", "A")), ("b", "1", data("b", "B"))]);
+        let server = server(&store);
+        let dir = cache_dir("mirror");
+
+        let mut caldav = CalDav::open_in(&config(&server), dir.clone()).unwrap();
+        assert_eq!(read(&dir, "displayname").as_deref(), Some("tasks"));
+        caldav.sync().unwrap();
+
+        assert_eq!(read(&dir, "displayname").as_deref(), Some("Work"));
+        assert_eq!(read(&dir, "color").as_deref(), Some("#3A7BD5"));
+        assert_eq!(read(&dir, "a.ics"), Some(data("a", "A")));
+        assert_eq!(read(&dir, "b.ics"), Some(data("b", "B")));
+
+        // Changed, removed and added on the server
+        {
+            let mut store = store.lock().unwrap();
+            store.insert(
+                format!("{COLLECTION}a.ics"),
+                ("\"2\"".to_owned(), data("a", "A2")),
+            );
+            store.remove(&format!("{COLLECTION}b.ics"));
+            store.insert(
+                format!("{COLLECTION}c.ics"),
+                ("\"1\"".to_owned(), data("c", "C")),
+            );
+        }
+
+        // The ETags are read from the cache
+        let mut caldav = CalDav::open_in(&config(&server), dir.clone()).unwrap();
+        caldav.sync().unwrap();
+
+        assert_eq!(read(&dir, "a.ics"), Some(data("a", "A2")));
+        assert_eq!(read(&dir, "b.ics"), None);
+        assert_eq!(read(&dir, "c.ics"), Some(data("c", "C")));
+
+        // Nothing is downloaded if nothing changed (only PROPFIND and calendar-query)
+        let requests = server.requests().len();
+        caldav.sync().unwrap();
+        assert_eq!(server.requests().len(), requests + 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn changes_made_offline_are_uploaded_with_the_next_sync() {
+        let store = store(&[("a", "1", data("a", "A")), ("b", "1", data("b", "B"))]);
+        let server = server(&store);
+        let dir = cache_dir("offline");
+
+        let mut caldav = CalDav::open_in(&config(&server), dir.clone()).unwrap();
+        caldav.sync().unwrap();
+
+        server.set_offline(true);
+        fs::write(dir.join("a.ics"), data("a", "A changed")).unwrap();
+        fs::remove_file(dir.join("b.ics")).unwrap();
+        fs::write(dir.join("new.ics"), data("new", "New")).unwrap();
+        caldav
+            .add_pending(["a", "b", "new"].map(str::to_owned))
+            .unwrap();
+
+        assert!(caldav.sync().is_err());
+        assert_eq!(read(&dir, PENDING_FILE).unwrap().lines().count(), 3);
+
+        // Still pending after a restart
+        server.set_offline(false);
+        let mut caldav = CalDav::open_in(&config(&server), dir.clone()).unwrap();
+        caldav.sync().unwrap();
+
+        let store = store.lock().unwrap();
+        let href = |file: &str| format!("{COLLECTION}{file}.ics");
+        assert_eq!(store[&href("a")].1, data("a", "A changed"));
+        assert!(!store.contains_key(&href("b")));
+        assert_eq!(store[&href("new")].1, data("new", "New"));
+        assert_eq!(read(&dir, PENDING_FILE).as_deref(), Some(""));
+
+        // The ETags of the uploads are known, so they aren't downloaded again
+        assert!(
+            read(&dir, ETAGS_FILE)
+                .unwrap()
+                .contains(&store[&href("a")].0)
+        );
+        assert_eq!(read(&dir, "a.ics"), Some(data("a", "A changed")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn conflicting_changes_are_replaced_by_the_server_version() {
+        let store = store(&[("a", "1", data("a", "A")), ("b", "1", data("b", "B"))]);
+        let server = server(&store);
+        let dir = cache_dir("conflict");
+
+        let mut caldav = CalDav::open_in(&config(&server), dir.clone()).unwrap();
+        caldav.sync().unwrap();
+
+        // Changed by another client since the last sync
+        {
+            let mut store = store.lock().unwrap();
+            for (file, summary) in [("a", "A remote"), ("b", "B remote")] {
+                let href = format!("{COLLECTION}{file}.ics");
+                store.insert(href, ("\"2\"".to_owned(), data(file, summary)));
+            }
+        }
+
+        fs::write(dir.join("a.ics"), data("a", "A local")).unwrap();
+        fs::remove_file(dir.join("b.ics")).unwrap();
+        caldav.add_pending(["a", "b"].map(str::to_owned)).unwrap();
+
+        let error = caldav.sync().unwrap_err().to_string();
+        assert!(error.contains("a, b"), "{error}");
+        assert_eq!(read(&dir, "a.ics"), Some(data("a", "A remote")));
+        assert_eq!(read(&dir, "b.ics"), Some(data("b", "B remote")));
+        assert!(
+            server
+                .requests()
+                .contains(&format!("PUT {COLLECTION}a.ics"))
+        );
+        assert!(
+            server
+                .requests()
+                .contains(&format!("DELETE {COLLECTION}b.ics"))
+        );
+
+        // Nothing left to upload
+        caldav.sync().unwrap();
+        assert_eq!(store.lock().unwrap().len(), 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn wrong_password_is_reported() {
+        let server = server(&store(&[]));
+        let dir = cache_dir("password");
+
+        let mut cfg = config(&server);
+        cfg.credentials = Some(CredentialsConfig {
+            username: "user".to_owned(),
+            password: PasswordSource::Command("echo wrong".to_owned()),
+        });
+
+        let mut caldav = CalDav::open_in(&cfg, dir.clone()).unwrap();
+        let error = caldav.sync().unwrap_err().to_string();
+        assert!(error.contains("denied"), "{error}");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn calendar_is_discovered_through_the_principal() {
+        let store = store(&[("a", "1", data("a", "A"))]);
+        let server = server(&store);
+        let dir = cache_dir("discovery");
+        let mut cfg = config(&server);
+        cfg.path = format!("{}/dav", server.url);
+
+        // Two calendars for tasks, the name selects one of them
+        let mut caldav = CalDav::open_in(&cfg, dir.clone()).unwrap();
+        let error = caldav.sync().unwrap_err().to_string();
+        assert!(error.contains("Chores, Work"), "{error}");
+
+        cfg.display_name = Some("work".to_owned());
+        let mut caldav = CalDav::open_in(&cfg, dir.clone()).unwrap();
+        caldav.sync().unwrap();
+        assert_eq!(read(&dir, "a.ics"), Some(data("a", "A")));
+        assert_eq!(
+            read(&dir, COLLECTION_FILE),
+            Some(format!("{}{COLLECTION}", server.url))
+        );
+
+        // The discovered collection is used after a restart (only PROPFIND and calendar-query)
+        let requests = server.requests().len();
+        let mut caldav = CalDav::open_in(&cfg, dir.clone()).unwrap();
+        caldav.sync().unwrap();
+        assert_eq!(
+            server.requests()[requests..],
+            [
+                format!("PROPFIND {COLLECTION}"),
+                format!("REPORT {COLLECTION}")
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
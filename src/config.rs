@@ -1,7 +1,13 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
+use eyre::{Result, eyre};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    helper::set_local_timezone,
+    line_format::{DEFAULT_LINE_FORMAT, Field},
+};
+
 /// Configuration structure for the application
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct IsekConfig {
@@ -10,6 +16,41 @@ pub struct IsekConfig {
 
     /// Default display Options
     pub display: DisplayOptions,
+
+    /// Colors and text styles used in the UI
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
+    /// Weights of the isek index
+    #[serde(default)]
+    pub index: IndexConfig,
+
+    /// IANA timezone (e.g. "Europe/Berlin") used for floating times and dates
+    ///
+    /// The timezone of the system is used if not set.
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// Settings of the reminder daemon (`isek remind`)
+    #[serde(default)]
+    pub remind: RemindConfig,
+}
+
+impl IsekConfig {
+    /// Load the configuration file and use the configured timezone as local time
+    pub fn load() -> Result<Self> {
+        let config: IsekConfig = confy::load("isek", "config")?;
+
+        if let Some(timezone) = &config.timezone {
+            set_local_timezone(
+                timezone
+                    .parse()
+                    .map_err(|_| eyre!("Unknown timezone '{}' in config", timezone))?,
+            );
+        }
+
+        Ok(config)
+    }
 }
 
 impl Default for IsekConfig {
@@ -22,10 +63,21 @@ impl Default for IsekConfig {
                     by: SortingVariant::Priority,
                     ascending: true,
                     ignore_done: true,
+                    then: vec![
+                        SortKey {
+                            by: SortingVariant::Date,
+                            ascending: true,
+                        },
+                        SortKey {
+                            by: SortingVariant::Summary,
+                            ascending: true,
+                        },
+                    ],
                 },
                 filter: FilterConfig {
                     show_done: ShowDoneOptions::Hide,
                     show_done_for: 5,
+                    hide_deferred: false,
                 },
                 date_format: DateFormatConfig {
                     date: "%Y-%m-%d".into(),
@@ -36,7 +88,15 @@ impl Default for IsekConfig {
                     done: "[X] ".into(),
                     in_progress: "[-] ".into(),
                 },
+                line_format: default_line_format(),
+                layout: LayoutVariant::List,
+                table_columns: default_table_columns(),
+                priority: PriorityDisplay::default(),
             },
+            theme: ThemeConfig::default(),
+            index: IndexConfig::default(),
+            timezone: None,
+            remind: RemindConfig::default(),
         }
     }
 }
@@ -52,6 +112,12 @@ impl Default for IsekConfig {
 pub enum CalendarType {
     /// VDIR calendar configuration variant
     VDIR,
+    /// Calendar collection on a CalDAV server, cached locally to work offline
+    CalDAV,
+    /// Single .ics file with all tasks (e.g. a `todo.ics` exported from Thunderbird)
+    File,
+    /// Read-only calendar fetched from a URL or the output of a command (e.g. a webcal feed)
+    Subscription,
 }
 
 /// Configuration for a VDIR-based calendar
@@ -60,11 +126,46 @@ pub struct CalendarConfig {
     /// Type of calendar structure
     pub kind: CalendarType,
 
-    /// Filesystem path to the calendar directory
+    /// Filesystem path to the calendar directory (the .ics file for File, URL of the calendar
+    /// collection or the server for CalDAV, URL of the feed for Subscription)
+    #[serde(default)]
     pub path: String,
 
     /// String displayed for this calendar
     pub display_name: Option<String>,
+
+    /// Color of the calendar as hex string, overrides the color of the calendar directory
+    pub color: Option<String>,
+
+    /// Login for CalDAV calendars
+    #[serde(default)]
+    pub credentials: Option<CredentialsConfig>,
+
+    /// Shell command printing the iCalendar data of a subscription (instead of a URL in `path`)
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Minutes until a subscription is fetched again
+    #[serde(default)]
+    pub refresh: Option<u64>,
+}
+
+/// Login for a CalDAV server (HTTP basic authentication)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialsConfig {
+    pub username: String,
+    /// Where the password is read from
+    pub password: PasswordSource,
+}
+
+/// Enum representing the ways to get a password without storing it in the config
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum PasswordSource {
+    /// First line printed by a shell command (e.g. `pass show caldav`)
+    Command(String),
+    /// Entry in the keyring of the desktop (Secret Service, read with `secret-tool`) with the
+    /// attributes `service` "isek" and `username`
+    Keyring,
 }
 
 /// Structure representing the display configuration options
@@ -78,24 +179,150 @@ pub struct DisplayOptions {
     pub filter: FilterConfig,
     /// Progress Strings
     pub progress: ProgressDisplayConfig,
+    /// Template for a single line in the task list
+    ///
+    /// Available fields: progress, priority, summary, tags, due, start, calendar, location,
+    /// percent, status and index. Dates can be shown relative with `{due:relative}`, the width
+    /// of a field can be limited with `{summary:30}` and `{>}` aligns the rest of the line right.
+    #[serde(default = "default_line_format")]
+    pub line_format: String,
+    /// Display tasks as list or table
+    #[serde(default)]
+    pub layout: LayoutVariant,
+    /// Columns displayed in the table layout
+    #[serde(default = "default_table_columns")]
+    pub table_columns: Vec<TableColumnConfig>,
+    /// How the priority field is displayed
+    #[serde(default)]
+    pub priority: PriorityDisplay,
 }
 
-/// Enum representing different sorting variants for tasks
+fn default_line_format() -> String {
+    DEFAULT_LINE_FORMAT.into()
+}
+
+fn default_table_columns() -> Vec<TableColumnConfig> {
+    [
+        (Field::Progress, Some(4)),
+        (Field::Priority, Some(3)),
+        (Field::Summary, None),
+        (Field::Calendar, Some(14)),
+        (Field::Due, Some(16)),
+        (Field::Start, Some(16)),
+        (Field::Tags, Some(16)),
+    ]
+    .into_iter()
+    .map(|(field, width)| TableColumnConfig { field, width })
+    .collect()
+}
+
+/// Enum representing the different layouts of the task view
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub enum LayoutVariant {
+    /// One line per task based on the line format
+    #[default]
+    List,
+    /// Table with aligned columns
+    Table,
+}
+
+/// Enum representing the different ways to display the priority of a task
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PriorityDisplay {
+    /// Exclamation marks for the RFC 5545 buckets (high `!!!`, medium `!!`, low `!`)
+    #[default]
+    Marks,
+    /// Letters for the RFC 5545 buckets (`H`, `M`, `L`)
+    Letters,
+    /// The raw value from 1 (highest) to 9 (lowest)
+    Number,
+}
+
+/// Configuration of a single column in the table layout
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableColumnConfig {
+    /// Field displayed in this column
+    pub field: Field,
+    /// Width of the column (takes the remaining space if not set)
+    pub width: Option<u16>,
+}
+
+/// Enum representing different sorting variants for tasks
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum SortingVariant {
     /// Sort by date
     Date,
     /// Sort by priority
     Priority,
-    /// Sort by ISEK index
+    /// Sort by ISEK index (ascending puts the highest index, i.e. the most urgent task, first)
     Index,
+    /// Sort by start date
+    Start,
+    /// Sort alphabetically by summary
+    Summary,
+    /// Sort by creation time (CREATED)
+    Created,
+    /// Sort by time of the last modification (LAST-MODIFIED)
+    LastModified,
+    /// Sort by status (needs action, in process, completed, cancelled)
+    Status,
+    /// Sort by calendar name
+    Calendar,
+}
+
+impl SortingVariant {
+    /// Sorting variant matching a field (e.g. for sorting by table column)
+    pub fn from_field(field: Field) -> Option<Self> {
+        match field {
+            Field::Due => Some(SortingVariant::Date),
+            Field::Priority => Some(SortingVariant::Priority),
+            Field::Index => Some(SortingVariant::Index),
+            Field::Start => Some(SortingVariant::Start),
+            Field::Summary => Some(SortingVariant::Summary),
+            Field::Status => Some(SortingVariant::Status),
+            Field::Calendar => Some(SortingVariant::Calendar),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SortingConfig {
     pub by: SortingVariant,
     pub ascending: bool,
+    /// Place completed and cancelled tasks after all open tasks (in any direction)
     pub ignore_done: bool,
+    /// Further sort keys used in this order if tasks are equal
+    ///
+    /// Tasks which are still equal are sorted by calendar and UID so the order is stable.
+    #[serde(default)]
+    pub then: Vec<SortKey>,
+}
+
+impl SortingConfig {
+    /// All sort keys starting with the primary one
+    pub fn keys(&self) -> Vec<SortKey> {
+        let primary = SortKey {
+            by: self.by.clone(),
+            ascending: self.ascending,
+        };
+
+        std::iter::once(primary)
+            .chain(self.then.iter().cloned())
+            .collect()
+    }
+}
+
+/// Single sort key with its direction
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub by: SortingVariant,
+    #[serde(default = "default_ascending")]
+    pub ascending: bool,
+}
+
+fn default_ascending() -> bool {
+    true
 }
 
 /// Structure representing the date formatting options
@@ -140,6 +367,10 @@ pub struct FilterConfig {
 
     // Shows tasks done for less than x days if ShowDoneOptions::Some
     pub show_done_for: u64,
+
+    /// Hide tasks whose start date (DTSTART) is in the future
+    #[serde(default)]
+    pub hide_deferred: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -148,3 +379,164 @@ pub struct ProgressDisplayConfig {
     pub done: String,
     pub in_progress: String,
 }
+
+/// Built-in themes which can be used as base for a custom theme
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub enum ThemeVariant {
+    /// Theme for terminals with dark background
+    #[default]
+    Dark,
+    /// Theme for terminals with light background
+    Light,
+    /// Strong contrasts for better readability
+    HighContrast,
+    /// Only uses text modifiers (bold, italic, ...) and no colors
+    NoColor,
+}
+
+/// Text modifiers which can be applied in addition to colors
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum StyleModifier {
+    Bold,
+    Dim,
+    Italic,
+    Underlined,
+    Reversed,
+    CrossedOut,
+}
+
+/// Style of a single UI element
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StyleConfig {
+    /// Foreground color (color name, hex string like "#ff0000" or terminal color index)
+    pub fg: Option<String>,
+    /// Background color (color name, hex string like "#ff0000" or terminal color index)
+    pub bg: Option<String>,
+    /// Text modifiers
+    #[serde(default)]
+    pub modifiers: Vec<StyleModifier>,
+}
+
+/// Scoring model of the isek index
+///
+/// The index of a task is the sum of all weighted components, tasks with a higher index are
+/// more urgent. Every component is scaled to the range 0 to 1 before it is weighted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct IndexConfig {
+    /// Weight of the priority (1 = highest, 9 = lowest, undefined = 0)
+    pub priority: f64,
+    /// Weight of the due date (0.5 when due now, approaching 1 when overdue)
+    pub due: f64,
+    /// Days until the due date within which the urgency rises
+    pub horizon_days: f64,
+    /// Added to overdue tasks
+    pub overdue: f64,
+    /// Added to tasks whose start date is in the future (usually negative)
+    pub deferred: f64,
+    /// Weight of the age since CREATED (reaches 1 after `age_days`)
+    pub age: f64,
+    /// Days after which a task counts as old
+    pub age_days: f64,
+    /// Added for every tag of a task
+    pub tags: HashMap<String, f64>,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            priority: 3.0,
+            due: 4.0,
+            horizon_days: 7.0,
+            overdue: 2.0,
+            deferred: -3.0,
+            age: 0.5,
+            age_days: 30.0,
+            tags: HashMap::new(),
+        }
+    }
+}
+
+/// Theme configuration
+///
+/// All element styles are optional and are applied on top of the `base` theme.
+/// If the `NO_COLOR` environment variable is set, `base` is ignored and colors are dropped.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ThemeConfig {
+    /// Built-in theme used as base
+    #[serde(default)]
+    pub base: ThemeVariant,
+
+    /// Regular list entries
+    pub list: Option<StyleConfig>,
+    /// Selected list entry
+    pub highlight: Option<StyleConfig>,
+    /// Borders of the main block
+    pub border: Option<StyleConfig>,
+    /// Title of the main block
+    pub title: Option<StyleConfig>,
+    /// Description of keybinds in the status bar
+    pub status_bar: Option<StyleConfig>,
+    /// Keys in the status bar
+    pub status_bar_key: Option<StyleConfig>,
+    /// Dates (e.g. due date)
+    pub date: Option<StyleConfig>,
+    /// Due dates in the past
+    pub date_overdue: Option<StyleConfig>,
+    /// Priority 1-4
+    pub priority_high: Option<StyleConfig>,
+    /// Priority 5
+    pub priority_medium: Option<StyleConfig>,
+    /// Priority 6-9
+    pub priority_low: Option<StyleConfig>,
+    /// Tasks which need action
+    pub pending: Option<StyleConfig>,
+    /// Tasks in process
+    pub in_progress: Option<StyleConfig>,
+    /// Completed tasks
+    pub done: Option<StyleConfig>,
+    /// Tasks marked for batch operations
+    pub marked: Option<StyleConfig>,
+}
+
+/// Settings of the reminder daemon (`isek remind`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RemindConfig {
+    /// Where notifications are sent
+    pub sink: NotificationSink,
+    /// Seconds between two checks (the calendars are reloaded for every check)
+    pub interval: u64,
+    /// Notify when open tasks are due (in addition to their alarms)
+    pub due: bool,
+    /// Minutes an alarm is delivered late (e.g. when the daemon was not running)
+    ///
+    /// Older alarms are skipped.
+    pub catch_up: u64,
+}
+
+impl Default for RemindConfig {
+    fn default() -> Self {
+        Self {
+            sink: NotificationSink::default(),
+            interval: 60,
+            due: true,
+            catch_up: 60,
+        }
+    }
+}
+
+/// Enum representing the ways notifications are delivered
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub enum NotificationSink {
+    /// Print a line per notification
+    #[default]
+    Stdout,
+    /// Run a shell command with the notification in the environment variables `ISEK_SUMMARY`,
+    /// `ISEK_BODY`, `ISEK_CALENDAR`, `ISEK_UID` and `ISEK_TIME`
+    Command(String),
+    /// Send a desktop notification over the freedesktop notification D-Bus interface
+    ///
+    /// Runs `gdbus` (part of GLib), which has to be installed.
+    DBus,
+}
@@ -1,65 +1,616 @@
-use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, Utc};
+use std::sync::OnceLock;
+
+use chrono::{
+    DateTime, Datelike, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeDelta,
+    TimeZone, Utc, Weekday,
+};
 use chrono_tz::Tz;
+use ical::property::Property;
 use icalendar::{CalendarDateTime, DatePerhapsTime};
 
+use crate::timezone::resolve_tzid;
+
 pub const ICAL_NAIVE_DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
 pub const ICAL_UTC_DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
 pub const ICAL_NAIVE_DATE_FORMAT: &str = "%Y%m%d";
 
-/// Formats provided datetime object
+pub const INPUT_DATE_FORMAT: &str = "%Y-%m-%d";
+pub const INPUT_DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Color of calendars which don't define one
+pub const DEFAULT_CALENDAR_COLOR: &str = "#3a87ad";
+
+/// Timezone used for floating times and dates (the system timezone if not set)
+static TIMEZONE: OnceLock<Tz> = OnceLock::new();
+
+/// Use another timezone than the system timezone as local time (only possible once)
+pub fn set_local_timezone(tz: Tz) {
+    let _ = TIMEZONE.set(tz);
+}
+
+/// Convert an instant to the local time
+pub fn to_local(dt: &DateTime<Utc>) -> NaiveDateTime {
+    match TIMEZONE.get() {
+        Some(tz) => dt.with_timezone(tz).naive_local(),
+        None => dt.with_timezone(&Local).naive_local(),
+    }
+}
+
+/// Convert a local time to an instant (see [`resolve_local`] for DST transitions)
+pub fn from_local(dt: NaiveDateTime) -> DateTime<Utc> {
+    let resolved = match TIMEZONE.get() {
+        Some(tz) => resolve_local(dt, tz).map(|dt| dt.to_utc()),
+        None => resolve_local(dt, &Local).map(|dt| dt.to_utc()),
+    };
+
+    resolved.unwrap_or_else(|| dt.and_utc())
+}
+
+/// Current date in the local timezone
+pub fn local_today() -> NaiveDate {
+    to_local(&Utc::now()).date()
+}
+
+/// Interpret a time in a timezone
+///
+/// Ambiguous times (when the clock is set back) resolve to the earlier instant, times which
+/// don't exist (when the clock is set forward) are moved forward by the length of the gap.
+pub fn resolve_local<T: TimeZone>(dt: NaiveDateTime, tz: &T) -> Option<DateTime<T>> {
+    match dt.and_local_timezone(tz.clone()) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => Some(dt),
+        LocalResult::None => {
+            // Offset before the gap applied to the time gives the instant after it
+            let before = (dt - TimeDelta::days(1))
+                .and_local_timezone(tz.clone())
+                .earliest()?;
+            let offset = before.offset().fix().local_minus_utc();
+            let instant = dt.and_utc() - TimeDelta::seconds(offset as i64);
+
+            Some(instant.with_timezone(tz))
+        }
+    }
+}
+
+/// Formats provided datetime object in local time
 pub fn format_ical_datetime(dt: DatePerhapsTime, date_fmt: &str, dt_fmt: &str) -> String {
     match dt {
         DatePerhapsTime::Date(dt) => {
             format!("{}", dt.format(date_fmt))
         }
-        DatePerhapsTime::DateTime(dt) => match dt {
-            CalendarDateTime::Floating(dt) => {
-                format!("{}", dt.format(dt_fmt))
-            }
-            CalendarDateTime::Utc(dt) => {
-                format!(
-                    "{}",
-                    dt.with_timezone(&Local::now().timezone()).format(dt_fmt)
-                )
-            }
-            CalendarDateTime::WithTimezone { date_time, tzid } => {
-                match dt_with_timezone(date_time, &tzid) {
-                    Some(dt) => format!(
-                        "{}",
-                        dt.with_timezone(&Local::now().timezone()).format(dt_fmt)
-                    ),
-                    None => format!("{} {}", date_time.format(dt_fmt), tzid.clone()),
-                }
-            }
-        },
+        DatePerhapsTime::DateTime(CalendarDateTime::Floating(dt)) => {
+            format!("{}", dt.format(dt_fmt))
+        }
+        dt => format!("{}", to_local(&ical_datetime_to_chrono(dt)).format(dt_fmt)),
     }
 }
 
-// Creates timezone aware Datetime from ical datetime object
+/// Creates timezone aware Datetime from ical datetime object
+///
+/// Floating times, dates (at the start of the day) and times with an unknown TZID are
+/// interpreted in the local timezone.
 pub fn ical_datetime_to_chrono(dt: DatePerhapsTime) -> DateTime<Utc> {
     match dt {
-        DatePerhapsTime::Date(dt) => dt.and_time(NaiveTime::default()).and_utc(),
+        DatePerhapsTime::Date(date) => from_local(date.and_time(NaiveTime::default())),
         DatePerhapsTime::DateTime(dt) => match dt {
-            CalendarDateTime::Floating(dt) => dt.and_utc(),
+            CalendarDateTime::Floating(dt) => from_local(dt),
             CalendarDateTime::Utc(dt) => dt,
             CalendarDateTime::WithTimezone { date_time, tzid } => {
                 match dt_with_timezone(date_time, &tzid) {
-                    Some(dt) => dt.to_utc(),
-                    None => date_time.and_utc(),
+                    Some(dt) => dt,
+                    None => from_local(date_time),
                 }
             }
         },
     }
 }
 
+/// Instant of a deadline (e.g. DUE)
+///
+/// Unlike [`ical_datetime_to_chrono`], dates without time last until the end of the local day.
+pub fn ical_deadline_to_chrono(dt: DatePerhapsTime) -> DateTime<Utc> {
+    match dt {
+        DatePerhapsTime::Date(date) => end_of_local_day(date),
+        dt => ical_datetime_to_chrono(dt),
+    }
+}
+
+/// Last instant of a day in the local timezone
+pub fn end_of_local_day(date: NaiveDate) -> DateTime<Utc> {
+    let next_day = date
+        .succ_opt()
+        .unwrap_or(date)
+        .and_time(NaiveTime::default());
+
+    from_local(next_day) - TimeDelta::nanoseconds(1)
+}
+
 // Tries to convert a naive datetime into an timezone aware datetime based on a timezone ID
-pub fn dt_with_timezone(dt: NaiveDateTime, tzid: &str) -> Option<DateTime<Tz>> {
-    let tz: Tz = tzid.parse().ok()?;
-    dt.and_local_timezone(tz).earliest()
+//
+// See [`crate::timezone::resolve_tzid`] for the supported TZIDs.
+pub fn dt_with_timezone(dt: NaiveDateTime, tzid: &str) -> Option<DateTime<Utc>> {
+    resolve_tzid(dt, tzid)
 }
 
-pub fn calculate_index(priority: &u32, datetime: &DateTime<Utc>, now: &DateTime<Utc>) -> f64 {
-    let diff = *datetime - now;
+/// Formats provided datetime object relative to now (e.g. "today", "in 3d", "2w ago")
+pub fn format_ical_datetime_relative(dt: DatePerhapsTime, now: &DateTime<Utc>) -> String {
+    let is_date = matches!(dt, DatePerhapsTime::Date(_));
+    let dt = ical_datetime_to_chrono(dt);
+
+    let diff = dt - now;
+    // Compare calendar days in the local timezone
+    let days = (to_local(&dt).date() - to_local(now).date()).num_days();
+
+    if !is_date && diff.abs() < TimeDelta::days(1) {
+        let (value, unit) = match diff.abs() < TimeDelta::hours(1) {
+            true => (diff.num_minutes(), "min"),
+            false => (diff.num_hours(), "h"),
+        };
+
+        return format_relative(value, unit);
+    }
+
+    match days {
+        0 => "today".into(),
+        1 => "tomorrow".into(),
+        -1 => "yesterday".into(),
+        d if d.abs() < 14 => format_relative(d, "d"),
+        d if d.abs() < 60 => format_relative(d / 7, "w"),
+        d => format_relative(d / 30, "mo"),
+    }
+}
+
+fn format_relative(value: i64, unit: &str) -> String {
+    match value {
+        v if v < 0 => format!("{}{} ago", -v, unit),
+        v => format!("in {}{}", v, unit),
+    }
+}
+
+/// Value of the first property with the given name
+pub fn get_property<'a>(properties: &'a [Property], name: &str) -> Option<&'a str> {
+    properties
+        .iter()
+        .find(|p| p.name == name)
+        .and_then(|p| p.value.as_deref())
+}
+
+/// Name in the cache directory for a remote source (URL or command)
+pub fn cache_name(source: &str) -> String {
+    source
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Check if any property references the timezone with the given TZID
+pub fn references_timezone(properties: &[Property], tzid: &str) -> bool {
+    properties.iter().any(|p| {
+        p.params.iter().flatten().any(|(name, values)| {
+            name.eq_ignore_ascii_case("TZID") && values.iter().any(|v| v == tzid)
+        })
+    })
+}
+
+/// Set the value of a property while keeping its position and parameters
+///
+/// The property is appended if it doesn't exist. Returns the previous value.
+pub fn set_property(properties: &mut Vec<Property>, name: &str, value: String) -> Option<String> {
+    match properties.iter_mut().find(|p| p.name == name) {
+        Some(p) => p.value.replace(value),
+        None => {
+            properties.push(Property {
+                name: name.to_owned(),
+                params: None,
+                value: Some(value),
+            });
+            None
+        }
+    }
+}
+
+/// Remove a property without changing the order of the remaining properties
+///
+/// Returns the value of the removed property.
+pub fn remove_property(properties: &mut Vec<Property>, name: &str) -> Option<String> {
+    let idx = properties.iter().position(|p| p.name == name)?;
+    properties.remove(idx).value
+}
+
+/// Parse a date entered by the user
+///
+/// Supports "today", "tomorrow", weekdays ("friday", "next mon"), offsets like "+3d" or "+2w"
+/// and absolute dates ("2025-01-31") or datetimes ("2025-01-31 14:00") in local time.
+pub fn parse_date_input(input: &str, today: NaiveDate) -> Option<DatePerhapsTime> {
+    let input = input.trim().to_lowercase();
+    let weekday = input.trim_start_matches("next ").parse::<Weekday>().ok();
+
+    let date = match input.as_str() {
+        "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        _ if weekday.is_some() => weekday.map(|weekday| next_weekday(today, weekday)),
+        offset if offset.starts_with('+') => {
+            let offset = &offset[1..];
+            let unit = offset.chars().last()?;
+            let value: i64 = offset[..offset.len() - unit.len_utf8()].parse().ok()?;
+
+            match unit {
+                'd' => today.checked_add_signed(TimeDelta::days(value)),
+                'w' => today.checked_add_signed(TimeDelta::weeks(value)),
+                _ => None,
+            }
+        }
+        date => NaiveDate::parse_from_str(date, INPUT_DATE_FORMAT).ok(),
+    };
+
+    if let Some(date) = date {
+        return Some(DatePerhapsTime::Date(date));
+    }
+
+    let dt = NaiveDateTime::parse_from_str(&input, INPUT_DATE_TIME_FORMAT).ok()?;
+
+    Some(DatePerhapsTime::DateTime(CalendarDateTime::Utc(
+        from_local(dt),
+    )))
+}
+
+/// Next date with the given weekday after `date` (one week later if `date` is that weekday)
+pub fn next_weekday(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (weekday.num_days_from_monday() + 7 - date.weekday().num_days_from_monday()) % 7;
+
+    date + TimeDelta::days(match days {
+        0 => 7,
+        days => days as i64,
+    })
+}
+
+/// Value of a date property (e.g. DTSTART) with its VALUE or TZID parameter
+pub fn property_datetime(property: &Property) -> Option<DatePerhapsTime> {
+    parse_ical_datetime(property.value.as_deref()?, param_value(property, "TZID"))
+}
+
+/// Parse a date or datetime value (e.g. of EXDATE or UNTIL)
+pub fn parse_ical_datetime(value: &str, tzid: Option<&str>) -> Option<DatePerhapsTime> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, ICAL_NAIVE_DATE_FORMAT) {
+        return Some(DatePerhapsTime::Date(date));
+    }
+
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value, ICAL_UTC_DATE_TIME_FORMAT) {
+        return Some(CalendarDateTime::Utc(dt.and_utc()).into());
+    }
+
+    let date_time = NaiveDateTime::parse_from_str(value, ICAL_NAIVE_DATE_TIME_FORMAT).ok()?;
+
+    Some(
+        match tzid {
+            Some(tzid) => CalendarDateTime::WithTimezone {
+                date_time,
+                tzid: tzid.to_owned(),
+            },
+            None => CalendarDateTime::Floating(date_time),
+        }
+        .into(),
+    )
+}
+
+/// First value of a property parameter (e.g. TZID)
+pub fn param_value<'a>(property: &'a Property, name: &str) -> Option<&'a str> {
+    property
+        .params
+        .iter()
+        .flatten()
+        .find(|(param, _)| param.eq_ignore_ascii_case(name))
+        .and_then(|(_, values)| values.first())
+        .map(|value| value.as_str())
+}
 
-    (diff.as_seconds_f64() / 500e3).tanh() / 2.0 * 0.5 * (*priority as f64)
+/// Parse a duration (e.g. "PT1H30M" or "-P2D")
+pub fn parse_ical_duration(value: &str) -> Option<TimeDelta> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let mut duration = TimeDelta::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => in_time = true,
+            unit => {
+                let n: i64 = std::mem::take(&mut number).parse().ok()?;
+
+                duration += match (unit, in_time) {
+                    ('W', false) => TimeDelta::weeks(n),
+                    ('D', false) => TimeDelta::days(n),
+                    ('H', true) => TimeDelta::hours(n),
+                    ('M', true) => TimeDelta::minutes(n),
+                    ('S', true) => TimeDelta::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(if negative { -duration } else { duration })
+}
+
+/// Format a duration as iCalendar value (e.g. "-PT15M" or "P1D")
+pub fn format_ical_duration(duration: TimeDelta) -> String {
+    let sign = if duration < TimeDelta::zero() {
+        "-"
+    } else {
+        ""
+    };
+    let seconds = duration.num_seconds().abs();
+
+    let (days, hours, minutes, seconds) = (
+        seconds / 86400,
+        seconds % 86400 / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+    );
+
+    let mut time = String::new();
+    for (n, unit) in [(hours, 'H'), (minutes, 'M'), (seconds, 'S')] {
+        if n > 0 {
+            time.push_str(&format!("{n}{unit}"));
+        }
+    }
+
+    match (days, time.is_empty()) {
+        (0, true) => "PT0S".to_owned(),
+        (0, false) => format!("{sign}PT{time}"),
+        (days, true) => format!("{sign}P{days}D"),
+        (days, false) => format!("{sign}P{days}DT{time}"),
+    }
+}
+
+/// Local date of a date property (e.g. DUE)
+pub fn property_local_date(property: &Property) -> Option<NaiveDate> {
+    let value = property.value.as_deref()?;
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, ICAL_NAIVE_DATE_FORMAT) {
+        return Some(date);
+    }
+
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value, ICAL_UTC_DATE_TIME_FORMAT) {
+        return Some(to_local(&dt.and_utc()).date());
+    }
+
+    let dt = NaiveDateTime::parse_from_str(value, ICAL_NAIVE_DATE_TIME_FORMAT).ok()?;
+    let tzid = param_value(property, "TZID");
+
+    match tzid.and_then(|tzid| dt_with_timezone(dt, tzid)) {
+        Some(dt) => Some(to_local(&dt).date()),
+        None => Some(dt.date()),
+    }
+}
+
+/// Shift a date property (e.g. DUE) by a number of days
+///
+/// Keeps the parameters and whether it is a date or datetime. Times are shifted as they are
+/// written, so times with a TZID keep their time of day in that timezone, UTC and floating
+/// times keep theirs in UTC and local time.
+pub fn shift_date_property(property: &mut Property, days: i64) -> Option<()> {
+    let value = property.value.as_deref()?;
+    let delta = TimeDelta::days(days);
+
+    let shifted = if let Ok(date) = NaiveDate::parse_from_str(value, ICAL_NAIVE_DATE_FORMAT) {
+        (date + delta).format(ICAL_NAIVE_DATE_FORMAT).to_string()
+    } else if let Ok(dt) = NaiveDateTime::parse_from_str(value, ICAL_UTC_DATE_TIME_FORMAT) {
+        (dt + delta).format(ICAL_UTC_DATE_TIME_FORMAT).to_string()
+    } else {
+        let dt = NaiveDateTime::parse_from_str(value, ICAL_NAIVE_DATE_TIME_FORMAT).ok()?;
+        (dt + delta).format(ICAL_NAIVE_DATE_TIME_FORMAT).to_string()
+    };
+
+    property.value = Some(shifted);
+    Some(())
+}
+
+/// Create a date property (e.g. DUE) with the matching VALUE or TZID parameter
+pub fn date_property(name: &str, dt: &DatePerhapsTime) -> Property {
+    let (params, value) = match dt {
+        DatePerhapsTime::Date(date) => (
+            Some(vec![("VALUE".to_owned(), vec!["DATE".to_owned()])]),
+            date.format(ICAL_NAIVE_DATE_FORMAT).to_string(),
+        ),
+        DatePerhapsTime::DateTime(CalendarDateTime::Floating(dt)) => {
+            (None, dt.format(ICAL_NAIVE_DATE_TIME_FORMAT).to_string())
+        }
+        DatePerhapsTime::DateTime(CalendarDateTime::Utc(dt)) => {
+            (None, dt.format(ICAL_UTC_DATE_TIME_FORMAT).to_string())
+        }
+        DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, tzid }) => (
+            Some(vec![("TZID".to_owned(), vec![tzid.clone()])]),
+            date_time.format(ICAL_NAIVE_DATE_TIME_FORMAT).to_string(),
+        ),
+    };
+
+    Property {
+        name: name.to_owned(),
+        params,
+        value: Some(value),
+    }
+}
+
+/// Replace a property (including its parameters) while keeping its position
+///
+/// The property is appended if it doesn't exist. Returns the previous property.
+pub fn replace_property(properties: &mut Vec<Property>, property: Property) -> Option<Property> {
+    match properties.iter_mut().find(|p| p.name == property.name) {
+        Some(p) => Some(std::mem::replace(p, property)),
+        None => {
+            properties.push(property);
+            None
+        }
+    }
+}
+
+/// Next higher priority (lower value) stepping through the RFC 5545 buckets
+///
+/// Undefined (0) -> low (9) -> medium (5) -> high (1)
+pub fn raise_priority(priority: u32) -> u32 {
+    match priority {
+        0 => 9,
+        6..=9 => 5,
+        _ => 1,
+    }
+}
+
+/// Next lower priority (higher value) stepping through the RFC 5545 buckets
+///
+/// High (1-4) -> medium (5) -> low (9) -> undefined (0)
+pub fn lower_priority(priority: u32) -> u32 {
+    match priority {
+        1..=4 => 5,
+        5 => 9,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// Local timezone of all tests (it can only be set once)
+    fn berlin() -> Tz {
+        set_local_timezone(chrono_tz::Europe::Berlin);
+        chrono_tz::Europe::Berlin
+    }
+
+    fn naive(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        naive(s).and_utc()
+    }
+
+    #[test]
+    fn time_in_spring_forward_gap_moves_forward() {
+        let resolved = resolve_local(naive("2025-03-30 02:30"), &berlin()).unwrap();
+
+        assert_eq!(resolved.naive_local(), naive("2025-03-30 03:30"));
+        assert_eq!(resolved.to_utc(), utc("2025-03-30 01:30"));
+    }
+
+    #[test]
+    fn ambiguous_fall_back_time_resolves_to_earlier_instant() {
+        let resolved = resolve_local(naive("2025-10-26 02:30"), &berlin()).unwrap();
+
+        // Still summer time (UTC+2), the second 02:30 is an hour later
+        assert_eq!(resolved.to_utc(), utc("2025-10-26 00:30"));
+    }
+
+    #[test]
+    fn floating_and_date_values_use_configured_timezone() {
+        berlin();
+
+        let floating =
+            DatePerhapsTime::DateTime(CalendarDateTime::Floating(naive("2025-01-15 10:00")));
+        assert_eq!(ical_datetime_to_chrono(floating), utc("2025-01-15 09:00"));
+
+        let date = DatePerhapsTime::Date(NaiveDate::from_ymd_opt(2025, 7, 1).unwrap());
+        assert_eq!(
+            ical_datetime_to_chrono(date.clone()),
+            utc("2025-06-30 22:00")
+        );
+
+        // Deadlines last until the end of the local day
+        assert_eq!(
+            ical_deadline_to_chrono(date) + TimeDelta::nanoseconds(1),
+            utc("2025-07-01 22:00")
+        );
+        assert_eq!(
+            from_local(naive("2025-07-01 12:00")),
+            Utc.with_ymd_and_hms(2025, 7, 1, 10, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn unknown_tzid_falls_back_to_local_time() {
+        berlin();
+
+        let dt = DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone {
+            date_time: naive("2025-01-15 10:00"),
+            tzid: String::from("Mars/Olympus_Mons"),
+        });
+
+        assert_eq!(
+            dt_with_timezone(naive("2025-01-15 10:00"), "Mars/Olympus_Mons"),
+            None
+        );
+        assert_eq!(ical_datetime_to_chrono(dt), utc("2025-01-15 09:00"));
+    }
+
+    #[test]
+    fn known_tzid_ignores_local_time() {
+        berlin();
+
+        let dt = DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone {
+            date_time: naive("2025-01-15 10:00"),
+            tzid: String::from("America/New_York"),
+        });
+
+        assert_eq!(ical_datetime_to_chrono(dt), utc("2025-01-15 15:00"));
+    }
+
+    /// Shift a property by some days and return its value and TZID
+    fn shifted(value: &str, tzid: Option<&str>, days: i64) -> (String, Option<String>) {
+        berlin();
+
+        let mut property = Property {
+            name: String::from("DUE"),
+            params: tzid.map(|tzid| vec![(String::from("TZID"), vec![tzid.to_owned()])]),
+            value: Some(value.to_owned()),
+        };
+        shift_date_property(&mut property, days).unwrap();
+
+        let tzid = param_value(&property, "TZID").map(str::to_owned);
+        (property.value.unwrap(), tzid)
+    }
+
+    #[test]
+    fn shifting_keeps_utc_and_floating_times_as_they_are() {
+        // Stays at 08:00 UTC, 09:00 in Berlin becomes 10:00 in summer time
+        assert_eq!(shifted("20250325T080000Z", None, 7).0, "20250401T080000Z");
+        assert_eq!(shifted("20250401T080000Z", None, -7).0, "20250325T080000Z");
+
+        // Floating times are local times
+        assert_eq!(shifted("20250325T090000", None, 7).0, "20250401T090000");
+        assert_eq!(shifted("20250330", None, 1).0, "20250331");
+        assert_eq!(shifted("20250301", None, -1).0, "20250228");
+    }
+
+    #[test]
+    fn shifting_times_with_tzid_keeps_time_of_day_in_their_timezone() {
+        // New York switches to summer time two weeks before Berlin
+        assert_eq!(
+            shifted("20250305T090000", Some("America/New_York"), 7),
+            (
+                String::from("20250312T090000"),
+                Some(String::from("America/New_York"))
+            )
+        );
+        assert_eq!(
+            shifted("20250325T090000", Some("Europe/Berlin"), 7).0,
+            "20250401T090000"
+        );
+        assert_eq!(
+            shifted("20250325T090000", Some("W. Europe Standard Time"), 7).0,
+            "20250401T090000"
+        );
+
+        // Unknown TZIDs are shifted the same way
+        assert_eq!(
+            shifted("20250325T090000", Some("Mars/Olympus_Mons"), 7).0,
+            "20250401T090000"
+        );
+    }
 }
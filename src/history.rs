@@ -0,0 +1,72 @@
+use ical::{generator::Emitter, parser::ical::component::IcalCalendar};
+
+/// Maximum number of operations which can be undone
+const MAX_HISTORY: usize = 100;
+
+/// Content of a single calendar file before and after a modification
+///
+/// `None` means the file did not exist (e.g. before a task was created or after it was deleted)
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    /// Name of the calendar containing the file
+    pub calendar: String,
+    /// Name of the file (without extension)
+    pub file: String,
+    pub before: Option<IcalCalendar>,
+    pub after: Option<IcalCalendar>,
+}
+
+impl FileChange {
+    /// Whether the content of the file is the same before and after the modification
+    pub fn is_unchanged(&self) -> bool {
+        self.before.as_ref().map(Emitter::generate) == self.after.as_ref().map(Emitter::generate)
+    }
+}
+
+/// Undo/redo history of task modifications
+///
+/// Every entry contains all file changes of one operation (e.g. source and target of a move)
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Vec<FileChange>>,
+    redo: Vec<Vec<FileChange>>,
+}
+
+impl History {
+    /// Record a new operation (clears the redo stack)
+    ///
+    /// Files which weren't changed are left out, nothing is recorded if no file was changed.
+    pub fn push(&mut self, mut changes: Vec<FileChange>) {
+        changes.retain(|change| !change.is_unchanged());
+        if changes.is_empty() {
+            return;
+        }
+
+        self.undo.push(changes);
+        self.redo.clear();
+
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Take the last operation for undoing it
+    ///
+    /// Returns the changes which have to be reverted (restore `before`)
+    pub fn undo(&mut self) -> Option<Vec<FileChange>> {
+        let changes = self.undo.pop()?;
+        self.redo.push(changes.clone());
+
+        Some(changes)
+    }
+
+    /// Take the last undone operation for applying it again
+    ///
+    /// Returns the changes which have to be applied (restore `after`)
+    pub fn redo(&mut self) -> Option<Vec<FileChange>> {
+        let changes = self.redo.pop()?;
+        self.undo.push(changes.clone());
+
+        Some(changes)
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result, eyre};
+use ical::{
+    IcalParser,
+    generator::Emitter,
+    parser::ical::component::{IcalCalendar, IcalTimeZone, IcalTodo},
+};
+
+use crate::{
+    helper::{get_property, references_timezone},
+    timezone::{scope_timezones, unscope_timezones},
+};
+
+/// Start of the file if it doesn't contain a calendar yet
+const EMPTY_CALENDAR: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//isek//isek-rs//EN\r\n";
+
+/// Calendar stored in a single .ics file
+///
+/// Only the tasks are written when saving, everything else in the file (events, journals,
+/// timezones and properties of the calendar) is kept exactly as it was read. Files with several
+/// calendars (VCALENDAR) keep each task in the calendar it was read from.
+#[derive(Debug)]
+pub struct IcsFile {
+    path: PathBuf,
+    /// Calendars in the file (at least one)
+    blocks: Vec<Block>,
+}
+
+/// Single VCALENDAR of a file
+#[derive(Debug)]
+struct Block {
+    /// Content of the calendar without the tasks and the final `END:VCALENDAR`
+    frame: String,
+    /// TZIDs of the timezones defined in the calendar
+    timezones: HashSet<String>,
+    /// UIDs of the tasks in the order of the calendar
+    uids: Vec<String>,
+}
+
+impl IcsFile {
+    /// Read the file, multiple calendars in it are merged into one
+    ///
+    /// The timezones of every calendar get TZIDs of their own (see [`scope_timezones`]).
+    pub fn read(path: &Path) -> Result<(Self, IcalCalendar)> {
+        let content = fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read iCalendar file at {}", path.display()))?;
+
+        let mut ical = IcalCalendar::new();
+        let mut blocks = vec![];
+        for segment in split_calendars(&content) {
+            let Some(calendar) = IcalParser::new(segment.as_bytes()).next() else {
+                continue;
+            };
+            let mut calendar =
+                calendar.map_err(|e| eyre!("Error parsing file {}: {}", path.display(), e))?;
+
+            blocks.push(Block {
+                frame: strip_todos(segment),
+                timezones: calendar
+                    .timezones
+                    .iter()
+                    .filter_map(|tz| get_property(&tz.properties, "TZID"))
+                    .map(str::to_owned)
+                    .collect(),
+                uids: calendar
+                    .todos
+                    .iter()
+                    .filter_map(|t| get_property(&t.properties, "UID"))
+                    .map(str::to_owned)
+                    .collect(),
+            });
+
+            scope_timezones(&mut calendar);
+            if ical.properties.is_empty() {
+                ical.properties = calendar.properties;
+            }
+            ical.events.extend(calendar.events);
+            ical.todos.extend(calendar.todos);
+            ical.journals.extend(calendar.journals);
+            ical.free_busys.extend(calendar.free_busys);
+            ical.timezones.extend(calendar.timezones);
+        }
+
+        if blocks.is_empty() {
+            blocks.push(Block {
+                frame: EMPTY_CALENDAR.to_owned(),
+                timezones: HashSet::new(),
+                uids: vec![],
+            });
+        }
+
+        let file = Self {
+            path: path.to_owned(),
+            blocks,
+        };
+
+        Ok((file, ical))
+    }
+
+    /// Write the tasks of the given calendars into the file
+    ///
+    /// The tasks are written after all other components of the calendar they were read from in
+    /// their original order, new tasks at the end of the last calendar. Timezones used by the
+    /// tasks which their calendar doesn't define yet (e.g. of a task moved from another calendar)
+    /// are added as well.
+    pub fn write<'a>(&self, calendars: impl IntoIterator<Item = &'a IcalCalendar>) -> Result<()> {
+        // The file contains the original TZIDs (see [`scope_timezones`])
+        let calendars: Vec<IcalCalendar> = calendars
+            .into_iter()
+            .map(|calendar| {
+                let mut calendar = calendar.clone();
+                unscope_timezones(&mut calendar);
+                calendar
+            })
+            .collect();
+        let timezones: Vec<&IcalTimeZone> = calendars.iter().flat_map(|c| &c.timezones).collect();
+
+        // Index of the calendar in the file and position in it of every task
+        let mut todos: Vec<((usize, usize), &IcalTodo)> = calendars
+            .iter()
+            .flat_map(|c| &c.todos)
+            .map(|todo| {
+                let uid = get_property(&todo.properties, "UID").unwrap_or_default();
+                let position = self
+                    .blocks
+                    .iter()
+                    .enumerate()
+                    .find_map(|(idx, block)| Some((idx, block.uids.iter().position(|u| u == uid)?)))
+                    .unwrap_or((self.blocks.len() - 1, usize::MAX));
+
+                (position, todo)
+            })
+            .collect();
+        todos.sort_by_key(|(position, todo)| (*position, get_property(&todo.properties, "UID")));
+
+        let mut content = String::new();
+        for (idx, block) in self.blocks.iter().enumerate() {
+            let todos: Vec<&IcalTodo> = todos
+                .iter()
+                .filter(|((block, _), _)| *block == idx)
+                .map(|(_, todo)| *todo)
+                .collect();
+
+            content += &block.frame;
+
+            let mut added = HashSet::new();
+            for tz in &timezones {
+                let Some(tzid) = get_property(&tz.properties, "TZID") else {
+                    continue;
+                };
+
+                if !block.timezones.contains(tzid)
+                    && !added.contains(tzid)
+                    && todos
+                        .iter()
+                        .any(|t| references_timezone(&t.properties, tzid))
+                {
+                    content += &tz.generate();
+                    added.insert(tzid);
+                }
+            }
+
+            for todo in todos {
+                content += &todo.generate();
+            }
+            content += "END:VCALENDAR\r\n";
+        }
+
+        fs::write(&self.path, content)
+            .wrap_err_with(|| format!("Failed to write iCalendar file at {}", self.path.display()))
+    }
+}
+
+/// Calendars of the content, each up to and including its `END:VCALENDAR` line
+///
+/// Anything after the last calendar is dropped.
+fn split_calendars(content: &str) -> Vec<&str> {
+    let mut calendars = vec![];
+    let mut start = 0;
+    let mut end = 0;
+
+    for line in content.split_inclusive('\n') {
+        end += line.len();
+
+        if line.trim_end().eq_ignore_ascii_case("END:VCALENDAR") {
+            calendars.push(&content[start..end]);
+            start = end;
+        }
+    }
+
+    calendars
+}
+
+/// Calendar without the tasks (VTODO) and its final `END:VCALENDAR`
+fn strip_todos(calendar: &str) -> String {
+    let mut frame = String::new();
+    let mut in_todo = false;
+
+    for line in calendar.split_inclusive('\n') {
+        let name = line.trim_end();
+
+        if in_todo {
+            in_todo = !name.eq_ignore_ascii_case("END:VTODO");
+            continue;
+        }
+        if name.eq_ignore_ascii_case("BEGIN:VTODO") {
+            in_todo = true;
+            continue;
+        }
+        if name.eq_ignore_ascii_case("END:VCALENDAR") {
+            break;
+        }
+
+        frame.push_str(line);
+    }
+
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::set_property;
+
+    const FIRST: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:test\r
+BEGIN:VTIMEZONE\r
+TZID:Customized Time Zone\r
+BEGIN:STANDARD\r
+DTSTART:19700101T000000\r
+TZOFFSETFROM:+0500\r
+TZOFFSETTO:+0500\r
+END:STANDARD\r
+END:VTIMEZONE\r
+BEGIN:VEVENT\r
+UID:event-1\r
+DTSTART;TZID=Customized Time Zone:20250301T090000\r
+SUMMARY:Meeting\r
+END:VEVENT\r
+BEGIN:VTODO\r
+UID:task-1\r
+DUE;TZID=Customized Time Zone:20250302T090000\r
+SUMMARY:First\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+    const SECOND: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:other\r
+BEGIN:VTODO\r
+UID:task-3\r
+SUMMARY:Third\r
+END:VTODO\r
+BEGIN:VEVENT\r
+UID:event-2\r
+DTSTART;VALUE=DATE:20250303\r
+SUMMARY:Holiday\r
+END:VEVENT\r
+BEGIN:VTODO\r
+UID:task-2\r
+SUMMARY:Second\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn tasks_stay_in_their_calendar() {
+        let dir = std::env::temp_dir().join(format!("isek-ics-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tasks.ics");
+        fs::write(&path, format!("{FIRST}{SECOND}")).unwrap();
+
+        let (file, mut ical) = IcsFile::read(&path).unwrap();
+        assert_eq!(ical.todos.len(), 3);
+        assert_eq!(ical.events.len(), 2);
+
+        // Unchanged tasks are written exactly as they were read (tasks after the other
+        // components of their calendar)
+        file.write([&ical]).unwrap();
+        let second = SECOND.replace(
+            "BEGIN:VTODO\r\nUID:task-3\r\nSUMMARY:Third\r\nEND:VTODO\r\n",
+            "",
+        );
+        let second = second.replace(
+            "END:VEVENT\r\n",
+            "END:VEVENT\r\nBEGIN:VTODO\r\nUID:task-3\r\nSUMMARY:Third\r\nEND:VTODO\r\n",
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{FIRST}{second}")
+        );
+
+        // New tasks are added to the last calendar
+        let mut new = ical.todos[2].clone();
+        set_property(&mut new.properties, "UID", "task-4".to_owned());
+        set_property(&mut new.properties, "SUMMARY", "Fourth".to_owned());
+        ical.todos.insert(0, new);
+        file.write([&ical]).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let (_, end) = content.split_once("PRODID:other").unwrap();
+        assert!(end.ends_with("UID:task-4\r\nSUMMARY:Fourth\r\nEND:VTODO\r\nEND:VCALENDAR\r\n"));
+        assert_eq!(content.matches("BEGIN:VCALENDAR").count(), 2);
+        assert_eq!(content.matches("BEGIN:VTIMEZONE").count(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use icalendar::Component;
+
+use crate::{app::IsekTodo, config::IndexConfig, helper::ical_deadline_to_chrono};
+
+/// Weighted components of the isek index of a task
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IndexScore {
+    pub priority: f64,
+    pub due: f64,
+    pub overdue: f64,
+    pub deferred: f64,
+    pub age: f64,
+    pub tags: f64,
+}
+
+impl IndexScore {
+    /// Calculate the index of a task (see [`IndexConfig`])
+    pub fn new(todo: &IsekTodo, config: &IndexConfig, now: &DateTime<Utc>) -> Self {
+        let t = todo.get();
+
+        // 1 (highest) is scaled to 1.0, 9 (lowest) to 0.11 and undefined to 0
+        let priority = match t.get_priority() {
+            Some(prio @ 1..=9) => (10 - prio) as f64 / 9.0,
+            _ => 0.0,
+        };
+
+        // Rises from 0 (far in the future) over 0.5 (due now) to 1 (long overdue)
+        let due = match t.get_due() {
+            Some(dt) => {
+                let days = (ical_deadline_to_chrono(dt) - *now).as_seconds_f64() / 86400.0;
+                0.5 - (days / config.horizon_days.max(f64::EPSILON)).tanh() / 2.0
+            }
+            None => 0.0,
+        };
+
+        let age = match t.get_created() {
+            Some(created) => {
+                let days = (*now - created).as_seconds_f64() / 86400.0;
+                (days / config.age_days.max(f64::EPSILON)).clamp(0.0, 1.0)
+            }
+            None => 0.0,
+        };
+
+        let tags = todo
+            .tags()
+            .iter()
+            .filter_map(|tag| config.tags.get(tag))
+            .fold(0.0, |sum, weight| sum + weight);
+
+        Self {
+            priority: config.priority * priority,
+            due: config.due * due,
+            overdue: match todo.is_overdue(now) {
+                true => config.overdue,
+                false => 0.0,
+            },
+            deferred: match todo.is_deferred(now) {
+                true => config.deferred,
+                false => 0.0,
+            },
+            age: config.age * age,
+            tags,
+        }
+    }
+
+    /// Sum of all components
+    pub fn total(&self) -> f64 {
+        self.priority + self.due + self.overdue + self.deferred + self.age + self.tags
+    }
+}
+
+impl Display for IndexScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Index {:.2} = priority {:.2} + due {:.2} + overdue {:.2} + deferred {:.2} + age {:.2} + tags {:.2}",
+            self.total(),
+            self.priority,
+            self.due,
+            self.overdue,
+            self.deferred,
+            self.age,
+            self.tags
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, TimeZone};
+    use colors_transform::{Color, Rgb};
+    use icalendar::{EventLike, Todo};
+
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap()
+    }
+
+    /// Index of a task with the default weights and the given tag weights
+    fn score(todo: &Todo, tags: &[(&str, f64)]) -> IndexScore {
+        let config = IndexConfig {
+            tags: tags.iter().map(|(t, w)| (t.to_string(), *w)).collect(),
+            ..IndexConfig::default()
+        };
+        let (name, display_name, color) = (String::from("tasks"), None, Rgb::new());
+
+        IndexScore::new(
+            &IsekTodo::new(&name, &display_name, &color, todo),
+            &config,
+            &now(),
+        )
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn task_without_values_scores_zero() {
+        assert_eq!(score(&Todo::new().done(), &[]), IndexScore::default());
+    }
+
+    #[test]
+    fn priority_is_scaled_from_highest_to_lowest() {
+        let priority = |p| score(&Todo::new().priority(p).done(), &[]).priority;
+
+        assert_close(priority(1), 3.0);
+        assert_close(priority(5), 3.0 * 5.0 / 9.0);
+        assert_close(priority(9), 3.0 / 9.0);
+        // Undefined
+        assert_close(priority(0), 0.0);
+    }
+
+    #[test]
+    fn due_date_rises_towards_overdue() {
+        let due = |days| score(&Todo::new().due(now() + TimeDelta::days(days)).done(), &[]);
+
+        assert_close(due(0).due, 2.0);
+        assert_close(due(0).overdue, 0.0);
+        assert_close(due(7).due, 4.0 * (0.5 - 1f64.tanh() / 2.0));
+        assert!(due(365).due < 0.01);
+
+        let overdue = due(-7);
+        assert_close(overdue.due, 4.0 * (0.5 + 1f64.tanh() / 2.0));
+        assert_close(overdue.overdue, 2.0);
+
+        // Completed tasks aren't overdue
+        let completed = Todo::new()
+            .due(now() - TimeDelta::days(7))
+            .completed(now())
+            .done();
+        assert_close(score(&completed, &[]).overdue, 0.0);
+    }
+
+    #[test]
+    fn future_start_defers_the_task() {
+        let start = |days| {
+            score(
+                &Todo::new().starts(now() + TimeDelta::days(days)).done(),
+                &[],
+            )
+        };
+
+        assert_close(start(1).deferred, -3.0);
+        assert_close(start(-1).deferred, 0.0);
+    }
+
+    #[test]
+    fn age_is_capped() {
+        let age =
+            |created: &str| score(&Todo::new().add_property("CREATED", created).done(), &[]).age;
+
+        assert_close(age("20261003T120000Z"), 0.25);
+        assert_close(age("20260701T120000Z"), 0.5);
+        // Created in the future
+        assert_close(age("20261020T120000Z"), 0.0);
+    }
+
+    #[test]
+    fn weights_of_all_tags_are_added() {
+        let todo = Todo::new()
+            .add_multi_property("CATEGORIES", "urgent, later,other")
+            .done();
+
+        let tags = [("urgent", 2.0), ("later", -0.5), ("unused", 10.0)];
+        assert_close(score(&todo, &tags).tags, 1.5);
+        assert_close(score(&todo, &[]).tags, 0.0);
+    }
+
+    #[test]
+    fn total_is_the_sum_of_all_components() {
+        let todo = Todo::new()
+            .priority(1)
+            .due(now() - TimeDelta::days(7))
+            .starts(now() + TimeDelta::days(1))
+            .add_property("CREATED", "20261003T120000Z")
+            .add_multi_property("CATEGORIES", "urgent")
+            .done();
+        let score = score(&todo, &[("urgent", 2.0)]);
+
+        let due = 4.0 * (0.5 + 1f64.tanh() / 2.0);
+        assert_close(score.total(), 3.0 + due + 2.0 - 3.0 + 0.25 + 2.0);
+        assert_eq!(
+            score.to_string(),
+            format!(
+                "Index {:.2} = priority 3.00 + due {due:.2} + overdue 2.00 + deferred -3.00 + age 0.25 + tags 2.00",
+                score.total()
+            )
+        );
+    }
+}
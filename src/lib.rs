@@ -1,3 +1,5 @@
+pub mod agenda;
+pub mod caldav;
 pub mod config;
 
 pub mod app;
@@ -6,6 +8,22 @@ pub use app::App;
 pub mod widgets;
 
 mod view;
-pub use view::{MainView, View};
+pub use view::{AgendaView, CalendarView, DetailView, MainView, StatsView, View, Views};
 
 pub mod helper;
+pub mod ics_file;
+
+pub mod history;
+pub mod index;
+
+pub mod line_format;
+pub mod recurrence;
+pub mod remind;
+pub mod sort;
+pub mod subscription;
+
+pub mod theme;
+pub mod timezone;
+
+#[cfg(test)]
+mod test_server;
@@ -0,0 +1,433 @@
+use std::str::FromStr;
+
+use eyre::{Report, Result, eyre};
+use ratatui::text::{Line, Span};
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+/// Fields which can be used in the line format
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Progress,
+    Priority,
+    Summary,
+    Tags,
+    Due,
+    Start,
+    Calendar,
+    Location,
+    Percent,
+    Status,
+    Index,
+}
+
+impl FromStr for Field {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "progress" => Field::Progress,
+            "priority" => Field::Priority,
+            "summary" => Field::Summary,
+            "tags" => Field::Tags,
+            "due" => Field::Due,
+            "start" => Field::Start,
+            "calendar" => Field::Calendar,
+            "location" => Field::Location,
+            "percent" => Field::Percent,
+            "status" => Field::Status,
+            "index" => Field::Index,
+            _ => return Err(eyre!("Unknown field '{{{}}}' in line format", s)),
+        })
+    }
+}
+
+impl Field {
+    /// Short name used e.g. as table header
+    pub fn title(&self) -> &'static str {
+        match self {
+            Field::Progress => "",
+            Field::Priority => "!",
+            Field::Summary => "Summary",
+            Field::Tags => "Tags",
+            Field::Due => "Due",
+            Field::Start => "Start",
+            Field::Calendar => "Calendar",
+            Field::Location => "Location",
+            Field::Percent => "%",
+            Field::Status => "Status",
+            Field::Index => "Index",
+        }
+    }
+}
+
+/// Placeholder for a field with its formatting options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldFormat {
+    pub field: Field,
+    /// Display dates relative to now (e.g. "in 3d")
+    pub relative: bool,
+    /// Maximum width of the field (longer values are truncated)
+    pub width: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Field(FieldFormat),
+    AlignRight,
+}
+
+/// Parsed line format template
+///
+/// Fields are written as `{name}` or `{name:option}` where option is `relative` (for dates)
+/// or a maximum width. Everything after `{>}` is aligned to the right edge of the line.
+/// Use `{{` and `}}` for literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineFormat {
+    left: Vec<Segment>,
+    right: Vec<Segment>,
+}
+
+pub const DEFAULT_LINE_FORMAT: &str = "{progress}{calendar} {summary} {due}";
+
+impl Default for LineFormat {
+    fn default() -> Self {
+        DEFAULT_LINE_FORMAT.parse().unwrap()
+    }
+}
+
+impl FromStr for LineFormat {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut segments = vec![];
+        let mut text = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(eyre!("Unclosed '{{' in line format '{}'", s)),
+                        }
+                    }
+
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+
+                    segments.push(match placeholder.as_str() {
+                        ">" => Segment::AlignRight,
+                        _ => Segment::Field(parse_placeholder(&placeholder)?),
+                    });
+                }
+                '}' => return Err(eyre!("Unexpected '}}' in line format '{}'", s)),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        let mut parts = segments.split(|s| *s == Segment::AlignRight);
+        let left = parts.next().unwrap_or_default().to_vec();
+        let right = parts.next().unwrap_or_default().to_vec();
+
+        if parts.next().is_some() {
+            return Err(eyre!("Multiple '{{>}}' in line format '{}'", s));
+        }
+
+        Ok(Self { left, right })
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<FieldFormat> {
+    let (name, option) = match placeholder.split_once(':') {
+        Some((name, option)) => (name, Some(option)),
+        None => (placeholder, None),
+    };
+
+    let mut format = FieldFormat {
+        field: name.trim().parse()?,
+        relative: false,
+        width: None,
+    };
+
+    match option.map(str::trim) {
+        None => {}
+        Some("relative") => format.relative = true,
+        Some(width) => {
+            format.width = Some(width.parse().map_err(|_| {
+                eyre!(
+                    "Invalid option '{}' for field '{}' in line format",
+                    width,
+                    name
+                )
+            })?)
+        }
+    }
+
+    Ok(format)
+}
+
+impl LineFormat {
+    /// Check if the line starts with the given field
+    pub fn starts_with(&self, field: Field) -> bool {
+        matches!(self.left.first(), Some(Segment::Field(f)) if f.field == field)
+    }
+
+    /// Render a line with at most `width` columns
+    ///
+    /// `render_field` is called for every placeholder and returns its content.
+    /// The summary field takes the remaining space and is truncated with an ellipsis if needed.
+    pub fn render<'a, F>(&self, width: usize, mut render_field: F) -> Line<'a>
+    where
+        F: FnMut(&FieldFormat) -> Span<'a>,
+    {
+        let mut render = |segments: &[Segment]| {
+            let mut spans: Vec<(Option<Field>, Span<'a>)> = vec![];
+            let mut skip_space = false;
+
+            for segment in segments {
+                match segment {
+                    Segment::Text(text) => {
+                        // Collapse the separator after an empty field
+                        let text = match skip_space {
+                            true => text.strip_prefix(' ').unwrap_or(text),
+                            false => text,
+                        };
+                        spans.push((None, Span::raw(text.to_owned())));
+                        skip_space = false;
+                    }
+                    Segment::Field(format) => {
+                        let mut span = render_field(format);
+                        if let Some(max) = format.width {
+                            span.content = truncate(&span.content, max).into();
+                        }
+                        skip_space = span.content.is_empty();
+                        spans.push((Some(format.field), span));
+                    }
+                    Segment::AlignRight => {}
+                }
+            }
+
+            spans
+        };
+
+        let mut left = render(&self.left);
+        let mut right = render(&self.right);
+
+        let used = |spans: &[(Option<Field>, Span)]| {
+            spans
+                .iter()
+                .filter(|(f, _)| *f != Some(Field::Summary))
+                .map(|(_, s)| s.width())
+                .sum::<usize>()
+        };
+
+        // Shrink summary to the remaining space
+        let mut available = width.saturating_sub(used(&left) + used(&right));
+        for (field, span) in left.iter_mut().chain(right.iter_mut()) {
+            if *field == Some(Field::Summary) {
+                span.content = truncate(&span.content, available).into();
+                available = available.saturating_sub(span.width());
+            }
+        }
+
+        let left_width: usize = left.iter().map(|(_, s)| s.width()).sum();
+        let right_width: usize = right.iter().map(|(_, s)| s.width()).sum();
+
+        let mut spans: Vec<Span<'a>> = left.into_iter().map(|(_, s)| s).collect();
+        if !right.is_empty() {
+            spans.push(Span::raw(
+                " ".repeat(width.saturating_sub(left_width + right_width)),
+            ));
+            spans.extend(right.into_iter().map(|(_, s)| s));
+        }
+
+        Line::from(spans)
+    }
+}
+
+/// Truncate string to the given display width and add an ellipsis if it was shortened
+pub fn truncate(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_owned();
+    }
+
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut res = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = c.to_string().width();
+        if used + w > width - 1 {
+            break;
+        }
+        used += w;
+        res.push(c);
+    }
+
+    let mut res = res.trim_end().to_owned();
+    res.push('…');
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render a format with fixed field contents (dates depend on the relative option)
+    fn render(format: &str, width: usize, fields: &[(Field, &str)]) -> String {
+        let format: LineFormat = format.parse().unwrap();
+        let line = format.render(width, |f| match f.field {
+            Field::Due if f.relative => Span::raw("in 3d"),
+            Field::Due => Span::raw("2025-04-01"),
+            field => Span::raw(
+                fields
+                    .iter()
+                    .find(|(f, _)| *f == field)
+                    .map_or("", |(_, content)| content)
+                    .to_owned(),
+            ),
+        });
+
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn double_braces_are_literal() {
+        let fields = [(Field::Summary, "Task")];
+
+        assert_eq!(render("{{{summary}}}", 20, &fields), "{Task}");
+        assert_eq!(
+            render("{{summary}} {summary}", 20, &fields),
+            "{summary} Task"
+        );
+        assert_eq!(render("{summary}{>}}}", 10, &fields), "Task     }");
+    }
+
+    #[test]
+    fn invalid_formats_are_rejected() {
+        for format in [
+            "{summary",
+            "summary}",
+            "{unknown}",
+            "{due:soon}",
+            "{summary}{>}{due}{>}",
+        ] {
+            assert!(format.parse::<LineFormat>().is_err(), "{format}");
+        }
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        let format: LineFormat = "{due:relative} {summary: 5}".parse().unwrap();
+
+        assert_eq!(
+            format.left,
+            vec![
+                Segment::Field(FieldFormat {
+                    field: Field::Due,
+                    relative: true,
+                    width: None,
+                }),
+                Segment::Text(" ".to_owned()),
+                Segment::Field(FieldFormat {
+                    field: Field::Summary,
+                    relative: false,
+                    width: Some(5),
+                }),
+            ]
+        );
+        assert!(format.right.is_empty());
+
+        assert_eq!(render("{due}", 20, &[]), "2025-04-01");
+        assert_eq!(render("{due:relative}", 20, &[]), "in 3d");
+    }
+
+    #[test]
+    fn fields_after_align_right_end_at_the_edge() {
+        let fields = [(Field::Summary, "Task"), (Field::Calendar, "Work")];
+
+        assert_eq!(
+            render("{summary}{>}{due:relative} {calendar}", 20, &fields),
+            "Task      in 3d Work"
+        );
+        // Nothing is padded without right aligned fields
+        assert_eq!(render("{summary} {calendar}", 20, &fields), "Task Work");
+    }
+
+    #[test]
+    fn summary_is_truncated_to_the_remaining_space() {
+        let fields = [(Field::Summary, "A long summary text")];
+
+        assert_eq!(
+            render("{summary}{>}{due:relative}", 12, &fields),
+            "A long…in 3d"
+        );
+        assert_eq!(
+            render("{summary} {due:relative}", 40, &fields),
+            "A long summary text in 3d"
+        );
+        // Other fields are kept even if there is no space left for the summary
+        assert_eq!(render("{summary}{>}{due}", 8, &fields), "2025-04-01");
+    }
+
+    #[test]
+    fn fields_are_truncated_to_their_width() {
+        let fields = [(Field::Calendar, "Personal"), (Field::Summary, "Task")];
+
+        assert_eq!(render("{calendar:4} {summary}", 20, &fields), "Per… Task");
+        assert_eq!(
+            render("{calendar:8} {summary}", 20, &fields),
+            "Personal Task"
+        );
+        assert_eq!(render("{calendar:0} {summary}", 20, &fields), "Task");
+    }
+
+    #[test]
+    fn truncate_counts_display_width() {
+        assert_eq!(truncate("abc", 3), "abc");
+        assert_eq!(truncate("abc", 0), "");
+        assert_eq!(truncate("abcd", 1), "…");
+        // Trailing spaces are dropped before the ellipsis
+        assert_eq!(truncate("ab cd", 4), "ab…");
+
+        // Multi-byte characters are cut between characters
+        assert_eq!(truncate("Café crème", 10), "Café crème");
+        assert_eq!(truncate("Café crème", 6), "Café…");
+        assert_eq!(truncate("Crème", 3), "Cr…");
+
+        // Wide characters take two columns and are never split
+        assert_eq!(truncate("日本語のタスク", 14), "日本語のタスク");
+        assert_eq!(truncate("日本語のタスク", 7), "日本語…");
+        assert_eq!(truncate("日本語", 4), "日…");
+        assert_eq!(truncate("日本語", 4).width(), 3);
+    }
+
+    #[test]
+    fn wide_summaries_fit_the_line() {
+        let fields = [(Field::Summary, "日本語のタスク")];
+        let line = render("{summary}{>}{due:relative}", 12, &fields);
+
+        assert_eq!(line, "日本語…in 3d");
+        assert_eq!(line.width(), 12);
+    }
+}
@@ -1,17 +1,27 @@
 use std::io::stdout;
 
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use color_eyre::eyre::Result;
 use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{EnterAlternateScreen, enable_raw_mode},
 };
-use isek_rs::{App, MainView, View};
+use isek_rs::{App, Views, config::NotificationSink, remind};
 use ratatui::{DefaultTerminal, Terminal, prelude::CrosstermBackend};
 
 /// Entry point of the application
 fn main() -> Result<()> {
+    let matches = cli().get_matches();
+
+    // Deliver reminders without starting the UI
+    if let Some(matches) = matches.subcommand_matches("remind") {
+        color_eyre::install()?;
+        return remind::run(matches.get_flag("once"), sink(matches));
+    }
+
     // Initialize alternate screen and raw mode for terminal input handling
-    execute!(stdout(), EnterAlternateScreen)?;
+    execute!(stdout(), EnterAlternateScreen, EnableMouseCapture)?;
     enable_raw_mode()?;
 
     // Set up panic hook to restore terminal state on panic
@@ -28,35 +38,79 @@ fn main() -> Result<()> {
     let res = run(&mut terminal, &mut app);
 
     //// Restore terminal to its original state before exiting
-    ratatui::restore();
+    restore();
     res
 }
 
+/// Command line interface
+fn cli() -> Command {
+    Command::new("isek")
+        .about("Terminal task manager for iCalendar tasks")
+        .subcommand(
+            Command::new("remind")
+                .about("Deliver notifications for alarms and due tasks")
+                .arg(
+                    Arg::new("once")
+                        .long("once")
+                        .action(ArgAction::SetTrue)
+                        .help("Check once and exit (e.g. when run periodically by cron)"),
+                )
+                .arg(
+                    Arg::new("sink")
+                        .long("sink")
+                        .value_parser(["stdout", "dbus"])
+                        .help("Where notifications are sent (overrides the config)"),
+                )
+                .arg(
+                    Arg::new("command")
+                        .long("command")
+                        .conflicts_with("sink")
+                        .help("Shell command run for every notification (overrides the config)"),
+                ),
+        )
+}
+
+/// Notification sink given on the command line
+fn sink(matches: &ArgMatches) -> Option<NotificationSink> {
+    if let Some(command) = matches.get_one::<String>("command") {
+        return Some(NotificationSink::Command(command.clone()));
+    }
+
+    match matches.get_one::<String>("sink").map(String::as_str) {
+        Some("stdout") => Some(NotificationSink::Stdout),
+        Some("dbus") => Some(NotificationSink::DBus),
+        _ => None,
+    }
+}
+
 /// Sets a custom panic hook that restores the terminal before showing the panic message.
 fn set_panic_hook() {
     let hook = std::panic::take_hook();
 
     std::panic::set_hook(Box::new(move |panic_info| {
         // Make sure to restore the terminal state even if the program panics
-        ratatui::restore();
+        restore();
 
         // Call original panic hook to display the error message
         hook(panic_info)
     }));
 }
 
+/// Disables mouse capture and restores the terminal
+fn restore() {
+    let _ = execute!(stdout(), DisableMouseCapture);
+    ratatui::restore();
+}
+
 /// Main application loop that handles drawing and event handling.
 fn run(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
-    // Create main view instance for rendering UI
-    let view = MainView;
-
     // Application loop - continues until exit flag is set to true
     while !app.exit {
-        // Draw the current application state in the terminal
-        terminal.draw(|f| view.draw(app, f))?;
+        // Draw the tab bar and the active view in the terminal
+        terminal.draw(|f| Views::draw(app, f))?;
 
-        // Handle user input and other events
-        view.handle_events(app)?;
+        // Handle user input and other events with the active view
+        Views::handle_events(app)?;
     }
 
     Ok(())
@@ -0,0 +1,474 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, TimeDelta, Weekday};
+use icalendar::DatePerhapsTime;
+
+use crate::helper::parse_ical_datetime;
+
+/// Frequency of a recurrence rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Recurrence rule of an event (RRULE)
+///
+/// Supports `FREQ` (daily to yearly), `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY`, `BYMONTHDAY` and
+/// `BYMONTH`. Rules with other parts (e.g. `BYSETPOS`) expand as if these parts were missing.
+#[derive(Debug, Clone)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<usize>,
+    /// Last possible occurrence (inclusive)
+    pub until: Option<DatePerhapsTime>,
+    /// Weekdays, optionally the nth one of the month (or year), negative counts from the end
+    by_day: Vec<(Option<i32>, Weekday)>,
+    /// Days of the month, negative counts from the end
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+/// Upper bound for the number of periods checked (protects against rules without matches)
+const MAX_PERIODS: u32 = 100_000;
+
+impl RecurrenceRule {
+    /// Parse the value of an RRULE property (e.g. `FREQ=WEEKLY;BYDAY=MO,WE`)
+    pub fn parse(rrule: &str) -> Option<Self> {
+        let parts: HashMap<String, &str> = rrule
+            .split(';')
+            .filter_map(|part| part.split_once('='))
+            .map(|(name, value)| (name.to_ascii_uppercase(), value))
+            .collect();
+
+        let frequency = match parts.get("FREQ")?.to_ascii_uppercase().as_str() {
+            "DAILY" => Frequency::Daily,
+            "WEEKLY" => Frequency::Weekly,
+            "MONTHLY" => Frequency::Monthly,
+            "YEARLY" => Frequency::Yearly,
+            _ => return None,
+        };
+
+        let list = |name: &str| {
+            parts
+                .get(name)
+                .map(|values| values.split(',').collect::<Vec<&str>>())
+                .unwrap_or_default()
+        };
+
+        Some(Self {
+            frequency,
+            interval: parts
+                .get("INTERVAL")
+                .and_then(|i| i.parse().ok())
+                .filter(|i| *i > 0)
+                .unwrap_or(1),
+            count: parts.get("COUNT").and_then(|c| c.parse().ok()),
+            until: parts
+                .get("UNTIL")
+                .and_then(|until| parse_ical_datetime(until, None)),
+            by_day: list("BYDAY")
+                .into_iter()
+                .filter_map(parse_weekday)
+                .collect(),
+            by_month_day: list("BYMONTHDAY")
+                .into_iter()
+                .filter_map(|d| d.parse().ok())
+                .collect(),
+            by_month: list("BYMONTH")
+                .into_iter()
+                .filter_map(|m| m.parse().ok())
+                .collect(),
+        })
+    }
+
+    /// Occurrences of the rule starting with `start` (the DTSTART) from `after` until `before`
+    ///
+    /// Times are local to the timezone of the event, `COUNT` is respected but `UNTIL` isn't
+    /// (it has to be compared as instant, see [`RecurrenceRule::until`]). Periods before `after`
+    /// are skipped without expanding them, unless the occurrences have to be counted.
+    pub fn occurrences(
+        &self,
+        start: NaiveDateTime,
+        after: NaiveDateTime,
+        before: NaiveDateTime,
+    ) -> Vec<NaiveDateTime> {
+        let mut occurrences = vec![];
+        let mut count = 0;
+        let first = self.first_period(start.date(), after.date());
+
+        for period in first..first.saturating_add(MAX_PERIODS) {
+            let Some(period_start) =
+                self.period_start(start.date(), period.saturating_mul(self.interval))
+            else {
+                break;
+            };
+
+            if period_start.and_time(start.time()) >= before {
+                break;
+            }
+
+            for date in self.dates_in_period(period_start, start.date()) {
+                let occurrence = date.and_time(start.time());
+
+                if occurrence < start {
+                    continue;
+                }
+                if occurrence >= before || self.count.is_some_and(|c| count >= c) {
+                    return occurrences;
+                }
+
+                count += 1;
+                if occurrence >= after {
+                    occurrences.push(occurrence);
+                }
+            }
+        }
+
+        occurrences
+    }
+
+    /// Number of the first period which can contain dates from `after` on
+    ///
+    /// Always the first period for rules with `COUNT`, their occurrences are counted from the start.
+    fn first_period(&self, start: NaiveDate, after: NaiveDate) -> u32 {
+        if self.count.is_some() || after <= start {
+            return 0;
+        }
+
+        let monday =
+            |date: NaiveDate| date - TimeDelta::days(date.weekday().num_days_from_monday() as i64);
+        let elapsed = match self.frequency {
+            Frequency::Daily => (after - start).num_days(),
+            Frequency::Weekly => (monday(after) - monday(start)).num_weeks(),
+            Frequency::Monthly => {
+                (after.year() - start.year()) as i64 * 12 + after.month() as i64
+                    - start.month() as i64
+            }
+            Frequency::Yearly => (after.year() - start.year()) as i64,
+        };
+
+        u32::try_from(elapsed / self.interval as i64).unwrap_or(u32::MAX)
+    }
+
+    /// First day of the nth period (the week starts on Monday)
+    fn period_start(&self, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+        match self.frequency {
+            Frequency::Daily => start.checked_add_signed(TimeDelta::days(n as i64)),
+            Frequency::Weekly => {
+                let monday = start - TimeDelta::days(start.weekday().num_days_from_monday() as i64);
+                monday.checked_add_signed(TimeDelta::weeks(n as i64))
+            }
+            Frequency::Monthly => start.with_day(1)?.checked_add_months(Months::new(n)),
+            Frequency::Yearly => NaiveDate::from_ymd_opt(start.year() + n as i32, 1, 1),
+        }
+    }
+
+    /// Sorted dates of a period matching the rule
+    fn dates_in_period(&self, period_start: NaiveDate, start: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates: Vec<NaiveDate> = match self.frequency {
+            Frequency::Daily => vec![period_start],
+            Frequency::Weekly if self.by_day.is_empty() => {
+                vec![period_start + TimeDelta::days(start.weekday().num_days_from_monday() as i64)]
+            }
+            Frequency::Weekly => period_start.iter_days().take(7).collect(),
+            Frequency::Monthly => self.month_dates(period_start, start),
+            Frequency::Yearly => {
+                let months = match (self.by_month.is_empty(), self.by_month_day.is_empty()) {
+                    (false, _) => self.by_month.clone(),
+                    // Days of every month (e.g. BYMONTHDAY=1 is the first of each month)
+                    (true, false) => (1..=12).collect(),
+                    (true, true) => vec![start.month()],
+                };
+
+                if !self.by_day.is_empty() && self.by_month.is_empty() {
+                    // Weekdays of the whole year (e.g. BYDAY=20MO)
+                    let days = year_days(period_start.year());
+                    days.iter()
+                        .copied()
+                        .filter(|d| self.matches_weekday(*d, &days))
+                        .filter(|d| {
+                            self.by_month_day.is_empty()
+                                || matches_month_day(*d, &self.by_month_day)
+                        })
+                        .collect()
+                } else {
+                    months
+                        .into_iter()
+                        .filter_map(|m| NaiveDate::from_ymd_opt(period_start.year(), m, 1))
+                        .flat_map(|first| self.month_dates(first, start))
+                        .collect()
+                }
+            }
+        };
+
+        // Limiting parts which weren't used to expand the period
+        dates.retain(|d| self.by_month.is_empty() || self.by_month.contains(&d.month()));
+        if self.frequency == Frequency::Daily {
+            dates.retain(|d| {
+                self.by_month_day.is_empty() || matches_month_day(*d, &self.by_month_day)
+            });
+            dates.retain(|d| {
+                self.by_day.is_empty() || self.by_day.iter().any(|(_, w)| *w == d.weekday())
+            });
+        }
+        if self.frequency == Frequency::Weekly && !self.by_day.is_empty() {
+            dates.retain(|d| self.by_day.iter().any(|(_, w)| *w == d.weekday()));
+        }
+
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    /// Matching dates in the month starting at `first`
+    fn month_dates(&self, first: NaiveDate, start: NaiveDate) -> Vec<NaiveDate> {
+        let days = month_days(first);
+
+        match (self.by_month_day.is_empty(), self.by_day.is_empty()) {
+            // Without further restrictions the occurrences keep their day of the month
+            (true, true) => first.with_day(start.day()).into_iter().collect(),
+            (false, true) => days
+                .into_iter()
+                .filter(|d| matches_month_day(*d, &self.by_month_day))
+                .collect(),
+            (month_days_empty, false) => days
+                .iter()
+                .copied()
+                .filter(|d| self.matches_weekday(*d, &days))
+                .filter(|d| month_days_empty || matches_month_day(*d, &self.by_month_day))
+                .collect(),
+        }
+    }
+
+    /// Check if a date matches BYDAY, the nth weekday is counted within `days`
+    fn matches_weekday(&self, date: NaiveDate, days: &[NaiveDate]) -> bool {
+        self.by_day.iter().any(|(n, weekday)| {
+            if date.weekday() != *weekday {
+                return false;
+            }
+
+            let Some(n) = n else {
+                return true;
+            };
+
+            let same: Vec<&NaiveDate> = days.iter().filter(|d| d.weekday() == *weekday).collect();
+            let idx = match *n {
+                n if n > 0 => n as usize - 1,
+                n => same.len().wrapping_sub(n.unsigned_abs() as usize),
+            };
+
+            same.get(idx).is_some_and(|d| **d == date)
+        })
+    }
+}
+
+/// Parse a BYDAY value like "MO", "2TU" or "-1FR"
+fn parse_weekday(day: &str) -> Option<(Option<i32>, Weekday)> {
+    let split = day.len().checked_sub(2)?;
+    let (n, weekday) = day.split_at(split);
+    let weekday = match weekday.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+
+    let n = match n {
+        "" => None,
+        n => Some(n.trim_start_matches('+').parse().ok()?),
+    };
+
+    Some((n, weekday))
+}
+
+/// Check if a date is one of the days of the month (negative counts from the end)
+fn matches_month_day(date: NaiveDate, month_days: &[i32]) -> bool {
+    let length = month_days_count(date);
+
+    month_days.iter().any(|d| match *d {
+        d if d > 0 => date.day() as i32 == d,
+        d => length as i32 + d + 1 == date.day() as i32,
+    })
+}
+
+/// All days of the month starting at `first`
+fn month_days(first: NaiveDate) -> Vec<NaiveDate> {
+    first
+        .iter_days()
+        .take_while(|d| d.month() == first.month())
+        .collect()
+}
+
+/// Number of days in the month of the date
+fn month_days_count(date: NaiveDate) -> u32 {
+    date.with_day(1)
+        .map_or(0, |first| month_days(first).len() as u32)
+}
+
+/// All days of a year
+fn year_days(year: i32) -> Vec<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .map(|first| first.iter_days().take_while(|d| d.year() == year).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Dates of the occurrences of a rule starting at 09:00 on the given day until 2030
+    fn dates(rrule: &str, start: NaiveDate) -> Vec<NaiveDate> {
+        dates_after(rrule, start, start)
+    }
+
+    /// Like [`dates`], only the occurrences from `after` on
+    fn dates_after(rrule: &str, start: NaiveDate, after: NaiveDate) -> Vec<NaiveDate> {
+        let time = |date: NaiveDate| date.and_hms_opt(9, 0, 0).unwrap();
+        RecurrenceRule::parse(rrule)
+            .unwrap()
+            .occurrences(time(start), time(after), time(date(2030, 1, 1)))
+            .into_iter()
+            .map(|dt| dt.date())
+            .collect()
+    }
+
+    #[test]
+    fn count_includes_the_start() {
+        assert_eq!(
+            dates("FREQ=DAILY;COUNT=3", date(2025, 1, 30)),
+            [date(2025, 1, 30), date(2025, 1, 31), date(2025, 2, 1)]
+        );
+        // Occurrences before `after` still count
+        assert_eq!(
+            dates_after("FREQ=DAILY;COUNT=3", date(2025, 1, 30), date(2025, 2, 1)),
+            [date(2025, 2, 1)]
+        );
+        assert_eq!(
+            dates_after("FREQ=DAILY;COUNT=3", date(2025, 1, 30), date(2025, 2, 2)),
+            []
+        );
+        // The start counts even if it doesn't match the rule
+        assert_eq!(
+            dates("FREQ=WEEKLY;BYDAY=MO;COUNT=2", date(2025, 3, 5)),
+            [date(2025, 3, 10), date(2025, 3, 17)]
+        );
+    }
+
+    #[test]
+    fn weekdays_with_ordinals() {
+        // Thanksgiving
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH;COUNT=3",
+                date(2024, 11, 28)
+            ),
+            [date(2024, 11, 28), date(2025, 11, 27), date(2026, 11, 26)]
+        );
+        // Last Friday of the month
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=-1FR;COUNT=3", date(2025, 1, 31)),
+            [date(2025, 1, 31), date(2025, 2, 28), date(2025, 3, 28)]
+        );
+        // 20th Monday of the year
+        assert_eq!(
+            dates("FREQ=YEARLY;BYDAY=20MO;COUNT=2", date(2025, 5, 19)),
+            [date(2025, 5, 19), date(2026, 5, 18)]
+        );
+    }
+
+    #[test]
+    fn intervals_skip_periods() {
+        assert_eq!(
+            dates(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH;COUNT=4",
+                date(2025, 3, 4)
+            ),
+            [
+                date(2025, 3, 4),
+                date(2025, 3, 6),
+                date(2025, 3, 18),
+                date(2025, 3, 20)
+            ]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;INTERVAL=5;COUNT=3", date(2025, 10, 15)),
+            [date(2025, 10, 15), date(2026, 3, 15), date(2026, 8, 15)]
+        );
+        // Starting at a later period keeps the periods of the interval
+        assert_eq!(
+            dates_after(
+                "FREQ=DAILY;INTERVAL=3",
+                date(2000, 1, 1),
+                date(2029, 12, 25)
+            ),
+            [date(2029, 12, 27), date(2029, 12, 30)]
+        );
+        assert_eq!(
+            dates_after(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO",
+                date(2000, 1, 3),
+                date(2029, 12, 1)
+            ),
+            [date(2029, 12, 10), date(2029, 12, 24)]
+        );
+    }
+
+    #[test]
+    fn month_days_at_the_end_of_the_month() {
+        // Months without a 31st are skipped
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=31;COUNT=4", date(2025, 1, 31)),
+            [
+                date(2025, 1, 31),
+                date(2025, 3, 31),
+                date(2025, 5, 31),
+                date(2025, 7, 31)
+            ]
+        );
+        // Without BYMONTHDAY the day of the start is kept, which doesn't exist in every month
+        assert_eq!(
+            dates("FREQ=MONTHLY;COUNT=2", date(2025, 1, 31)),
+            [date(2025, 1, 31), date(2025, 3, 31)]
+        );
+        // Last day of the month
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=-1;COUNT=3", date(2024, 1, 31)),
+            [date(2024, 1, 31), date(2024, 2, 29), date(2024, 3, 31)]
+        );
+    }
+
+    #[test]
+    fn yearly_month_days_without_month_repeat_every_month() {
+        assert_eq!(
+            dates("FREQ=YEARLY;BYMONTHDAY=1;COUNT=3", date(2025, 11, 1)),
+            [date(2025, 11, 1), date(2025, 12, 1), date(2026, 1, 1)]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=1;COUNT=2",
+                date(2025, 2, 1)
+            ),
+            [date(2025, 2, 1), date(2026, 2, 1)]
+        );
+        // Friday the 13th
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYDAY=FR;BYMONTHDAY=13;COUNT=3",
+                date(2025, 6, 13)
+            ),
+            [date(2025, 6, 13), date(2026, 2, 13), date(2026, 3, 13)]
+        );
+    }
+}
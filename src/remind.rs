@@ -0,0 +1,790 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+    process::Command,
+    thread,
+};
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use eyre::{Context, Result, eyre};
+use ical::{parser::ical::component::IcalAlarm, property::Property};
+use icalendar::{Component, DatePerhapsTime};
+
+use crate::{
+    agenda::{AgendaItem, agenda},
+    app::IsekCalendars,
+    config::{IsekConfig, NotificationSink},
+    helper::{
+        ICAL_UTC_DATE_TIME_FORMAT, INPUT_DATE_TIME_FORMAT, format_ical_duration, from_local,
+        get_property, ical_datetime_to_chrono, ical_deadline_to_chrono, param_value,
+        parse_date_input, parse_ical_datetime, parse_ical_duration, to_local,
+    },
+};
+
+/// Days after the checked period in which events are searched for alarms
+///
+/// Alarms which are triggered earlier before their event are not delivered.
+const EVENT_LOOKAHEAD_DAYS: u32 = 8;
+
+/// Time of an alarm added by the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmTrigger {
+    /// Some time before the task is due
+    BeforeDue(TimeDelta),
+    /// Fixed point in time
+    At(DateTime<Utc>),
+}
+
+impl AlarmTrigger {
+    /// Parse an alarm entered by the user
+    ///
+    /// Supports offsets before the due date like "15m", "2h", "1d" or "1w" ("0" for the due
+    /// time itself) and dates accepted by [`parse_date_input`].
+    pub fn parse(input: &str, today: NaiveDate) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+
+        if input == "0" {
+            return Some(AlarmTrigger::BeforeDue(TimeDelta::zero()));
+        }
+
+        // Plain numbers with a unit, offsets with a sign ("+3d") are dates
+        if let Some(unit) = input.chars().last()
+            && let number = &input[..input.len() - unit.len_utf8()]
+            && number.chars().all(|c| c.is_ascii_digit())
+            && let Ok(n) = number.parse::<i64>()
+        {
+            match unit {
+                'm' => return Some(AlarmTrigger::BeforeDue(TimeDelta::minutes(n))),
+                'h' => return Some(AlarmTrigger::BeforeDue(TimeDelta::hours(n))),
+                'd' => return Some(AlarmTrigger::BeforeDue(TimeDelta::days(n))),
+                'w' => return Some(AlarmTrigger::BeforeDue(TimeDelta::weeks(n))),
+                _ => {}
+            }
+        }
+
+        parse_date_input(&input, today).map(|dt| AlarmTrigger::At(ical_datetime_to_chrono(dt)))
+    }
+
+    /// Display alarm (VALARM) with this trigger and the given description
+    pub fn to_alarm(self, description: &str) -> IcalAlarm {
+        let property = |name: &str, params: Option<(&str, &str)>, value: String| Property {
+            name: name.to_owned(),
+            params: params.map(|(param, v)| vec![(param.to_owned(), vec![v.to_owned()])]),
+            value: Some(value),
+        };
+
+        let trigger = match self {
+            AlarmTrigger::BeforeDue(before) => property(
+                "TRIGGER",
+                Some(("RELATED", "END")),
+                format_ical_duration(-before),
+            ),
+            AlarmTrigger::At(at) => property(
+                "TRIGGER",
+                Some(("VALUE", "DATE-TIME")),
+                at.format(ICAL_UTC_DATE_TIME_FORMAT).to_string(),
+            ),
+        };
+
+        IcalAlarm {
+            properties: vec![
+                property("ACTION", None, "DISPLAY".to_owned()),
+                trigger,
+                property("DESCRIPTION", None, description.to_owned()),
+            ],
+        }
+    }
+}
+
+/// Trigger of an alarm (TRIGGER property)
+enum Trigger {
+    /// Offset from the start (DTSTART) or end (DUE or DTEND) of the component
+    Relative {
+        offset: TimeDelta,
+        end: bool,
+    },
+    Absolute(DateTime<Utc>),
+}
+
+impl Trigger {
+    fn of(alarm: &IcalAlarm) -> Option<Self> {
+        let property = alarm.properties.iter().find(|p| p.name == "TRIGGER")?;
+        let value = property.value.as_deref()?;
+
+        match param_value(property, "VALUE") {
+            Some(v) if v.eq_ignore_ascii_case("DATE-TIME") => parse_ical_datetime(value, None)
+                .map(|dt| Trigger::Absolute(ical_datetime_to_chrono(dt))),
+            _ => Some(Trigger::Relative {
+                offset: parse_ical_duration(value)?,
+                end: param_value(property, "RELATED")
+                    .is_some_and(|r| r.eq_ignore_ascii_case("END")),
+            }),
+        }
+    }
+}
+
+/// Human readable description of an alarm (e.g. "15m before due")
+///
+/// `end` names the end of the component the alarm belongs to ("due" for tasks).
+pub fn describe_alarm(alarm: &IcalAlarm, end: &str) -> String {
+    let mut description = match Trigger::of(alarm) {
+        None => "invalid trigger".to_owned(),
+        Some(Trigger::Absolute(at)) => to_local(&at).format(INPUT_DATE_TIME_FORMAT).to_string(),
+        Some(Trigger::Relative {
+            offset,
+            end: related_end,
+        }) => {
+            let anchor = if related_end { end } else { "start" };
+
+            match offset {
+                offset if offset < TimeDelta::zero() => {
+                    format!("{} before {anchor}", format_offset(-offset))
+                }
+                offset if offset > TimeDelta::zero() => {
+                    format!("{} after {anchor}", format_offset(offset))
+                }
+                _ => format!("at {anchor}"),
+            }
+        }
+    };
+
+    if let Some((repeat, interval)) = repetition(alarm) {
+        description.push_str(&format!(
+            ", {repeat} more every {}",
+            format_offset(interval)
+        ));
+    }
+
+    description
+}
+
+/// Short duration like "1d 2h" or "15m"
+fn format_offset(duration: TimeDelta) -> String {
+    let parts: Vec<String> = [
+        (duration.num_weeks(), "w"),
+        (duration.num_days() % 7, "d"),
+        (duration.num_hours() % 24, "h"),
+        (duration.num_minutes() % 60, "m"),
+    ]
+    .into_iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, unit)| format!("{n}{unit}"))
+    .collect();
+
+    match parts.is_empty() {
+        true => format!("{}s", duration.num_seconds()),
+        false => parts.join(" "),
+    }
+}
+
+/// Number of repetitions and the time between them (REPEAT and DURATION)
+fn repetition(alarm: &IcalAlarm) -> Option<(u32, TimeDelta)> {
+    let repeat = get_property(&alarm.properties, "REPEAT")?.parse().ok()?;
+    let interval = parse_ical_duration(get_property(&alarm.properties, "DURATION")?)?;
+
+    (repeat > 0 && interval > TimeDelta::zero()).then_some((repeat, interval))
+}
+
+/// All instants an alarm is triggered at
+///
+/// Relative triggers fall back to the other end if the component has no start (or end).
+fn alarm_times(
+    alarm: &IcalAlarm,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Vec<DateTime<Utc>> {
+    let first = match Trigger::of(alarm) {
+        Some(Trigger::Absolute(at)) => at,
+        Some(Trigger::Relative { offset, end: true }) => match end.or(start) {
+            Some(anchor) => anchor + offset,
+            None => return vec![],
+        },
+        Some(Trigger::Relative { offset, end: false }) => match start.or(end) {
+            Some(anchor) => anchor + offset,
+            None => return vec![],
+        },
+        None => return vec![],
+    };
+
+    let (repeat, interval) = repetition(alarm).unwrap_or((0, TimeDelta::zero()));
+    (0..=repeat as i32).map(|n| first + interval * n).collect()
+}
+
+/// Notification about an alarm or a due task
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    /// Identifies the reminder across restarts (calendar, UID, kind and time)
+    pub key: String,
+    /// Time the reminder is delivered at
+    pub time: DateTime<Utc>,
+    pub summary: String,
+    pub body: String,
+    /// Display name of the calendar
+    pub calendar: String,
+    pub uid: String,
+}
+
+/// All reminders of open tasks and events with a time after `from` up to `to` (inclusive)
+///
+/// Contains the alarms (VALARM) of tasks and event occurrences and, if `due` is set, the due
+/// times of open tasks.
+pub fn reminders(
+    calendars: &IsekCalendars,
+    due: bool,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<Reminder> {
+    let mut reminders = vec![];
+    let mut push = |reminder: Reminder| {
+        if from < reminder.time && reminder.time <= to {
+            reminders.push(reminder);
+        }
+    };
+
+    for todo in calendars.get_todos(None, None) {
+        let (Some(id), false) = (todo.id(), todo.is_closed()) else {
+            continue;
+        };
+
+        let t = todo.get();
+        let summary = t.get_summary().unwrap_or_default().to_owned();
+        let calendar = todo
+            .cal_display_name
+            .as_ref()
+            .unwrap_or(todo.cal_name)
+            .clone();
+        // Tasks due on a day (without time) are due at the end of it
+        let due_time = t.get_due().map(ical_deadline_to_chrono);
+        let body = t.get_due().map(format_due).unwrap_or_default();
+
+        let reminder = |kind: &str, time: DateTime<Utc>, body: String| Reminder {
+            key: format!("{}/{}/{kind}/{}", id.calendar, id.uid, time.timestamp()),
+            time,
+            summary: summary.clone(),
+            body,
+            calendar: calendar.clone(),
+            uid: id.uid.clone(),
+        };
+
+        for alarm in calendars.get_alarms(&id) {
+            let body = alarm_body(alarm, &summary).unwrap_or_else(|| body.clone());
+            let start = t.get_start().map(ical_datetime_to_chrono);
+
+            for time in alarm_times(alarm, start, due_time) {
+                push(reminder("ALARM", time, body.clone()));
+            }
+        }
+
+        if due && let Some(time) = due_time {
+            push(reminder("DUE", time, body.clone()));
+        }
+    }
+
+    // Event occurrences whose alarms may be triggered in the period
+    let first = to_local(&from).date() - TimeDelta::days(1);
+    let days = (to_local(&to).date() - first).num_days() as u32 + EVENT_LOOKAHEAD_DAYS;
+    let mut seen = HashSet::new();
+
+    for entry in agenda(calendars, first, days) {
+        let AgendaItem::Event(event) = &entry.item else {
+            continue;
+        };
+        let (start, end) = (from_local(entry.start), from_local(entry.end));
+        let uid = event.property("UID").unwrap_or_default().to_owned();
+        let occurrence = format!("{}/{uid}/ALARM/{}", event.cal_name, start.timestamp());
+
+        // Events spanning several days are listed once per day
+        if !seen.insert(occurrence.clone()) {
+            continue;
+        }
+
+        let summary = event.property("SUMMARY").unwrap_or_default().to_owned();
+        let calendar = event.cal_display_name.as_ref().unwrap_or(event.cal_name);
+
+        for alarm in &event.get().alarms {
+            for time in alarm_times(alarm, Some(start), Some(end)) {
+                push(Reminder {
+                    key: format!("{occurrence}/{}", time.timestamp()),
+                    time,
+                    summary: summary.clone(),
+                    body: alarm_body(alarm, &summary).unwrap_or_else(|| {
+                        format!("Starts {}", entry.start.format(INPUT_DATE_TIME_FORMAT))
+                    }),
+                    calendar: calendar.clone(),
+                    uid: uid.clone(),
+                });
+            }
+        }
+    }
+
+    reminders.sort_by_key(|r| r.time);
+    reminders
+}
+
+/// DESCRIPTION of an alarm unless it only repeats the summary
+fn alarm_body(alarm: &IcalAlarm, summary: &str) -> Option<String> {
+    get_property(&alarm.properties, "DESCRIPTION")
+        .map(str::trim)
+        .filter(|d| !d.is_empty() && *d != summary)
+        .map(str::to_owned)
+}
+
+/// Due date of a task as notification body
+fn format_due(due: DatePerhapsTime) -> String {
+    match due {
+        DatePerhapsTime::Date(date) => format!("Due {}", date.format("%Y-%m-%d")),
+        dt => format!(
+            "Due {}",
+            to_local(&ical_datetime_to_chrono(dt)).format(INPUT_DATE_TIME_FORMAT)
+        ),
+    }
+}
+
+impl Reminder {
+    /// Deliver the reminder
+    pub fn notify(&self, sink: &NotificationSink) -> Result<()> {
+        match sink {
+            NotificationSink::Stdout => {
+                println!(
+                    "{}  {} [{}] {}",
+                    to_local(&self.time).format(INPUT_DATE_TIME_FORMAT),
+                    self.summary,
+                    self.calendar,
+                    self.body
+                );
+                Ok(())
+            }
+            NotificationSink::Command(command) => {
+                let status = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .env("ISEK_SUMMARY", &self.summary)
+                    .env("ISEK_BODY", &self.body)
+                    .env("ISEK_CALENDAR", &self.calendar)
+                    .env("ISEK_UID", &self.uid)
+                    .env("ISEK_TIME", self.time.to_rfc3339())
+                    .status()
+                    .wrap_err("Could not run notification command")?;
+
+                match status.success() {
+                    true => Ok(()),
+                    false => Err(eyre!("Notification command failed ({})", status)),
+                }
+            }
+            NotificationSink::DBus => {
+                // org.freedesktop.Notifications.Notify(app_name, replaces_id, app_icon, summary,
+                // body, actions, hints, expire_timeout)
+                let output = Command::new("gdbus")
+                    .args([
+                        "call",
+                        "--session",
+                        "--dest",
+                        "org.freedesktop.Notifications",
+                        "--object-path",
+                        "/org/freedesktop/Notifications",
+                        "--method",
+                        "org.freedesktop.Notifications.Notify",
+                        "--",
+                        "'isek'",
+                        "uint32 0",
+                        "''",
+                        &gvariant_string(&self.summary),
+                        &gvariant_string(&format!("{}\n{}", self.body, self.calendar)),
+                        "@as []",
+                        "@a{sv} {}",
+                        "int32 -1",
+                    ])
+                    .output()
+                    .map_err(|e| match e.kind() {
+                        ErrorKind::NotFound => eyre!(
+                            "gdbus not found, it is needed for desktop notifications (part of \
+                             GLib, e.g. the package libglib2.0-bin or glib2)"
+                        ),
+                        _ => eyre!("Could not run gdbus to send the notification: {e}"),
+                    })?;
+
+                match output.status.success() {
+                    true => Ok(()),
+                    false => Err(eyre!(
+                        "Notification over D-Bus failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// String literal in the GVariant text format (used for gdbus arguments)
+fn gvariant_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Reminders which were already delivered, remembered across restarts
+///
+/// Stored next to the config file with one line per reminder (time and key).
+#[derive(Debug)]
+pub struct FiredAlarms {
+    path: PathBuf,
+    fired: HashMap<String, DateTime<Utc>>,
+}
+
+impl FiredAlarms {
+    /// Load the delivered reminders
+    pub fn load() -> Result<Self> {
+        let path =
+            confy::get_configuration_file_path("isek", "config")?.with_file_name("fired_alarms");
+
+        Ok(Self::load_from(path))
+    }
+
+    /// Load the delivered reminders from the given file (see [`FiredAlarms::load`])
+    pub fn load_from(path: PathBuf) -> Self {
+        let fired = match fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .filter_map(|line| {
+                    let (time, key) = line.split_once('\t')?;
+                    let time = DateTime::parse_from_rfc3339(time).ok()?;
+                    Some((key.to_owned(), time.to_utc()))
+                })
+                .collect(),
+            Err(_) => HashMap::new(),
+        };
+
+        Self { path, fired }
+    }
+
+    pub fn contains(&self, reminder: &Reminder) -> bool {
+        self.fired.contains_key(&reminder.key)
+    }
+
+    pub fn insert(&mut self, reminder: &Reminder) {
+        self.fired.insert(reminder.key.clone(), reminder.time);
+    }
+
+    /// Forget reminders up to the given time (they are never delivered again)
+    pub fn prune(&mut self, until: DateTime<Utc>) {
+        self.fired.retain(|_, time| *time > until);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let content: String = self
+            .fired
+            .iter()
+            .map(|(key, time)| format!("{}\t{key}\n", time.to_rfc3339()))
+            .collect();
+
+        fs::write(&self.path, content)
+            .wrap_err_with(|| format!("Could not write {}", self.path.display()))
+    }
+}
+
+/// Deliver the reminders up to `now` which were not delivered yet
+///
+/// Reminders older than `catch_up` are skipped, reminders which can't be delivered are tried
+/// again with the next check. Returns the delivered reminders.
+fn deliver(
+    calendars: &IsekCalendars,
+    fired: &mut FiredAlarms,
+    sink: &NotificationSink,
+    due: bool,
+    now: DateTime<Utc>,
+    catch_up: TimeDelta,
+) -> Result<Vec<Reminder>> {
+    let mut delivered = vec![];
+
+    for reminder in reminders(calendars, due, now - catch_up, now) {
+        if fired.contains(&reminder) {
+            continue;
+        }
+
+        match reminder.notify(sink) {
+            Ok(()) => {
+                fired.insert(&reminder);
+                delivered.push(reminder);
+            }
+            Err(e) => eprintln!("Could not deliver reminder '{}': {e}", reminder.summary),
+        }
+    }
+
+    fired.prune(now - catch_up);
+    fired.save()?;
+
+    Ok(delivered)
+}
+
+/// Deliver reminders until the process is stopped (or once if `once` is set)
+///
+/// Reminders which are due less than `catch_up` minutes ago are delivered late, e.g. when the
+/// computer was suspended. The calendars are reloaded before every check.
+pub fn run(once: bool, sink: Option<NotificationSink>) -> Result<()> {
+    let config = IsekConfig::load()?;
+    let settings = config.remind;
+    let sink = sink.unwrap_or(settings.sink);
+
+    let mut calendars = IsekCalendars::from_config(config.calendars, config.index)?;
+    let mut fired = FiredAlarms::load()?;
+
+    if let Some(problem) = calendars.take_sync_problem() {
+        eprintln!("{problem}");
+    }
+
+    let interval = TimeDelta::seconds(settings.interval.max(1) as i64);
+    let catch_up = TimeDelta::minutes(settings.catch_up as i64);
+
+    loop {
+        let now = Utc::now();
+        deliver(&calendars, &mut fired, &sink, settings.due, now, catch_up)?;
+
+        if once {
+            return Ok(());
+        }
+
+        // Wake up for the next reminder if it comes before the next check
+        let next = reminders(&calendars, settings.due, now, now + interval)
+            .first()
+            .map(|r| r.time)
+            .unwrap_or(now + interval);
+        thread::sleep((next - Utc::now()).to_std().unwrap_or_default());
+
+        if let Err(e) = calendars.reload() {
+            eprintln!("Could not reload calendars: {e}");
+        }
+        if let Some(problem) = calendars.take_sync_problem() {
+            eprintln!("{problem}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::NaiveDateTime;
+    use chrono_tz::Tz;
+
+    use super::*;
+    use crate::{
+        config::{CalendarConfig, CalendarType, IndexConfig},
+        helper::set_local_timezone,
+    };
+
+    fn berlin() {
+        set_local_timezone(Tz::Europe__Berlin);
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .and_utc()
+    }
+
+    /// Empty directory for a test
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("isek-remind-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Calendar directory with a task for every (UID, properties) pair
+    fn calendars(dir: &Path, tasks: &[(&str, &str)]) -> IsekCalendars {
+        let cal = dir.join("cal");
+        fs::create_dir_all(&cal).unwrap();
+        fs::write(cal.join("displayname"), "Tasks").unwrap();
+        fs::write(cal.join("color"), "#3A7BD5").unwrap();
+
+        for (uid, properties) in tasks {
+            let data = format!(
+                "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:test\r\nBEGIN:VTODO\r\nUID:{uid}\r\n\
+                 SUMMARY:Task {uid}\r\n{}\r\nEND:VTODO\r\nEND:VCALENDAR\r\n",
+                properties.trim().replace('\n', "\r\n")
+            );
+            fs::write(cal.join(format!("{uid}.ics")), data).unwrap();
+        }
+
+        let cfg = CalendarConfig {
+            kind: CalendarType::VDIR,
+            path: cal.to_str().unwrap().to_owned(),
+            display_name: None,
+            color: None,
+            credentials: None,
+            command: None,
+            refresh: None,
+        };
+
+        IsekCalendars::from_config(vec![cfg], IndexConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn parse_alarm_triggers() {
+        berlin();
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let parse = |input| AlarmTrigger::parse(input, today);
+
+        assert_eq!(parse("0"), Some(AlarmTrigger::BeforeDue(TimeDelta::zero())));
+        assert_eq!(
+            parse("15m"),
+            Some(AlarmTrigger::BeforeDue(TimeDelta::minutes(15)))
+        );
+        assert_eq!(
+            parse(" 2H "),
+            Some(AlarmTrigger::BeforeDue(TimeDelta::hours(2)))
+        );
+        assert_eq!(
+            parse("1d"),
+            Some(AlarmTrigger::BeforeDue(TimeDelta::days(1)))
+        );
+        assert_eq!(
+            parse("3w"),
+            Some(AlarmTrigger::BeforeDue(TimeDelta::weeks(3)))
+        );
+
+        // Dates and times in the local timezone
+        assert_eq!(
+            parse("2025-01-31 09:00"),
+            Some(AlarmTrigger::At(utc("2025-01-31 08:00:00")))
+        );
+        assert_eq!(
+            parse("2025-07-31 09:00"),
+            Some(AlarmTrigger::At(utc("2025-07-31 07:00:00")))
+        );
+        assert_eq!(
+            parse("+3d"),
+            Some(AlarmTrigger::At(utc("2025-03-03 23:00:00")))
+        );
+        assert_eq!(
+            parse("tomorrow"),
+            Some(AlarmTrigger::At(utc("2025-03-01 23:00:00")))
+        );
+
+        for invalid in ["", "m", "15x", "-15m", "1.5h", "soon"] {
+            assert_eq!(parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn alarms_relative_to_due_dates_use_the_end_of_the_day() {
+        berlin();
+        let dir = temp_dir("deadline");
+        let calendars = calendars(
+            &dir,
+            &[
+                (
+                    "date",
+                    "DUE;VALUE=DATE:20250310
+BEGIN:VALARM
+ACTION:DISPLAY
+TRIGGER;RELATED=END:-PT1H
+END:VALARM",
+                ),
+                (
+                    "time",
+                    "DTSTART:20250310T080000Z
+DUE:20250310T120000Z
+BEGIN:VALARM
+ACTION:DISPLAY
+TRIGGER:-PT30M
+END:VALARM
+BEGIN:VALARM
+ACTION:DISPLAY
+TRIGGER;RELATED=END:-PT30M
+END:VALARM",
+                ),
+            ],
+        );
+
+        let times: Vec<(String, DateTime<Utc>)> = reminders(
+            &calendars,
+            true,
+            utc("2025-03-10 00:00:00"),
+            utc("2025-03-11 00:00:00"),
+        )
+        .into_iter()
+        .map(|r| {
+            (
+                format!("{}/{}", r.uid, r.key.split('/').nth(2).unwrap()),
+                r.time,
+            )
+        })
+        .collect();
+
+        // End of the day in Berlin (UTC+1)
+        let end_of_day = utc("2025-03-10 23:00:00") - TimeDelta::nanoseconds(1);
+        assert_eq!(
+            times,
+            [
+                ("time/ALARM".to_owned(), utc("2025-03-10 07:30:00")),
+                ("time/ALARM".to_owned(), utc("2025-03-10 11:30:00")),
+                ("time/DUE".to_owned(), utc("2025-03-10 12:00:00")),
+                ("date/ALARM".to_owned(), end_of_day - TimeDelta::hours(1)),
+                ("date/DUE".to_owned(), end_of_day),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reminders_are_delivered_once_and_caught_up_for_a_while() {
+        berlin();
+        let dir = temp_dir("fired");
+        let calendars = calendars(
+            &dir,
+            &[("a", "DUE:20250310T120000Z"), ("b", "DUE:20250310T123000Z")],
+        );
+        let fired_path = dir.join("fired_alarms");
+        let sink = NotificationSink::Command("true".to_owned());
+        let catch_up = TimeDelta::minutes(10);
+        let uids = |reminders: Vec<Reminder>| -> Vec<String> {
+            reminders.into_iter().map(|r| r.uid).collect()
+        };
+
+        // Missed 5 minutes ago, delivered late
+        let mut fired = FiredAlarms::load_from(fired_path.clone());
+        let now = utc("2025-03-10 12:05:00");
+        let delivered = deliver(&calendars, &mut fired, &sink, true, now, catch_up).unwrap();
+        assert_eq!(uids(delivered), ["a"]);
+
+        // Not delivered again, not even after a restart
+        let delivered = deliver(&calendars, &mut fired, &sink, true, now, catch_up).unwrap();
+        assert!(delivered.is_empty());
+        let mut fired = FiredAlarms::load_from(fired_path.clone());
+        let delivered = deliver(&calendars, &mut fired, &sink, true, now, catch_up).unwrap();
+        assert!(delivered.is_empty());
+
+        // Failed deliveries are tried again with the next check
+        let failing = NotificationSink::Command("exit 1".to_owned());
+        let now = utc("2025-03-10 12:31:00");
+        let delivered = deliver(&calendars, &mut fired, &failing, true, now, catch_up).unwrap();
+        assert!(delivered.is_empty());
+        let delivered = deliver(&calendars, &mut fired, &sink, true, now, catch_up).unwrap();
+        assert_eq!(uids(delivered), ["b"]);
+
+        // Older reminders are forgotten since they aren't delivered anymore anyway
+        let saved = fs::read_to_string(&fired_path).unwrap();
+        assert_eq!(saved.lines().count(), 1);
+        assert!(saved.contains("/b/DUE/"), "{saved}");
+
+        // Skipped when missed for longer than the catch up time
+        let mut fired = FiredAlarms::load_from(dir.join("other"));
+        let now = utc("2025-03-10 12:45:00");
+        let delivered = deliver(&calendars, &mut fired, &sink, true, now, catch_up).unwrap();
+        assert!(delivered.is_empty());
+
+        // Due times are only reminded of if enabled
+        let mut fired = FiredAlarms::load_from(dir.join("no_due"));
+        let now = utc("2025-03-10 12:05:00");
+        let delivered = deliver(&calendars, &mut fired, &sink, false, now, catch_up).unwrap();
+        assert!(delivered.is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
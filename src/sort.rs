@@ -0,0 +1,330 @@
+use std::cmp::Ordering;
+
+use chrono::Utc;
+use icalendar::{Component, Todo, TodoStatus};
+
+use crate::{
+    app::IsekTodo,
+    config::{IndexConfig, SortKey, SortingConfig, SortingVariant},
+    helper::{ical_datetime_to_chrono, ical_deadline_to_chrono},
+    index::IndexScore,
+};
+
+/// Sort tasks by all keys of the sorting configuration
+///
+/// If `ignore_done` is set, closed (completed or cancelled) tasks are placed after all open
+/// tasks regardless of the keys and their direction, both groups are sorted by the keys.
+/// Otherwise closed tasks are sorted like any other task.
+///
+/// Tasks which are equal for every key are ordered by calendar, UID and summary so the order
+/// doesn't depend on the order the tasks were loaded in.
+pub fn sort_todos(todos: &mut Vec<IsekTodo>, sort: &SortingConfig, index: &IndexConfig) {
+    let keys = sort.keys();
+
+    // The index is expensive, so it is only calculated once per task
+    let now = Utc::now();
+    let with_index = keys.iter().any(|k| k.by == SortingVariant::Index);
+    let mut scored = std::mem::take(todos)
+        .into_iter()
+        .map(|t| match with_index {
+            true => (IndexScore::new(&t, index, &now).total(), t),
+            false => (0.0, t),
+        })
+        .collect::<Vec<(f64, IsekTodo)>>();
+
+    scored.sort_by(|(a_index, a), (b_index, b)| {
+        let closed = match sort.ignore_done {
+            true => a.is_closed().cmp(&b.is_closed()),
+            false => Ordering::Equal,
+        };
+
+        closed.then_with(|| {
+            keys.iter()
+                .map(|key| cmp_key(key, a, b, *a_index, *b_index))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| tie_breaker(a, b))
+        })
+    });
+
+    *todos = scored.into_iter().map(|(_, t)| t).collect();
+}
+
+/// Compare two tasks by a single key in its direction
+fn cmp_key(key: &SortKey, a: &IsekTodo, b: &IsekTodo, a_index: f64, b_index: f64) -> Ordering {
+    let (ta, tb) = (a.get(), b.get());
+
+    let ordering = match key.by {
+        SortingVariant::Date => cmp_missing_last(
+            ta.get_due().map(ical_deadline_to_chrono),
+            tb.get_due().map(ical_deadline_to_chrono),
+        ),
+        SortingVariant::Start => cmp_missing_last(
+            ta.get_start().map(ical_datetime_to_chrono),
+            tb.get_start().map(ical_datetime_to_chrono),
+        ),
+        SortingVariant::Priority => cmp_missing_last(priority(ta), priority(tb)),
+        // Most urgent tasks (highest index) first
+        SortingVariant::Index => b_index.total_cmp(&a_index),
+        SortingVariant::Summary => cmp_missing_last(summary(ta), summary(tb)),
+        SortingVariant::Created => cmp_missing_last(ta.get_created(), tb.get_created()),
+        SortingVariant::LastModified => {
+            cmp_missing_last(ta.get_last_modified(), tb.get_last_modified())
+        }
+        SortingVariant::Status => status(ta).cmp(&status(tb)),
+        SortingVariant::Calendar => a.cal_name.cmp(b.cal_name),
+    };
+
+    match key.ascending {
+        true => ordering,
+        false => ordering.reverse(),
+    }
+}
+
+/// Final comparison for tasks which are equal for all sort keys
+fn tie_breaker(a: &IsekTodo, b: &IsekTodo) -> Ordering {
+    let key = |t: &IsekTodo| {
+        (
+            t.cal_name.clone(),
+            t.get().get_uid().map(str::to_owned),
+            t.get().get_summary().map(str::to_owned),
+        )
+    };
+
+    key(a).cmp(&key(b))
+}
+
+/// Compare optional values, missing values come last
+fn cmp_missing_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Priority of a task (undefined priority is treated as missing)
+fn priority(t: &Todo) -> Option<u32> {
+    t.get_priority().filter(|p| *p > 0)
+}
+
+/// Summary for case insensitive sorting
+fn summary(t: &Todo) -> Option<String> {
+    t.get_summary().map(str::to_lowercase)
+}
+
+/// Position of the status in the typical life cycle of a task
+fn status(t: &Todo) -> u8 {
+    match t.get_status() {
+        Some(TodoStatus::NeedsAction) | None => 0,
+        Some(TodoStatus::InProcess) => 1,
+        Some(TodoStatus::Completed) => 2,
+        Some(TodoStatus::Cancelled) => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone};
+    use colors_transform::{Color, Rgb};
+    use icalendar::{Component, EventLike, Todo, TodoStatus};
+
+    use super::*;
+
+    const VARIANTS: [SortingVariant; 9] = [
+        SortingVariant::Date,
+        SortingVariant::Priority,
+        SortingVariant::Index,
+        SortingVariant::Start,
+        SortingVariant::Summary,
+        SortingVariant::Created,
+        SortingVariant::LastModified,
+        SortingVariant::Status,
+        SortingVariant::Calendar,
+    ];
+
+    /// Task with a date (day of October 2026), priority and summary
+    fn todo(uid: &str, day: u32, priority: u32, summary: &str) -> Todo {
+        let date = NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+
+        Todo::new()
+            .uid(uid)
+            .summary(summary)
+            .priority(priority)
+            .due(date)
+            .starts(date.pred_opt().unwrap())
+            .add_property("CREATED", format!("202601{day:02}T000000Z"))
+            .add_property("LAST-MODIFIED", format!("202602{day:02}T000000Z"))
+            .done()
+    }
+
+    /// Tasks of two calendars: open ones, closed ones with the earliest dates and highest
+    /// priorities, tasks without any values and pairs which are equal for every key
+    fn calendars() -> Vec<(String, Vec<Todo>)> {
+        let completed = todo("done", 1, 1, "Done")
+            .completed(Utc.with_ymd_and_hms(2026, 10, 2, 8, 0, 0).unwrap())
+            .status(TodoStatus::Completed)
+            .done();
+        let cancelled = todo("cancelled", 2, 1, "Cancelled")
+            .status(TodoStatus::Cancelled)
+            .done();
+
+        vec![
+            (
+                String::from("home"),
+                vec![
+                    todo("h1", 20, 5, "Water plants"),
+                    todo("same", 15, 3, "Same"),
+                    Todo::new().uid("empty").done(),
+                    completed,
+                ],
+            ),
+            (
+                String::from("work"),
+                vec![
+                    todo("w1", 10, 9, "Answer mails"),
+                    todo("w2", 12, 1, "Budget")
+                        .status(TodoStatus::InProcess)
+                        .done(),
+                    todo("same", 15, 3, "Same"),
+                    todo("same-2", 15, 3, "Same"),
+                    cancelled,
+                ],
+            ),
+        ]
+    }
+
+    /// Calendar and UID of every task in the sorted order
+    fn sorted(
+        calendars: &[(String, Vec<Todo>)],
+        sort: &SortingConfig,
+        rotate: usize,
+    ) -> Vec<String> {
+        let (display_name, color) = (None, Rgb::new());
+        let mut todos: Vec<IsekTodo> = calendars
+            .iter()
+            .flat_map(|(name, todos)| {
+                todos
+                    .iter()
+                    .map(|t| IsekTodo::new(name, &display_name, &color, t))
+            })
+            .collect();
+        let len = todos.len();
+        todos.rotate_left(rotate % len);
+
+        sort_todos(&mut todos, sort, &IndexConfig::default());
+
+        todos
+            .iter()
+            .map(|t| format!("{}/{}", t.cal_name, t.get().get_uid().unwrap()))
+            .collect()
+    }
+
+    fn is_closed(id: &str) -> bool {
+        id.ends_with("/done") || id.ends_with("/cancelled")
+    }
+
+    #[test]
+    fn every_variant_and_direction() {
+        let calendars = calendars();
+        let count = calendars.iter().map(|(_, t)| t.len()).sum::<usize>();
+
+        for by in VARIANTS {
+            for ascending in [true, false] {
+                for ignore_done in [true, false] {
+                    let sort = SortingConfig {
+                        by: by.clone(),
+                        ascending,
+                        ignore_done,
+                        then: vec![],
+                    };
+                    let case = format!("{by:?} ascending={ascending} ignore_done={ignore_done}");
+
+                    // The order doesn't depend on the order the tasks were loaded in
+                    let order = sorted(&calendars, &sort, 0);
+                    for rotate in 1..count {
+                        assert_eq!(order, sorted(&calendars, &sort, rotate), "{case}");
+                    }
+
+                    if ignore_done {
+                        let first_closed = order.iter().position(|id| is_closed(id)).unwrap();
+                        assert!(
+                            order[first_closed..].iter().all(|id| is_closed(id)),
+                            "{case}: {order:?}"
+                        );
+                    }
+
+                    // Tasks equal for every key are ordered by calendar, then UID
+                    let same = |id: &&String| id.ends_with("/same") || id.ends_with("/same-2");
+                    let equal: Vec<&String> = order.iter().filter(same).collect();
+                    match by {
+                        // Differ by calendar which is the sort key itself
+                        SortingVariant::Calendar if !ascending => {
+                            assert_eq!(equal, ["work/same", "work/same-2", "home/same"], "{case}")
+                        }
+                        _ => assert_eq!(equal, ["home/same", "work/same", "work/same-2"], "{case}"),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn closed_tasks_are_sorted_like_open_ones_without_ignore_done() {
+        let sort = |by, ascending, ignore_done| SortingConfig {
+            by,
+            ascending,
+            ignore_done,
+            then: vec![],
+        };
+        let calendars = calendars();
+
+        // Closed tasks have the earliest due dates
+        let order = sorted(&calendars, &sort(SortingVariant::Date, true, false), 0);
+        assert_eq!(order[..2], ["home/done", "work/cancelled"]);
+
+        let order = sorted(&calendars, &sort(SortingVariant::Date, true, true), 0);
+        assert_eq!(order[..2], ["work/w1", "work/w2"]);
+        assert_eq!(order[order.len() - 2..], ["home/done", "work/cancelled"]);
+
+        // Missing values come last in ascending order, open tasks stay in front of closed ones
+        let order = sorted(&calendars, &sort(SortingVariant::Priority, true, true), 0);
+        assert_eq!(order[..2], ["work/w2", "home/same"]);
+        assert_eq!(
+            order[order.len() - 3..],
+            ["home/empty", "home/done", "work/cancelled"]
+        );
+
+        let order = sorted(&calendars, &sort(SortingVariant::Status, false, false), 0);
+        assert_eq!(order[..3], ["work/cancelled", "home/done", "work/w2"]);
+    }
+
+    #[test]
+    fn ascending_index_puts_the_most_urgent_tasks_first() {
+        let sort = |ascending| SortingConfig {
+            by: SortingVariant::Index,
+            ascending,
+            ignore_done: false,
+            then: vec![],
+        };
+        // Only the priority counts, so the index doesn't depend on the current time
+        let calendars = vec![(
+            String::from("tasks"),
+            vec![
+                Todo::new().uid("none").done(),
+                Todo::new().uid("low").priority(9).done(),
+                Todo::new().uid("high").priority(1).done(),
+                Todo::new().uid("medium").priority(5).done(),
+            ],
+        )];
+
+        assert_eq!(
+            sorted(&calendars, &sort(true), 0),
+            ["tasks/high", "tasks/medium", "tasks/low", "tasks/none"]
+        );
+        assert_eq!(
+            sorted(&calendars, &sort(false), 0),
+            ["tasks/none", "tasks/low", "tasks/medium", "tasks/high"]
+        );
+    }
+}
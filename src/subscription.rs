@@ -0,0 +1,409 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, SystemTime},
+};
+
+use directories::ProjectDirs;
+use eyre::{Context, ContextCompat, Result, eyre};
+use ical::IcalParser;
+use ureq::Agent;
+
+use crate::{config::CalendarConfig, helper::cache_name};
+
+/// Time until a download is given up (e.g. when offline)
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Minutes until a subscription is fetched again if `refresh` isn't configured
+const DEFAULT_REFRESH: u64 = 60;
+
+/// Where the iCalendar data of a subscription comes from
+#[derive(Debug)]
+enum Source {
+    /// `http://`, `https://` or `webcal://` URL
+    Url(String),
+    /// `file://` URL or path of a local file
+    File(PathBuf),
+    /// Shell command printing the data
+    Command(String),
+}
+
+impl Source {
+    fn from_config(cfg: &CalendarConfig) -> Result<Self> {
+        if let Some(command) = &cfg.command {
+            return Ok(Source::Command(command.clone()));
+        }
+
+        let path = cfg.path.as_str();
+        match path.split_once("://") {
+            Some(("http" | "https", _)) => Ok(Source::Url(path.to_owned())),
+            // webcal is only a hint for calendar apps, the feed is served with HTTPS
+            Some(("webcal", rest)) => Ok(Source::Url(format!("https://{rest}"))),
+            Some(("file", file)) => Ok(Source::File(PathBuf::from(file))),
+            Some((scheme, _)) => Err(eyre!("Unsupported subscription URL scheme '{scheme}'")),
+            None if path.is_empty() => Err(eyre!("Subscription without a URL or a command")),
+            None => Ok(Source::File(PathBuf::from(path))),
+        }
+    }
+
+    /// Get the current data
+    fn fetch(&self) -> Result<String> {
+        match self {
+            Source::Url(url) => {
+                let config = Agent::config_builder()
+                    .timeout_global(Some(TIMEOUT))
+                    .build();
+
+                Agent::new_with_config(config)
+                    .get(url)
+                    .call()
+                    .and_then(|mut response| response.body_mut().read_to_string())
+                    .wrap_err_with(|| format!("Could not download {url}"))
+            }
+            Source::File(path) => fs::read_to_string(path)
+                .wrap_err_with(|| format!("Could not read {}", path.display())),
+            Source::Command(command) => {
+                let output = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .output()
+                    .wrap_err_with(|| format!("Could not run '{command}'"))?;
+
+                if !output.status.success() {
+                    return Err(eyre!(
+                        "'{command}' failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ));
+                }
+
+                String::from_utf8(output.stdout)
+                    .wrap_err_with(|| format!("Output of '{command}' is not valid UTF-8"))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Url(url) => write!(f, "{url}"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Command(command) => write!(f, "'{command}'"),
+        }
+    }
+}
+
+/// Read-only calendar fetched from a URL or the output of a command
+///
+/// The data is cached in a single .ics file, so the calendar is available offline. It's only
+/// fetched again once the cache is older than the refresh interval.
+#[derive(Debug)]
+pub struct Subscription {
+    source: Source,
+    /// Cached iCalendar data
+    cache: PathBuf,
+    /// Time between two fetches
+    refresh: Duration,
+    /// Time of the last failed fetch (retried after the refresh interval as well)
+    failed: Option<SystemTime>,
+}
+
+impl Subscription {
+    /// Open the cache of a subscription (without fetching it)
+    pub fn open(cfg: &CalendarConfig) -> Result<Self> {
+        let dirs = ProjectDirs::from("rs", "", "isek").context("No cache directory found")?;
+        Self::open_in(cfg, &dirs.cache_dir().join("subscriptions"))
+    }
+
+    /// Open a subscription cached in the given directory (see [`Subscription::open`])
+    pub fn open_in(cfg: &CalendarConfig, dir: &Path) -> Result<Self> {
+        let source = Source::from_config(cfg)?;
+
+        fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Could not create cache at {}", dir.display()))?;
+        let name = cache_name(cfg.command.as_ref().unwrap_or(&cfg.path));
+
+        Ok(Self {
+            source,
+            cache: dir.join(format!("{name}.ics")),
+            refresh: Duration::from_secs(cfg.refresh.unwrap_or(DEFAULT_REFRESH) * 60),
+            failed: None,
+        })
+    }
+
+    /// File with the cached data (doesn't exist before the first successful fetch)
+    pub fn cache(&self) -> &Path {
+        &self.cache
+    }
+
+    /// URL, path or command the data is fetched from
+    pub fn source(&self) -> String {
+        self.source.to_string()
+    }
+
+    /// Time until the data should be fetched again (zero if it's due)
+    pub fn next_refresh(&self) -> Duration {
+        let fetched = fs::metadata(&self.cache).and_then(|m| m.modified()).ok();
+        let last = fetched.max(self.failed);
+
+        match last.and_then(|time| time.elapsed().ok()) {
+            Some(age) => self.refresh.saturating_sub(age),
+            None if last.is_some() => self.refresh,
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Fetch the data and replace the cache with it
+    ///
+    /// The cache is kept if the data can't be fetched or isn't valid iCalendar data (e.g. an
+    /// error page).
+    pub fn fetch(&mut self) -> Result<()> {
+        let res = self.source.fetch().and_then(|data| {
+            let mut calendars = 0;
+            for calendar in IcalParser::new(data.as_bytes()) {
+                calendar.map_err(|e| eyre!("Invalid iCalendar data from {}: {e}", self.source))?;
+                calendars += 1;
+            }
+            if calendars == 0 {
+                return Err(eyre!("No iCalendar data from {}", self.source));
+            }
+
+            // Replaced at once, so the cache is never incomplete
+            let temp = self.cache.with_extension("part");
+            fs::write(&temp, data)
+                .and_then(|_| fs::rename(&temp, &self.cache))
+                .wrap_err_with(|| format!("Could not write cache at {}", self.cache.display()))
+        });
+
+        self.failed = res.is_err().then(SystemTime::now);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+    use crate::{
+        config::CalendarType,
+        test_server::{Response, TestServer},
+    };
+
+    fn data(summary: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTODO\r\nUID:1\r\nSUMMARY:{summary}\r\nEND:VTODO\r\nEND:VCALENDAR\r\n"
+        )
+    }
+
+    fn config(path: &str, command: Option<&str>) -> CalendarConfig {
+        CalendarConfig {
+            kind: CalendarType::Subscription,
+            path: path.to_owned(),
+            display_name: None,
+            color: None,
+            credentials: None,
+            command: command.map(str::to_owned),
+            refresh: None,
+        }
+    }
+
+    /// Empty cache directory for a test
+    fn cache_dir(test: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("isek-subscription-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn sources_from_config() {
+        let source = |path: &str| Source::from_config(&config(path, None)).map(|s| s.to_string());
+
+        assert_eq!(
+            source("webcal://example.com/feed.ics").unwrap(),
+            "https://example.com/feed.ics"
+        );
+        assert_eq!(
+            source("http://example.com/feed.ics").unwrap(),
+            "http://example.com/feed.ics"
+        );
+        assert_eq!(source("file:///tmp/feed.ics").unwrap(), "/tmp/feed.ics");
+        assert_eq!(source("feed.ics").unwrap(), "feed.ics");
+        assert!(source("ftp://example.com/feed.ics").is_err());
+        assert!(source("").is_err());
+
+        // The command wins over the path
+        let cfg = config("https://example.com", Some("cat feed.ics"));
+        assert_eq!(
+            Source::from_config(&cfg).unwrap().to_string(),
+            "'cat feed.ics'"
+        );
+    }
+
+    #[test]
+    fn fetch_from_url_keeps_cache_on_errors() {
+        let response = Arc::new(Mutex::new((200, data("First"))));
+        let server = {
+            let response = response.clone();
+            TestServer::start(move |request| {
+                assert_eq!(request.method, "GET");
+                let (status, body) = response.lock().unwrap().clone();
+                Response::new(status, body)
+            })
+        };
+        let dir = cache_dir("url");
+
+        let url = format!("{}/feed.ics", server.url);
+        let mut subscription = Subscription::open_in(&config(&url, None), &dir).unwrap();
+        assert!(!subscription.cache().exists());
+
+        subscription.fetch().unwrap();
+        assert_eq!(
+            fs::read_to_string(subscription.cache()).unwrap(),
+            data("First")
+        );
+
+        for failure in [
+            (500, "Internal Server Error".to_owned()),
+            (200, "<html>Maintenance</html>".to_owned()),
+            (200, String::new()),
+        ] {
+            *response.lock().unwrap() = failure;
+            assert!(subscription.fetch().is_err());
+            assert_eq!(
+                fs::read_to_string(subscription.cache()).unwrap(),
+                data("First")
+            );
+        }
+
+        *response.lock().unwrap() = (200, data("Second"));
+        subscription.fetch().unwrap();
+        assert_eq!(
+            fs::read_to_string(subscription.cache()).unwrap(),
+            data("Second")
+        );
+        assert_eq!(server.requests().len(), 5);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_from_url_fails_while_offline() {
+        let server = TestServer::start(|_| Response::new(200, data("Task")));
+        server.set_offline(true);
+        let dir = cache_dir("offline");
+
+        let url = format!("{}/feed.ics", server.url);
+        let mut subscription = Subscription::open_in(&config(&url, None), &dir).unwrap();
+        let error = subscription.fetch().unwrap_err().to_string();
+        assert!(error.contains("Could not download"), "{error}");
+        assert!(!subscription.cache().exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_from_command() {
+        let dir = cache_dir("command");
+        let feed = dir.join("feed.ics");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&feed, data("Command")).unwrap();
+
+        let command = format!("cat '{}'", feed.display());
+        let mut subscription = Subscription::open_in(&config("", Some(&command)), &dir).unwrap();
+        subscription.fetch().unwrap();
+        assert_eq!(
+            fs::read_to_string(subscription.cache()).unwrap(),
+            data("Command")
+        );
+
+        let cfg = config("", Some("echo 'no feed' >&2; exit 1"));
+        let error = Subscription::open_in(&cfg, &dir)
+            .unwrap()
+            .fetch()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("no feed"), "{error}");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_from_file_url() {
+        let dir = cache_dir("file");
+        let feed = dir.join("feed.ics");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&feed, data("File")).unwrap();
+
+        let url = format!("file://{}", feed.display());
+        let mut subscription = Subscription::open_in(&config(&url, None), &dir).unwrap();
+        subscription.fetch().unwrap();
+        assert_eq!(
+            fs::read_to_string(subscription.cache()).unwrap(),
+            data("File")
+        );
+
+        fs::remove_file(&feed).unwrap();
+        assert!(subscription.fetch().is_err());
+        assert_eq!(
+            fs::read_to_string(subscription.cache()).unwrap(),
+            data("File")
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn refresh_interval() {
+        let dir = cache_dir("refresh");
+        let feed = dir.join("feed.ics");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut cfg = config(feed.to_str().unwrap(), None);
+        cfg.refresh = Some(30);
+        let refresh = Duration::from_secs(30 * 60);
+        let mut subscription = Subscription::open_in(&cfg, &dir).unwrap();
+
+        // Due without a cache
+        assert_eq!(subscription.next_refresh(), Duration::ZERO);
+
+        // Failed fetches are retried after the interval as well
+        assert!(subscription.fetch().is_err());
+        let next = subscription.next_refresh();
+        assert!(next <= refresh && next > refresh - Duration::from_secs(60));
+
+        fs::write(&feed, data("Task")).unwrap();
+        let mut subscription = Subscription::open_in(&cfg, &dir).unwrap();
+        subscription.fetch().unwrap();
+        let next = subscription.next_refresh();
+        assert!(next <= refresh && next > refresh - Duration::from_secs(60));
+
+        // The age of the cache counts after a restart
+        let fetched = SystemTime::now() - Duration::from_secs(20 * 60);
+        File::options()
+            .write(true)
+            .open(subscription.cache())
+            .unwrap()
+            .set_modified(fetched)
+            .unwrap();
+        let subscription = Subscription::open_in(&cfg, &dir).unwrap();
+        let next = subscription.next_refresh();
+        assert!(next <= Duration::from_secs(10 * 60) && next > Duration::from_secs(9 * 60));
+
+        let fetched = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(subscription.cache())
+            .unwrap()
+            .set_modified(fetched)
+            .unwrap();
+        assert_eq!(subscription.next_refresh(), Duration::ZERO);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
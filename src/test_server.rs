@@ -0,0 +1,149 @@
+//! Minimal HTTP server on localhost for the tests of CalDAV calendars and subscriptions
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+/// Request received by the server
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// Headers by lowercase name
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(String::as_str)
+    }
+}
+
+/// Response sent by the server
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.into(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+/// Server answering every request with a handler until the test ends
+pub struct TestServer {
+    /// Base URL (`http://127.0.0.1:<port>`)
+    pub url: String,
+    offline: Arc<AtomicBool>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServer {
+    pub fn start(handler: impl Fn(&Request) -> Response + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let offline = Arc::new(AtomicBool::new(false));
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let (thread_offline, thread_requests) = (offline.clone(), requests.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // Connections are closed without an answer while offline
+                if thread_offline.load(Ordering::SeqCst) {
+                    continue;
+                }
+
+                if let Some(request) = read_request(&stream) {
+                    let log = format!("{} {}", request.method, request.path);
+                    thread_requests.lock().unwrap().push(log);
+                    write_response(stream, handler(&request));
+                }
+            }
+        });
+
+        Self {
+            url,
+            offline,
+            requests,
+        }
+    }
+
+    /// Simulate a server which can't be reached
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+    }
+
+    /// Method and path of all requests so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = (parts.next()?.to_owned(), parts.next()?.to_owned());
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        match line.trim_end().split_once(':') {
+            Some((name, value)) => {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_owned());
+            }
+            None => break,
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path,
+        headers,
+        body: String::from_utf8(body).ok()?,
+    })
+}
+
+fn write_response(mut stream: TcpStream, response: Response) {
+    let headers: String = response
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\r\n"))
+        .collect();
+
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} Test\r\nContent-Length: {}\r\nConnection: close\r\n{headers}\r\n{}",
+        response.status,
+        response.body.len(),
+        response.body
+    );
+}
@@ -0,0 +1,245 @@
+use std::{env, str::FromStr};
+
+use colors_transform::{Color as _, Rgb};
+use eyre::{Result, eyre};
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::config::{StyleConfig, StyleModifier, ThemeConfig, ThemeVariant};
+
+/// Resolved styles for all themeable UI elements
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub list: Style,
+    pub highlight: Style,
+    pub border: Style,
+    pub title: Style,
+    pub status_bar: Style,
+    pub status_bar_key: Style,
+    pub date: Style,
+    pub date_overdue: Style,
+    pub priority_high: Style,
+    pub priority_medium: Style,
+    pub priority_low: Style,
+    pub pending: Style,
+    pub in_progress: Style,
+    pub done: Style,
+    pub marked: Style,
+
+    /// Use the calendar colors for calendar chips
+    pub calendar_colors: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// Theme for terminals with dark background
+    pub fn dark() -> Self {
+        Self {
+            list: Style::new(),
+            highlight: Style::new().add_modifier(Modifier::BOLD),
+            border: Style::new(),
+            title: Style::new().add_modifier(Modifier::BOLD),
+            status_bar: Style::new(),
+            status_bar_key: Style::new().fg(Color::Cyan),
+            date: Style::new().fg(Color::Blue),
+            date_overdue: Style::new().fg(Color::Red),
+            priority_high: Style::new().fg(Color::Red),
+            priority_medium: Style::new().fg(Color::Yellow),
+            priority_low: Style::new().fg(Color::Green),
+            pending: Style::new(),
+            in_progress: Style::new().fg(Color::Yellow),
+            done: Style::new().fg(Color::DarkGray),
+            marked: Style::new().bg(Color::DarkGray),
+            calendar_colors: true,
+        }
+    }
+
+    /// Theme for terminals with light background
+    pub fn light() -> Self {
+        Self {
+            list: Style::new(),
+            highlight: Style::new().add_modifier(Modifier::BOLD),
+            border: Style::new().fg(Color::DarkGray),
+            title: Style::new().add_modifier(Modifier::BOLD),
+            status_bar: Style::new(),
+            status_bar_key: Style::new().fg(Color::Blue),
+            date: Style::new().fg(Color::Blue),
+            date_overdue: Style::new().fg(Color::Red),
+            priority_high: Style::new().fg(Color::Red),
+            priority_medium: Style::new().fg(Color::Magenta),
+            priority_low: Style::new().fg(Color::Green),
+            pending: Style::new(),
+            in_progress: Style::new().fg(Color::Magenta),
+            done: Style::new().fg(Color::Gray),
+            marked: Style::new().bg(Color::Gray),
+            calendar_colors: true,
+        }
+    }
+
+    /// Theme with strong contrasts
+    pub fn high_contrast() -> Self {
+        Self {
+            list: Style::new().fg(Color::White),
+            highlight: Style::new()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            border: Style::new().fg(Color::White),
+            title: Style::new().fg(Color::White).add_modifier(Modifier::BOLD),
+            status_bar: Style::new().fg(Color::White),
+            status_bar_key: Style::new()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            date: Style::new().fg(Color::LightCyan),
+            date_overdue: Style::new()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+            priority_high: Style::new()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+            priority_medium: Style::new().fg(Color::LightYellow),
+            priority_low: Style::new().fg(Color::LightGreen),
+            pending: Style::new(),
+            in_progress: Style::new().fg(Color::LightYellow),
+            done: Style::new()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::CROSSED_OUT),
+            marked: Style::new().fg(Color::Black).bg(Color::LightMagenta),
+            calendar_colors: true,
+        }
+    }
+
+    /// Theme without any colors
+    pub fn no_color() -> Self {
+        Self {
+            list: Style::new(),
+            highlight: Style::new().add_modifier(Modifier::REVERSED),
+            border: Style::new(),
+            title: Style::new().add_modifier(Modifier::BOLD),
+            status_bar: Style::new(),
+            status_bar_key: Style::new().add_modifier(Modifier::BOLD),
+            date: Style::new(),
+            date_overdue: Style::new().add_modifier(Modifier::BOLD),
+            priority_high: Style::new().add_modifier(Modifier::BOLD),
+            priority_medium: Style::new(),
+            priority_low: Style::new().add_modifier(Modifier::DIM),
+            pending: Style::new(),
+            in_progress: Style::new().add_modifier(Modifier::ITALIC),
+            done: Style::new().add_modifier(Modifier::DIM),
+            marked: Style::new().add_modifier(Modifier::UNDERLINED),
+            calendar_colors: false,
+        }
+    }
+
+    /// Build theme from config
+    ///
+    /// Respects the `NO_COLOR` environment variable (see https://no-color.org)
+    pub fn from_config(cfg: &ThemeConfig) -> Result<Self> {
+        let no_color = env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+
+        let mut theme = match (no_color, &cfg.base) {
+            (true, _) | (_, ThemeVariant::NoColor) => Self::no_color(),
+            (_, ThemeVariant::Dark) => Self::dark(),
+            (_, ThemeVariant::Light) => Self::light(),
+            (_, ThemeVariant::HighContrast) => Self::high_contrast(),
+        };
+
+        let overrides = [
+            (&mut theme.list, &cfg.list),
+            (&mut theme.highlight, &cfg.highlight),
+            (&mut theme.border, &cfg.border),
+            (&mut theme.title, &cfg.title),
+            (&mut theme.status_bar, &cfg.status_bar),
+            (&mut theme.status_bar_key, &cfg.status_bar_key),
+            (&mut theme.date, &cfg.date),
+            (&mut theme.date_overdue, &cfg.date_overdue),
+            (&mut theme.priority_high, &cfg.priority_high),
+            (&mut theme.priority_medium, &cfg.priority_medium),
+            (&mut theme.priority_low, &cfg.priority_low),
+            (&mut theme.pending, &cfg.pending),
+            (&mut theme.in_progress, &cfg.in_progress),
+            (&mut theme.done, &cfg.done),
+            (&mut theme.marked, &cfg.marked),
+        ];
+
+        for (style, style_cfg) in overrides {
+            if let Some(style_cfg) = style_cfg {
+                *style = style.patch(parse_style(style_cfg, no_color)?);
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Style for a calendar chip with the calendar color as background
+    ///
+    /// The foreground is chosen based on the luminance of the background to stay readable
+    pub fn calendar_chip(&self, color: &Rgb) -> Style {
+        if !self.calendar_colors {
+            return Style::new().add_modifier(Modifier::REVERSED);
+        }
+
+        let fg = if relative_luminance(color) > 0.179 {
+            Color::Black
+        } else {
+            Color::White
+        };
+
+        Style::new().fg(fg).bg(Color::Rgb(
+            color.get_red() as u8,
+            color.get_green() as u8,
+            color.get_blue() as u8,
+        ))
+    }
+}
+
+/// Convert style configuration into a ratatui style
+fn parse_style(cfg: &StyleConfig, no_color: bool) -> Result<Style> {
+    let mut style = Style::new();
+
+    if !no_color {
+        if let Some(fg) = &cfg.fg {
+            style = style.fg(parse_color(fg)?);
+        }
+        if let Some(bg) = &cfg.bg {
+            style = style.bg(parse_color(bg)?);
+        }
+    }
+
+    for modifier in &cfg.modifiers {
+        style = style.add_modifier(match modifier {
+            StyleModifier::Bold => Modifier::BOLD,
+            StyleModifier::Dim => Modifier::DIM,
+            StyleModifier::Italic => Modifier::ITALIC,
+            StyleModifier::Underlined => Modifier::UNDERLINED,
+            StyleModifier::Reversed => Modifier::REVERSED,
+            StyleModifier::CrossedOut => Modifier::CROSSED_OUT,
+        });
+    }
+
+    Ok(style)
+}
+
+fn parse_color(color: &str) -> Result<Color> {
+    Color::from_str(color).map_err(|_| eyre!("Invalid color '{}' in theme configuration", color))
+}
+
+/// Relative luminance as defined by WCAG 2.0
+fn relative_luminance(color: &Rgb) -> f32 {
+    let linear = |c: f32| {
+        let c = c / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+
+    0.2126 * linear(color.get_red())
+        + 0.7152 * linear(color.get_green())
+        + 0.0722 * linear(color.get_blue())
+}
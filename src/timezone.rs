@@ -0,0 +1,722 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::RwLock,
+};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeDelta, Utc, Weekday};
+use chrono_tz::Tz;
+use ical::{
+    generator::Emitter,
+    parser::ical::component::{IcalCalendar, IcalTimeZone},
+    property::Property,
+};
+
+use crate::helper::{ICAL_NAIVE_DATE_TIME_FORMAT, get_property, resolve_local};
+
+/// Windows timezone names (used e.g. by Outlook and Exchange) and their IANA equivalent
+///
+/// Based on the default ("001") territory of the CLDR windowsZones mapping.
+const WINDOWS_ZONES: &[(&str, &str)] = &[
+    ("Dateline Standard Time", "Etc/GMT+12"),
+    ("UTC-11", "Etc/GMT+11"),
+    ("Aleutian Standard Time", "America/Adak"),
+    ("Hawaiian Standard Time", "Pacific/Honolulu"),
+    ("Marquesas Standard Time", "Pacific/Marquesas"),
+    ("Alaskan Standard Time", "America/Anchorage"),
+    ("UTC-09", "Etc/GMT+9"),
+    ("Pacific Standard Time (Mexico)", "America/Tijuana"),
+    ("UTC-08", "Etc/GMT+8"),
+    ("Pacific Standard Time", "America/Los_Angeles"),
+    ("US Mountain Standard Time", "America/Phoenix"),
+    ("Mountain Standard Time (Mexico)", "America/Mazatlan"),
+    ("Mountain Standard Time", "America/Denver"),
+    ("Yukon Standard Time", "America/Whitehorse"),
+    ("Central America Standard Time", "America/Guatemala"),
+    ("Central Standard Time", "America/Chicago"),
+    ("Easter Island Standard Time", "Pacific/Easter"),
+    ("Central Standard Time (Mexico)", "America/Mexico_City"),
+    ("Canada Central Standard Time", "America/Regina"),
+    ("SA Pacific Standard Time", "America/Bogota"),
+    ("Eastern Standard Time (Mexico)", "America/Cancun"),
+    ("Eastern Standard Time", "America/New_York"),
+    ("Haiti Standard Time", "America/Port-au-Prince"),
+    ("Cuba Standard Time", "America/Havana"),
+    ("US Eastern Standard Time", "America/Indianapolis"),
+    ("Turks And Caicos Standard Time", "America/Grand_Turk"),
+    ("Paraguay Standard Time", "America/Asuncion"),
+    ("Atlantic Standard Time", "America/Halifax"),
+    ("Venezuela Standard Time", "America/Caracas"),
+    ("Central Brazilian Standard Time", "America/Cuiaba"),
+    ("SA Western Standard Time", "America/La_Paz"),
+    ("Pacific SA Standard Time", "America/Santiago"),
+    ("Newfoundland Standard Time", "America/St_Johns"),
+    ("Tocantins Standard Time", "America/Araguaina"),
+    ("E. South America Standard Time", "America/Sao_Paulo"),
+    ("SA Eastern Standard Time", "America/Cayenne"),
+    ("Argentina Standard Time", "America/Buenos_Aires"),
+    ("Greenland Standard Time", "America/Godthab"),
+    ("Montevideo Standard Time", "America/Montevideo"),
+    ("Magallanes Standard Time", "America/Punta_Arenas"),
+    ("Saint Pierre Standard Time", "America/Miquelon"),
+    ("Bahia Standard Time", "America/Bahia"),
+    ("UTC-02", "Etc/GMT+2"),
+    ("Azores Standard Time", "Atlantic/Azores"),
+    ("Cape Verde Standard Time", "Atlantic/Cape_Verde"),
+    ("UTC", "Etc/UTC"),
+    ("GMT Standard Time", "Europe/London"),
+    ("Greenwich Standard Time", "Atlantic/Reykjavik"),
+    ("Sao Tome Standard Time", "Africa/Sao_Tome"),
+    ("Morocco Standard Time", "Africa/Casablanca"),
+    ("W. Europe Standard Time", "Europe/Berlin"),
+    ("Central Europe Standard Time", "Europe/Budapest"),
+    ("Romance Standard Time", "Europe/Paris"),
+    ("Central European Standard Time", "Europe/Warsaw"),
+    ("W. Central Africa Standard Time", "Africa/Lagos"),
+    ("Jordan Standard Time", "Asia/Amman"),
+    ("GTB Standard Time", "Europe/Bucharest"),
+    ("Middle East Standard Time", "Asia/Beirut"),
+    ("Egypt Standard Time", "Africa/Cairo"),
+    ("E. Europe Standard Time", "Europe/Chisinau"),
+    ("Syria Standard Time", "Asia/Damascus"),
+    ("West Bank Standard Time", "Asia/Hebron"),
+    ("South Africa Standard Time", "Africa/Johannesburg"),
+    ("FLE Standard Time", "Europe/Kiev"),
+    ("Israel Standard Time", "Asia/Jerusalem"),
+    ("South Sudan Standard Time", "Africa/Juba"),
+    ("Kaliningrad Standard Time", "Europe/Kaliningrad"),
+    ("Sudan Standard Time", "Africa/Khartoum"),
+    ("Libya Standard Time", "Africa/Tripoli"),
+    ("Namibia Standard Time", "Africa/Windhoek"),
+    ("Arabic Standard Time", "Asia/Baghdad"),
+    ("Turkey Standard Time", "Europe/Istanbul"),
+    ("Arab Standard Time", "Asia/Riyadh"),
+    ("Belarus Standard Time", "Europe/Minsk"),
+    ("Russian Standard Time", "Europe/Moscow"),
+    ("E. Africa Standard Time", "Africa/Nairobi"),
+    ("Volgograd Standard Time", "Europe/Volgograd"),
+    ("Iran Standard Time", "Asia/Tehran"),
+    ("Arabian Standard Time", "Asia/Dubai"),
+    ("Astrakhan Standard Time", "Europe/Astrakhan"),
+    ("Azerbaijan Standard Time", "Asia/Baku"),
+    ("Russia Time Zone 3", "Europe/Samara"),
+    ("Mauritius Standard Time", "Indian/Mauritius"),
+    ("Saratov Standard Time", "Europe/Saratov"),
+    ("Georgian Standard Time", "Asia/Tbilisi"),
+    ("Caucasus Standard Time", "Asia/Yerevan"),
+    ("Afghanistan Standard Time", "Asia/Kabul"),
+    ("West Asia Standard Time", "Asia/Tashkent"),
+    ("Ekaterinburg Standard Time", "Asia/Yekaterinburg"),
+    ("Pakistan Standard Time", "Asia/Karachi"),
+    ("Qyzylorda Standard Time", "Asia/Qyzylorda"),
+    ("India Standard Time", "Asia/Calcutta"),
+    ("Sri Lanka Standard Time", "Asia/Colombo"),
+    ("Nepal Standard Time", "Asia/Katmandu"),
+    ("Central Asia Standard Time", "Asia/Bishkek"),
+    ("Bangladesh Standard Time", "Asia/Dhaka"),
+    ("Omsk Standard Time", "Asia/Omsk"),
+    ("Myanmar Standard Time", "Asia/Rangoon"),
+    ("SE Asia Standard Time", "Asia/Bangkok"),
+    ("Altai Standard Time", "Asia/Barnaul"),
+    ("W. Mongolia Standard Time", "Asia/Hovd"),
+    ("North Asia Standard Time", "Asia/Krasnoyarsk"),
+    ("N. Central Asia Standard Time", "Asia/Novosibirsk"),
+    ("Tomsk Standard Time", "Asia/Tomsk"),
+    ("China Standard Time", "Asia/Shanghai"),
+    ("North Asia East Standard Time", "Asia/Irkutsk"),
+    ("Singapore Standard Time", "Asia/Singapore"),
+    ("W. Australia Standard Time", "Australia/Perth"),
+    ("Taipei Standard Time", "Asia/Taipei"),
+    ("Ulaanbaatar Standard Time", "Asia/Ulaanbaatar"),
+    ("Aus Central W. Standard Time", "Australia/Eucla"),
+    ("Transbaikal Standard Time", "Asia/Chita"),
+    ("Tokyo Standard Time", "Asia/Tokyo"),
+    ("North Korea Standard Time", "Asia/Pyongyang"),
+    ("Korea Standard Time", "Asia/Seoul"),
+    ("Yakutsk Standard Time", "Asia/Yakutsk"),
+    ("Cen. Australia Standard Time", "Australia/Adelaide"),
+    ("AUS Central Standard Time", "Australia/Darwin"),
+    ("E. Australia Standard Time", "Australia/Brisbane"),
+    ("AUS Eastern Standard Time", "Australia/Sydney"),
+    ("West Pacific Standard Time", "Pacific/Port_Moresby"),
+    ("Tasmania Standard Time", "Australia/Hobart"),
+    ("Vladivostok Standard Time", "Asia/Vladivostok"),
+    ("Lord Howe Standard Time", "Australia/Lord_Howe"),
+    ("Bougainville Standard Time", "Pacific/Bougainville"),
+    ("Russia Time Zone 10", "Asia/Srednekolymsk"),
+    ("Magadan Standard Time", "Asia/Magadan"),
+    ("Norfolk Standard Time", "Pacific/Norfolk"),
+    ("Sakhalin Standard Time", "Asia/Sakhalin"),
+    ("Central Pacific Standard Time", "Pacific/Guadalcanal"),
+    ("Russia Time Zone 11", "Asia/Kamchatka"),
+    ("New Zealand Standard Time", "Pacific/Auckland"),
+    ("UTC+12", "Etc/GMT-12"),
+    ("Fiji Standard Time", "Pacific/Fiji"),
+    ("Chatham Islands Standard Time", "Pacific/Chatham"),
+    ("UTC+13", "Etc/GMT-13"),
+    ("Tonga Standard Time", "Pacific/Tongatapu"),
+    ("Samoa Standard Time", "Pacific/Apia"),
+    ("Line Islands Standard Time", "Pacific/Kiritimati"),
+];
+
+/// Timezones defined by VTIMEZONE components of the loaded calendars (by scoped TZID)
+///
+/// The scoped TZID contains a hash of the definition, so different definitions of the same TZID
+/// never replace each other (see [`scope_timezones`]).
+static CUSTOM_ZONES: RwLock<BTreeMap<String, CustomZone>> = RwLock::new(BTreeMap::new());
+
+/// Separates the TZID from the hash of its definition in scoped TZIDs
+const SCOPE_SEPARATOR: &str = "/isek-";
+
+/// Timezone defined by the STANDARD and DAYLIGHT rules of a VTIMEZONE component
+#[derive(Debug, Clone)]
+pub struct CustomZone {
+    observances: Vec<Observance>,
+}
+
+/// Single STANDARD or DAYLIGHT rule of a VTIMEZONE
+#[derive(Debug, Clone)]
+struct Observance {
+    /// First onset in local time (before the change)
+    start: NaiveDateTime,
+    /// Offset before the onset in seconds
+    offset_from: i32,
+    /// Offset after the onset in seconds
+    offset_to: i32,
+    /// Yearly repetition of the onset
+    rule: Option<YearlyRule>,
+    /// Additional onsets in local time
+    dates: Vec<NaiveDateTime>,
+}
+
+/// Yearly recurrence as used in VTIMEZONE (e.g. `FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU`)
+#[derive(Debug, Clone)]
+struct YearlyRule {
+    month: u32,
+    /// Nth weekday in the month (negative counts from the end)
+    weekday: Option<(i32, Weekday)>,
+    /// Days of the month (e.g. `BYMONTHDAY=8,9,10,11,12,13,14` for "second Sunday")
+    month_days: Vec<u32>,
+    until: Option<NaiveDateTime>,
+}
+
+/// Convert a time in the timezone with the given TZID to an instant
+///
+/// The TZID is resolved as IANA name, as Windows timezone name and as scoped TZID of a timezone
+/// defined by a VTIMEZONE component of the loaded calendars (in this order).
+pub fn resolve_tzid(dt: NaiveDateTime, tzid: &str) -> Option<DateTime<Utc>> {
+    let tzid = tzid.trim().trim_matches('"');
+
+    if let Some(tz) = iana_zone(tzid) {
+        return resolve_local(dt, &tz).map(|dt| dt.to_utc());
+    }
+
+    let zones = CUSTOM_ZONES.read().ok()?;
+    zones.get(tzid).map(|zone| zone.to_utc(dt))
+}
+
+/// Timezone of an IANA or Windows timezone name
+fn iana_zone(tzid: &str) -> Option<Tz> {
+    tzid.parse::<Tz>().ok().or_else(|| {
+        WINDOWS_ZONES
+            .iter()
+            .find(|(windows, _)| windows.eq_ignore_ascii_case(tzid))
+            .and_then(|(_, iana)| iana.parse().ok())
+    })
+}
+
+/// Give the timezones defined by VTIMEZONE components of a calendar file TZIDs of their own
+///
+/// Files may define different timezones with the same TZID (e.g. Outlook uses `Customized Time
+/// Zone` for any timezone), so the TZID of a timezone which isn't an IANA or Windows name and
+/// all references to it are replaced by a TZID with a hash of the definition. Times are always
+/// resolved with the definition of their own file this way. [`unscope_timezones`] restores the
+/// TZIDs of the file.
+pub fn scope_timezones(ical: &mut IcalCalendar) {
+    let mut scoped = HashMap::new();
+    for timezone in &mut ical.timezones {
+        let Some((tzid, zone)) = CustomZone::parse(timezone) else {
+            continue;
+        };
+        if iana_zone(&tzid).is_some() || tzid.contains(SCOPE_SEPARATOR) {
+            continue;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        timezone.generate().hash(&mut hasher);
+        let key = format!("{tzid}{SCOPE_SEPARATOR}{:016x}", hasher.finish());
+
+        if let Ok(mut zones) = CUSTOM_ZONES.write() {
+            zones.insert(key.clone(), zone);
+        }
+        rename_tzids(&mut timezone.properties, &|_| Some(key.clone()));
+        scoped.insert(tzid, key);
+    }
+
+    if !scoped.is_empty() {
+        rename_all_tzids(ical, &|tzid| scoped.get(tzid).cloned());
+    }
+}
+
+/// Restore the TZIDs of the file in a calendar with scoped TZIDs (see [`scope_timezones`])
+pub fn unscope_timezones(ical: &mut IcalCalendar) {
+    let unscoped = |tzid: &str| {
+        tzid.rsplit_once(SCOPE_SEPARATOR)
+            .map(|(tzid, _)| tzid.to_owned())
+    };
+
+    for timezone in &mut ical.timezones {
+        rename_tzids(&mut timezone.properties, &unscoped);
+    }
+    rename_all_tzids(ical, &unscoped);
+}
+
+/// Rename the TZID parameters of all components of a calendar
+fn rename_all_tzids(ical: &mut IcalCalendar, rename: &dyn Fn(&str) -> Option<String>) {
+    for todo in &mut ical.todos {
+        rename_tzids(&mut todo.properties, rename);
+    }
+    for event in &mut ical.events {
+        rename_tzids(&mut event.properties, rename);
+    }
+    for journal in &mut ical.journals {
+        rename_tzids(&mut journal.properties, rename);
+    }
+}
+
+/// Rename TZID properties and parameters (values without a new name are kept)
+fn rename_tzids(properties: &mut [Property], rename: &dyn Fn(&str) -> Option<String>) {
+    for property in properties {
+        if property.name == "TZID"
+            && let Some(tzid) = property.value.as_deref().and_then(rename)
+        {
+            property.value = Some(tzid);
+        }
+
+        for (name, values) in property.params.iter_mut().flatten() {
+            if name.eq_ignore_ascii_case("TZID") {
+                for value in values {
+                    if let Some(tzid) = rename(value.trim_matches('"')) {
+                        *value = tzid;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl CustomZone {
+    /// TZID and rules of a VTIMEZONE component (`None` if it has no TZID or no valid rule)
+    fn parse(timezone: &IcalTimeZone) -> Option<(String, Self)> {
+        let tzid = get_property(&timezone.properties, "TZID")?;
+
+        let observances: Vec<Observance> = timezone
+            .transitions
+            .iter()
+            .filter_map(|t| Observance::parse(&t.properties))
+            .collect();
+
+        (!observances.is_empty()).then(|| (tzid.to_owned(), Self { observances }))
+    }
+
+    /// Offset in seconds at the given instant
+    fn offset_at(&self, instant: NaiveDateTime) -> i32 {
+        // Latest onset before the instant
+        let latest = self
+            .observances
+            .iter()
+            .filter_map(|o| o.last_onset(instant).map(|onset| (onset, o.offset_to)))
+            .max_by_key(|(onset, _)| *onset);
+
+        match latest {
+            Some((_, offset)) => offset,
+            // Before the first onset the offset before the earliest rule applies
+            None => self
+                .observances
+                .iter()
+                .min_by_key(|o| o.start)
+                .map(|o| o.offset_from)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Convert a local time in this timezone to an instant
+    ///
+    /// Handles DST transitions like [`resolve_local`] (earlier instant for ambiguous times,
+    /// times in a gap are moved forward).
+    fn to_utc(&self, dt: NaiveDateTime) -> DateTime<Utc> {
+        let candidates = self
+            .observances
+            .iter()
+            .flat_map(|o| [o.offset_from, o.offset_to])
+            .map(|offset| dt - TimeDelta::seconds(offset as i64))
+            .filter(|utc| dt - TimeDelta::seconds(self.offset_at(*utc) as i64) == *utc);
+
+        match candidates.min() {
+            Some(utc) => utc.and_utc(),
+            None => {
+                // Offset before the gap applied to the time gives the instant after it
+                let before = self.offset_at(dt - TimeDelta::days(1));
+                (dt - TimeDelta::seconds(before as i64)).and_utc()
+            }
+        }
+    }
+}
+
+impl Observance {
+    fn parse(properties: &[ical::property::Property]) -> Option<Self> {
+        let start = NaiveDateTime::parse_from_str(
+            get_property(properties, "DTSTART")?,
+            ICAL_NAIVE_DATE_TIME_FORMAT,
+        )
+        .ok()?;
+
+        let rule = get_property(properties, "RRULE").and_then(YearlyRule::parse);
+        let dates = properties
+            .iter()
+            .filter(|p| p.name == "RDATE")
+            .filter_map(|p| p.value.as_deref())
+            .flat_map(|v| v.split(','))
+            .filter_map(|v| NaiveDateTime::parse_from_str(v, ICAL_NAIVE_DATE_TIME_FORMAT).ok())
+            .collect();
+
+        Some(Self {
+            start,
+            offset_from: parse_offset(get_property(properties, "TZOFFSETFROM")?)?,
+            offset_to: parse_offset(get_property(properties, "TZOFFSETTO")?)?,
+            rule,
+            dates,
+        })
+    }
+
+    /// Latest onset (as UTC time) at or before the given UTC time
+    fn last_onset(&self, instant: NaiveDateTime) -> Option<NaiveDateTime> {
+        let to_utc = |local: NaiveDateTime| local - TimeDelta::seconds(self.offset_from as i64);
+
+        let mut onsets = vec![self.start];
+        onsets.extend(self.dates.iter().copied());
+
+        if let Some(rule) = &self.rule {
+            // Onsets of this and the previous year are enough to find the latest one
+            for year in [instant.year() - 1, instant.year()] {
+                if year < self.start.year() {
+                    continue;
+                }
+
+                onsets.extend(
+                    rule.dates(year, self.start.date())
+                        .into_iter()
+                        .map(|date| date.and_time(self.start.time()))
+                        .filter(|onset| *onset >= self.start)
+                        .filter(|onset| rule.until.is_none_or(|until| to_utc(*onset) <= until)),
+                );
+            }
+        }
+
+        onsets
+            .into_iter()
+            .map(to_utc)
+            .filter(|onset| *onset <= instant)
+            .max()
+    }
+}
+
+impl YearlyRule {
+    fn parse(rrule: &str) -> Option<Self> {
+        let parts: HashMap<&str, &str> = rrule
+            .split(';')
+            .filter_map(|part| part.split_once('='))
+            .collect();
+
+        if parts.get("FREQ") != Some(&"YEARLY") {
+            return None;
+        }
+
+        let weekday = parts.get("BYDAY").and_then(|day| {
+            let split = day.len().checked_sub(2)?;
+            let (n, weekday) = day.split_at(split);
+            let weekday = match weekday {
+                "MO" => Weekday::Mon,
+                "TU" => Weekday::Tue,
+                "WE" => Weekday::Wed,
+                "TH" => Weekday::Thu,
+                "FR" => Weekday::Fri,
+                "SA" => Weekday::Sat,
+                "SU" => Weekday::Sun,
+                _ => return None,
+            };
+
+            Some((n.trim_start_matches('+').parse().unwrap_or(1), weekday))
+        });
+
+        let until = parts.get("UNTIL").and_then(|until| {
+            NaiveDateTime::parse_from_str(until.trim_end_matches('Z'), ICAL_NAIVE_DATE_TIME_FORMAT)
+                .ok()
+        });
+
+        Some(Self {
+            month: parts.get("BYMONTH")?.parse().ok()?,
+            weekday,
+            month_days: parts
+                .get("BYMONTHDAY")
+                .map(|days| days.split(',').filter_map(|d| d.parse().ok()).collect())
+                .unwrap_or_default(),
+            until,
+        })
+    }
+
+    /// Dates of the onset in the given year (`start` is the date of the first onset)
+    fn dates(&self, year: i32, start: NaiveDate) -> Vec<NaiveDate> {
+        let Some(first) = NaiveDate::from_ymd_opt(year, self.month, 1) else {
+            return vec![];
+        };
+
+        let days = first
+            .iter_days()
+            .take_while(|d| d.month() == self.month)
+            .filter(|d| self.month_days.is_empty() || self.month_days.contains(&d.day()));
+
+        match self.weekday {
+            Some((n, weekday)) => {
+                let matching: Vec<NaiveDate> = days.filter(|d| d.weekday() == weekday).collect();
+                let idx = match n {
+                    n if n > 0 => n as usize - 1,
+                    n => matching.len().wrapping_sub(n.unsigned_abs() as usize),
+                };
+
+                matching.get(idx).copied().into_iter().collect()
+            }
+            // Without further restrictions the onset keeps its day of the month
+            None if self.month_days.is_empty() => {
+                NaiveDate::from_ymd_opt(year, self.month, start.day())
+                    .into_iter()
+                    .collect()
+            }
+            None => days.collect(),
+        }
+    }
+}
+
+/// Parse an UTC offset like "+0100" or "-053000" into seconds
+fn parse_offset(offset: &str) -> Option<i32> {
+    let (sign, digits) = match offset.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+
+    let hours: i32 = digits.get(0..2)?.parse().ok()?;
+    let minutes: i32 = digits.get(2..4)?.parse().ok()?;
+    let seconds: i32 = digits.get(4..6).map_or(Some(0), |s| s.parse().ok())?;
+
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+    use ical::IcalParser;
+
+    use super::*;
+
+    /// Parse the VTIMEZONE component with the given lines between BEGIN and END
+    fn zone(lines: &str) -> (String, CustomZone) {
+        let data = format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTIMEZONE\r\n{}\r\nEND:VTIMEZONE\r\nEND:VCALENDAR\r\n",
+            lines.trim().replace('\n', "\r\n")
+        );
+        let calendar = IcalParser::new(data.as_bytes()).next().unwrap().unwrap();
+
+        CustomZone::parse(&calendar.timezones[0]).unwrap()
+    }
+
+    fn naive(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    /// Compare the early morning hours (when DST changes) and noon of every day of a year with
+    /// the IANA definition of the timezone
+    fn assert_matches_iana(zone: &CustomZone, tz: Tz, year: i32) {
+        let days = naive(year, 1, 1, 0, 0).date().iter_days();
+        for day in days.take_while(|d| d.year() == year) {
+            for (hour, minute) in [(0, 30), (1, 30), (2, 0), (2, 30), (3, 30), (12, 0)] {
+                let dt = day.and_hms_opt(hour, minute, 0).unwrap();
+                let expected = resolve_local(dt, &tz).unwrap().to_utc();
+
+                assert_eq!(zone.to_utc(dt), expected, "{dt} in {tz}");
+            }
+        }
+    }
+
+    #[test]
+    fn yearly_rules_with_last_weekday() {
+        let (tzid, berlin) = zone(
+            "TZID:Custom Berlin
+BEGIN:DAYLIGHT
+DTSTART:19810329T020000
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU
+TZOFFSETFROM:+0100
+TZOFFSETTO:+0200
+END:DAYLIGHT
+BEGIN:STANDARD
+DTSTART:19961027T030000
+RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU
+TZOFFSETFROM:+0200
+TZOFFSETTO:+0100
+END:STANDARD",
+        );
+
+        assert_eq!(tzid, "Custom Berlin");
+        for year in [2020, 2024, 2025] {
+            assert_matches_iana(&berlin, Tz::Europe__Berlin, year);
+        }
+
+        // Gap when the clock is set forward, ambiguous hour when it is set back
+        let utc = |dt: NaiveDateTime| dt.and_utc();
+        assert_eq!(
+            berlin.to_utc(naive(2025, 3, 30, 2, 30)),
+            utc(naive(2025, 3, 30, 1, 30))
+        );
+        assert_eq!(
+            berlin.to_utc(naive(2025, 10, 26, 2, 30)),
+            utc(naive(2025, 10, 26, 0, 30))
+        );
+    }
+
+    #[test]
+    fn yearly_rules_with_month_days_and_until() {
+        let (_, new_york) = zone(
+            "TZID:Custom New York
+BEGIN:DAYLIGHT
+DTSTART:19870405T020000
+RRULE:FREQ=YEARLY;BYMONTH=4;BYDAY=1SU;UNTIL=20060402T070000Z
+TZOFFSETFROM:-0500
+TZOFFSETTO:-0400
+END:DAYLIGHT
+BEGIN:STANDARD
+DTSTART:19671029T020000
+RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU;UNTIL=20061029T060000Z
+TZOFFSETFROM:-0400
+TZOFFSETTO:-0500
+END:STANDARD
+BEGIN:DAYLIGHT
+DTSTART:20070311T020000
+RRULE:FREQ=YEARLY;BYMONTH=3;BYMONTHDAY=8,9,10,11,12,13,14;BYDAY=SU
+TZOFFSETFROM:-0500
+TZOFFSETTO:-0400
+END:DAYLIGHT
+BEGIN:STANDARD
+DTSTART:20071104T020000
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU
+TZOFFSETFROM:-0400
+TZOFFSETTO:-0500
+END:STANDARD",
+        );
+
+        // Rules until 2006 and since 2007
+        for year in [2005, 2006, 2007, 2024] {
+            assert_matches_iana(&new_york, Tz::America__New_York, year);
+        }
+    }
+
+    #[test]
+    fn single_onsets_and_rdates() {
+        let (_, zone) = zone(
+            "TZID:Custom Changes
+BEGIN:DAYLIGHT
+DTSTART:20240101T000000
+RDATE:20250101T000000
+TZOFFSETFROM:+0000
+TZOFFSETTO:+0300
+END:DAYLIGHT
+BEGIN:STANDARD
+DTSTART:20240601T000000
+TZOFFSETFROM:+0300
+TZOFFSETTO:+0000
+END:STANDARD",
+        );
+
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        for (date, utc_hour) in [
+            // Before the first onset
+            ((2023, 6, 1), 12),
+            ((2024, 3, 1), 9),
+            ((2024, 8, 1), 12),
+            // Onset from RDATE
+            ((2025, 3, 1), 9),
+        ] {
+            let date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+            assert_eq!(
+                zone.to_utc(date.and_time(noon)),
+                date.and_hms_opt(utc_hour, 0, 0).unwrap().and_utc(),
+                "{date}"
+            );
+        }
+    }
+
+    /// Calendar file with a task at noon on 2024-01-15 in the timezones with the given TZIDs
+    /// and offsets (defined by VTIMEZONE components)
+    fn file(zones: &[(&str, &str)]) -> IcalCalendar {
+        let timezones: String = zones
+            .iter()
+            .map(|(tzid, offset)| {
+                format!(
+                    "BEGIN:VTIMEZONE\r\nTZID:{tzid}\r\nBEGIN:STANDARD\r\nDTSTART:19700101T000000\r\n\
+                     TZOFFSETFROM:{offset}\r\nTZOFFSETTO:{offset}\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n"
+                )
+            })
+            .collect();
+        let data = format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{timezones}BEGIN:VTODO\r\nUID:task\r\n\
+             DTSTART;TZID={}:20240115T120000\r\nEND:VTODO\r\nEND:VCALENDAR\r\n",
+            zones[0].0
+        );
+
+        IcalParser::new(data.as_bytes()).next().unwrap().unwrap()
+    }
+
+    /// TZID of the start of the task in a file
+    fn start_tzid(file: &IcalCalendar) -> String {
+        let start = &file.todos[0].properties[1];
+        start.params.as_ref().unwrap()[0].1[0].clone()
+    }
+
+    #[test]
+    fn tzids_resolve_by_iana_windows_and_vtimezone_names() {
+        let dt = naive(2024, 1, 15, 12, 0);
+        let utc = |h| naive(2024, 1, 15, h, 0).and_utc();
+
+        // Definitions in files don't override IANA or Windows names
+        let mut iana = file(&[
+            ("Europe/Berlin", "+0500"),
+            ("W. Europe Standard Time", "+0500"),
+        ]);
+        scope_timezones(&mut iana);
+        assert_eq!(start_tzid(&iana), "Europe/Berlin");
+        assert_eq!(resolve_tzid(dt, "Europe/Berlin"), Some(utc(11)));
+        assert_eq!(resolve_tzid(dt, "w. europe standard time"), Some(utc(11)));
+        assert_eq!(resolve_tzid(dt, "Eastern Standard Time"), Some(utc(17)));
+        assert_eq!(resolve_tzid(dt, "Unknown Zone"), None);
+
+        // Each file resolves a TZID with its own definition
+        let mut first = file(&[("Customized Time Zone", "+0500")]);
+        let mut second = file(&[("Customized Time Zone", "+0600")]);
+        let original = first.clone();
+        scope_timezones(&mut first);
+        scope_timezones(&mut second);
+        assert_eq!(resolve_tzid(dt, &start_tzid(&first)), Some(utc(7)));
+        assert_eq!(resolve_tzid(dt, &start_tzid(&second)), Some(utc(6)));
+        assert_eq!(resolve_tzid(dt, "Customized Time Zone"), None);
+
+        // Scoping again (e.g. when a task is moved to another file) keeps the TZID
+        let scoped = start_tzid(&first);
+        scope_timezones(&mut first);
+        assert_eq!(start_tzid(&first), scoped);
+
+        unscope_timezones(&mut first);
+        assert_eq!(first.generate(), original.generate());
+    }
+}
@@ -1,28 +1,292 @@
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use std::{any::Any, fmt, rc::Rc};
+
+use chrono::{TimeDelta, Weekday};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
 use eyre::Result;
 use ratatui::{
     Frame,
-    layout::Rect,
-    style::Stylize,
+    layout::{Position, Rect},
+    style::Styled,
     symbols::border,
     text::Line,
     widgets::{Block, Padding},
 };
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
     App,
-    app::State,
-    config::{FilterConfig, SortingConfig, SortingVariant},
-    widgets::{StatusBar, ToDoList},
+    app::{Prompt, State},
+    config::{FilterConfig, LayoutVariant, SortingConfig, SortingVariant},
+    helper::{local_today, next_weekday},
+    widgets::{StatusBar, ToDoList, ToDoTable},
 };
 
+mod agenda;
+pub use agenda::AgendaView;
+
+mod calendar;
+pub use calendar::CalendarView;
+
+mod detail;
+pub use detail::DetailView;
+
+mod help;
+pub use help::HelpView;
+
+mod stats;
+pub use stats::StatsView;
+
 /// Trait defining the interface for views in the application
-pub trait View {
-    /// Handle user input events
-    fn handle_events(&self, app: &mut App) -> Result<()>;
+///
+/// Views are shown as tabs by [`Views`], which reads the events and passes them on to the
+/// active view.
+pub trait View: Any {
+    /// Name of the view shown in the tab bar
+    fn title(&self) -> &'static str;
+
+    /// Handle key presses (switching tabs is handled by [`Views`])
+    fn handle_key_event(&self, app: &mut App, key_event: KeyEvent) -> Result<()>;
+
+    /// Handle mouse events outside the tab bar
+    fn handle_mouse_event(&self, app: &mut App, mouse_event: MouseEvent) -> Result<()>;
+
+    /// Render the view into an area of the terminal frame
+    fn draw(&self, app: &mut App, frame: &mut Frame, area: Rect);
+}
+
+/// All views of the application, shown as tabs
+pub struct Views {
+    views: Vec<Rc<dyn View>>,
+    active: usize,
+    /// Area the tab bar was rendered in last (used to map mouse positions to tabs)
+    tab_area: Rect,
+}
+
+impl Default for Views {
+    fn default() -> Self {
+        Self::new(vec![
+            Rc::new(MainView),
+            Rc::new(DetailView),
+            Rc::new(AgendaView),
+            Rc::new(CalendarView),
+            Rc::new(StatsView),
+            Rc::new(HelpView),
+        ])
+    }
+}
+
+impl fmt::Debug for Views {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Views")
+            .field(
+                "views",
+                &self.views.iter().map(|v| v.title()).collect::<Vec<_>>(),
+            )
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+impl Views {
+    /// Create the tabs in the given order, the first one is active
+    pub fn new(views: Vec<Rc<dyn View>>) -> Self {
+        Self {
+            views,
+            active: 0,
+            tab_area: Rect::default(),
+        }
+    }
+
+    /// Currently shown view
+    pub fn active(&self) -> Rc<dyn View> {
+        self.views[self.active].clone()
+    }
+
+    /// Show the view of the given type (if it is one of the tabs)
+    pub fn show<V: View>(&mut self) {
+        if let Some(idx) = self.views.iter().position(|v| is_view::<V>(v.as_ref())) {
+            self.active = idx;
+        }
+    }
+
+    /// Check if the view of the given type is shown
+    pub fn is_active<V: View>(&self) -> bool {
+        is_view::<V>(self.views[self.active].as_ref())
+    }
+
+    /// Show the next (or previous) tab
+    pub fn cycle(&mut self, forward: bool) {
+        let count = self.views.len();
+        self.active = match forward {
+            true => (self.active + 1) % count,
+            false => (self.active + count - 1) % count,
+        };
+    }
+
+    /// Read the next event and handle it with the active view
+    ///
+    /// `Tab` and `Shift+Tab` switch between the tabs (in normal mode), so do clicks on the
+    /// tab bar. Subscriptions which are due are fetched again while waiting.
+    pub fn handle_events(app: &mut App) -> Result<()> {
+        let view = app.views.active();
+        let active = app.views.active;
+        let switchable = matches!(app.state, State::Normal);
+
+        // Subscriptions are fetched again when they are due while waiting for input
+        if let Some(timeout) = app.calendars.next_refresh()
+            && !event::poll(timeout)?
+        {
+            app.refresh_subscriptions();
+        } else {
+            match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    app.message = None;
 
-    /// Render the view to the terminal frame
-    fn draw(&self, app: &mut App, frame: &mut Frame);
+                    match key_event.code {
+                        KeyCode::Tab if switchable => app.views.cycle(true),
+                        KeyCode::BackTab if switchable => app.views.cycle(false),
+                        _ => view.handle_key_event(app, key_event)?,
+                    }
+                }
+                Event::Mouse(mouse_event) => {
+                    let tab = app.views.tab_at(mouse_event.column, mouse_event.row);
+
+                    match (tab, mouse_event.kind) {
+                        (Some(idx), MouseEventKind::Down(MouseButton::Left)) if switchable => {
+                            app.views.active = idx
+                        }
+                        (Some(_), _) => {}
+                        (None, _) => view.handle_mouse_event(app, mouse_event)?,
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // The task detail and the help start at the top whenever they are shown again
+        if app.views.active != active {
+            app.detail_scroll = 0;
+            app.help_scroll = 0;
+        }
+
+        // Problems while syncing with CalDAV servers or fetching subscriptions (e.g. offline)
+        if let Some(problem) = app.calendars.take_sync_problem() {
+            app.message = Some(problem);
+        }
+
+        Ok(())
+    }
+
+    /// Render the tab bar and the active view below it
+    pub fn draw(app: &mut App, frame: &mut Frame) {
+        let area = frame.area();
+        let tab_area = Rect::new(area.x, area.y, area.width, 1);
+        let view_area = Rect::new(
+            area.x,
+            area.y + 1,
+            area.width,
+            area.height.saturating_sub(1),
+        );
+
+        app.views.tab_area = tab_area;
+        frame.render_widget(app.views.tab_bar(app), tab_area);
+
+        app.views.active().draw(app, frame, view_area);
+    }
+
+    /// Titles of all views, the active one highlighted
+    fn tab_bar(&self, app: &App) -> Line<'static> {
+        let spans = self.views.iter().enumerate().map(|(idx, view)| {
+            let style = match idx == self.active {
+                true => app.theme.status_bar.patch(app.theme.highlight),
+                false => app.theme.status_bar,
+            };
+
+            format!(" {} ", view.title()).set_style(style)
+        });
+
+        Line::from(spans.collect::<Vec<_>>()).style(app.theme.status_bar)
+    }
+
+    /// Index of the tab at a position
+    fn tab_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.tab_area.contains(Position::new(column, row)) {
+            return None;
+        }
+
+        let mut start = self.tab_area.x;
+        for (idx, view) in self.views.iter().enumerate() {
+            let end = start + view.title().width() as u16 + 2;
+            if (start..end).contains(&column) {
+                return Some(idx);
+            }
+            start = end;
+        }
+
+        None
+    }
+}
+
+/// Check if a view is of the given type
+fn is_view<V: View>(view: &dyn View) -> bool {
+    (view as &dyn Any).is::<V>()
+}
+
+/// Split an area into the area of the view and the status bar below it
+fn split_status_bar(area: Rect) -> (Rect, Rect) {
+    let height = area.height.saturating_sub(1);
+
+    (
+        Rect::new(area.x, area.y, area.width, height),
+        Rect::new(area.x, area.y + height, area.width, 1),
+    )
+}
+
+/// Render the message or the keybinds in the status bar
+fn render_status_bar(app: &App, keybinds: Vec<(String, String)>, frame: &mut Frame, area: Rect) {
+    match &app.message {
+        Some(message) => {
+            let line = Line::from(format!(" {}", message)).style(app.theme.status_bar);
+            frame.render_widget(line, area);
+        }
+        None => frame.render_widget(
+            StatusBar::new(keybinds).styles(app.theme.status_bar, app.theme.status_bar_key),
+            area,
+        ),
+    }
+}
+
+/// Keybinds for the status bar from pairs of keys and descriptions
+fn keybinds_from<'a>(
+    keybinds: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<(String, String)> {
+    keybinds
+        .into_iter()
+        .map(|(key, description)| (key.to_owned(), description.to_owned()))
+        .collect()
+}
+
+/// Handle a click on a keybind hint in the status bar like a press of the key
+///
+/// Returns `None` if the click was not on a hint. Only keys of a single character are
+/// pressed, clicks on other hints are ignored.
+fn click_status_bar(
+    view: &impl View,
+    app: &mut App,
+    keybinds: Vec<(String, String)>,
+    mouse_event: MouseEvent,
+) -> Option<Result<()>> {
+    let status_bar = StatusBar::new(keybinds);
+    let key = status_bar.hint_at(app.status_bar_area, mouse_event.column, mouse_event.row)?;
+
+    let mut chars = key.chars();
+    Some(match (chars.next(), chars.next()) {
+        (Some(c), None) => view.handle_key_event(app, KeyCode::Char(c).into()),
+        _ => Ok(()),
+    })
 }
 
 /// Main application view that displays the todo list
@@ -30,107 +294,456 @@ pub trait View {
 pub struct MainView;
 
 impl MainView {
+    /// Keybinds shown in the status bar
+    fn keybinds(&self, app: &App) -> Vec<(String, String)> {
+        // Calendars are chosen by number
+        if let State::MoveTarget { .. } = app.state {
+            return app
+                .calendars
+                .names()
+                .into_iter()
+                .take(9)
+                .enumerate()
+                .map(|(idx, name)| ((idx + 1).to_string(), name.clone()))
+                .collect();
+        }
+
+        let keybinds = match (&app.state, &app.display.layout) {
+            // All other keys are listed in the help
+            (State::Normal, _) => vec![
+                ("q", "Exit"),
+                ("n", "New"),
+                ("x", "Done"),
+                ("z", "Snooze"),
+                ("u", "Undo"),
+                ("v", "Select"),
+                ("A", "Mark all"),
+                ("b", "Batch"),
+                ("H", "Help"),
+            ],
+            (State::ConfigSort, _) => vec![
+                ("d", "By Date"),
+                ("p", "By Priority"),
+                ("i", "By Index"),
+                ("t", "By Start"),
+                ("n", "By Summary"),
+                ("c", "By Created"),
+                ("m", "By Modified"),
+                ("u", "By Status"),
+                ("l", "By Calendar"),
+                ("a", "Toggle Ascending"),
+                ("o", "Toggle Done Last"),
+            ],
+            (State::ConfigFilter, _) => {
+                vec![("d", "Rotate show done"), ("s", "Toggle deferred")]
+            }
+            (State::Snooze { .. }, _) => vec![
+                ("1", "1 day"),
+                ("3", "3 days"),
+                ("w", "Next Monday"),
+                ("c", "Custom"),
+            ],
+            (State::Visual(_), _) => vec![("j/k", "Extend"), ("v", "Mark range")],
+            (State::Batch, _) => vec![
+                ("c", "Complete"),
+                ("o", "Uncomplete"),
+                ("d", "Delete"),
+                ("p", "Priority"),
+                ("D", "Due date"),
+                ("S", "Start date"),
+                ("t", "Add tag"),
+                ("n", "Add alarm"),
+                ("N", "Remove alarms"),
+                ("m", "Move"),
+                ("y", "Copy"),
+            ],
+            _ => vec![],
+        };
+
+        keybinds_from(keybinds)
+    }
+}
+
+impl View for MainView {
+    fn title(&self) -> &'static str {
+        "Tasks"
+    }
+
     /// Handle key press events for navigation and quitting
     fn handle_key_event(&self, app: &mut App, key_event: KeyEvent) -> Result<()> {
         match app.state {
             State::Normal => match key_event.code {
-                KeyCode::Char('q') => Ok(app.exit()),
-                KeyCode::Char('j') => Ok(app.list_state.select_next()),
-                KeyCode::Char('k') => Ok(app.list_state.select_previous()),
-                KeyCode::Char('s') => Ok(app.switch_state(State::ConfigSort)),
-                KeyCode::Char('f') => Ok(app.switch_state(State::ConfigFilter)),
+                KeyCode::Char('q') => {
+                    app.exit();
+                    Ok(())
+                }
+                KeyCode::Char('j') => {
+                    app.list_state.select_next();
+                    Ok(())
+                }
+                KeyCode::Char('k') => {
+                    app.list_state.select_previous();
+                    Ok(())
+                }
+                KeyCode::Char('s') => {
+                    app.switch_state(State::ConfigSort);
+                    Ok(())
+                }
+                KeyCode::Char('f') => {
+                    app.switch_state(State::ConfigFilter);
+                    Ok(())
+                }
                 KeyCode::Char('x') => app.toggle_done(),
-                KeyCode::Esc => Ok(app.escape()),
+                KeyCode::Char('+') => app.step_priority(true),
+                KeyCode::Char('-') => app.step_priority(false),
+                KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    app.redo()
+                }
+                KeyCode::Char('r') => app.reload(),
+                KeyCode::Char('u') => app.undo(),
+                KeyCode::Char('t') => {
+                    app.toggle_layout();
+                    Ok(())
+                }
+                KeyCode::Char('h') if app.display.layout == LayoutVariant::Table => {
+                    app.select_column(false);
+                    Ok(())
+                }
+                KeyCode::Char('l') if app.display.layout == LayoutVariant::Table => {
+                    app.select_column(true);
+                    Ok(())
+                }
+                KeyCode::Char('<') if app.display.layout == LayoutVariant::Table => {
+                    app.resize_column(-1);
+                    Ok(())
+                }
+                KeyCode::Char('>') if app.display.layout == LayoutVariant::Table => {
+                    app.resize_column(1);
+                    Ok(())
+                }
+                KeyCode::Char(c @ '1'..='9') if app.display.layout == LayoutVariant::Table => {
+                    app.sort_by_column(c as usize - '1' as usize)
+                }
+                KeyCode::Char(' ') => {
+                    app.toggle_mark();
+                    Ok(())
+                }
+                KeyCode::Char('v') => {
+                    app.start_visual();
+                    Ok(())
+                }
+                KeyCode::Char('A') => {
+                    app.mark_all();
+                    Ok(())
+                }
+                KeyCode::Char('b') => {
+                    app.switch_state(State::Batch);
+                    Ok(())
+                }
+                KeyCode::Char('S') => {
+                    app.open_prompt(Prompt::Start);
+                    Ok(())
+                }
+                KeyCode::Char('n') => {
+                    app.open_prompt(Prompt::New);
+                    Ok(())
+                }
+                KeyCode::Char('?') => {
+                    app.explain_index();
+                    Ok(())
+                }
+                KeyCode::Enter => {
+                    app.views.show::<DetailView>();
+                    Ok(())
+                }
+                KeyCode::Char('a') => {
+                    app.views.show::<AgendaView>();
+                    Ok(())
+                }
+                KeyCode::Char('c') => {
+                    app.move_calendar_cursor(app.calendar_cursor);
+                    app.views.show::<CalendarView>();
+                    Ok(())
+                }
+                KeyCode::Char('H') => {
+                    app.views.show::<HelpView>();
+                    Ok(())
+                }
+                KeyCode::Char('z') => {
+                    app.switch_state(State::Snooze { overdue: false });
+                    Ok(())
+                }
+                KeyCode::Char('Z') => {
+                    app.switch_state(State::Snooze { overdue: true });
+                    Ok(())
+                }
+                // First escape clears the marks, the second one the selection
+                KeyCode::Esc if !app.marked.is_empty() => {
+                    app.marked.clear();
+                    Ok(())
+                }
+                KeyCode::Esc => {
+                    app.escape();
+                    Ok(())
+                }
+                _ => Ok(()),
+            },
+            State::Visual(_) => match key_event.code {
+                KeyCode::Char('j') => {
+                    app.list_state.select_next();
+                    Ok(())
+                }
+                KeyCode::Char('k') => {
+                    app.list_state.select_previous();
+                    Ok(())
+                }
+                KeyCode::Char(' ') | KeyCode::Char('v') | KeyCode::Enter => {
+                    app.finish_visual();
+                    Ok(())
+                }
+                _ => {
+                    app.switch_state(State::Normal);
+                    Ok(())
+                }
+            },
+            State::Batch => match key_event.code {
+                KeyCode::Char('c') => app.batch_set_completed(true),
+                KeyCode::Char('o') => app.batch_set_completed(false),
+                KeyCode::Char('d') => app.batch_delete(),
+                KeyCode::Char('p') => {
+                    app.open_prompt(Prompt::Priority);
+                    Ok(())
+                }
+                KeyCode::Char('D') => {
+                    app.open_prompt(Prompt::Due);
+                    Ok(())
+                }
+                KeyCode::Char('S') => {
+                    app.open_prompt(Prompt::Start);
+                    Ok(())
+                }
+                KeyCode::Char('t') => {
+                    app.open_prompt(Prompt::Tag);
+                    Ok(())
+                }
+                KeyCode::Char('n') => {
+                    app.open_prompt(Prompt::Alarm);
+                    Ok(())
+                }
+                KeyCode::Char('N') => app.batch_remove_alarms(),
+                KeyCode::Char('m') => {
+                    app.switch_state(State::MoveTarget { copy: false });
+                    Ok(())
+                }
+                KeyCode::Char('y') => {
+                    app.switch_state(State::MoveTarget { copy: true });
+                    Ok(())
+                }
+                _ => {
+                    app.switch_state(State::Normal);
+                    Ok(())
+                }
+            },
+            State::Snooze { overdue } => {
+                let today = local_today();
+
+                match key_event.code {
+                    KeyCode::Char('1') => app.snooze(today + TimeDelta::days(1), overdue),
+                    KeyCode::Char('3') => app.snooze(today + TimeDelta::days(3), overdue),
+                    KeyCode::Char('w') => app.snooze(next_weekday(today, Weekday::Mon), overdue),
+                    KeyCode::Char('c') => {
+                        app.open_prompt(Prompt::Snooze { overdue });
+                        Ok(())
+                    }
+                    _ => {
+                        app.switch_state(State::Normal);
+                        Ok(())
+                    }
+                }
+            }
+            State::MoveTarget { copy } => match key_event.code {
+                KeyCode::Char(c @ '1'..='9') => {
+                    let target = app
+                        .calendars
+                        .names()
+                        .get(c as usize - '1' as usize)
+                        .map(|name| name.to_string());
+
+                    match target {
+                        Some(target) => app.batch_transfer(&target, copy),
+                        None => Ok(()),
+                    }
+                }
+                _ => {
+                    app.switch_state(State::Normal);
+                    Ok(())
+                }
+            },
+            State::Prompt(_) => match key_event.code {
+                KeyCode::Char(c) => {
+                    app.input.push(c);
+                    Ok(())
+                }
+                KeyCode::Backspace => {
+                    app.input.pop();
+                    Ok(())
+                }
+                KeyCode::Enter => app.submit_prompt(),
+                KeyCode::Esc => {
+                    app.switch_state(State::Normal);
+                    Ok(())
+                }
                 _ => Ok(()),
             },
             State::ConfigSort => match key_event.code {
                 KeyCode::Char('d') => app.configure_sort(SortingConfig {
                     by: SortingVariant::Date,
-                    ascending: app.display.sort.ascending,
-                    ignore_done: app.display.sort.ignore_done,
+                    ..app.display.sort.clone()
                 }),
                 KeyCode::Char('p') => app.configure_sort(SortingConfig {
                     by: SortingVariant::Priority,
-                    ascending: app.display.sort.ascending,
-                    ignore_done: app.display.sort.ignore_done,
+                    ..app.display.sort.clone()
                 }),
                 KeyCode::Char('i') => app.configure_sort(SortingConfig {
                     by: SortingVariant::Index,
-                    ascending: app.display.sort.ascending,
-                    ignore_done: app.display.sort.ignore_done,
+                    ..app.display.sort.clone()
+                }),
+                KeyCode::Char('t') => app.configure_sort(SortingConfig {
+                    by: SortingVariant::Start,
+                    ..app.display.sort.clone()
+                }),
+                KeyCode::Char('n') => app.configure_sort(SortingConfig {
+                    by: SortingVariant::Summary,
+                    ..app.display.sort.clone()
+                }),
+                KeyCode::Char('c') => app.configure_sort(SortingConfig {
+                    by: SortingVariant::Created,
+                    ..app.display.sort.clone()
+                }),
+                KeyCode::Char('m') => app.configure_sort(SortingConfig {
+                    by: SortingVariant::LastModified,
+                    ..app.display.sort.clone()
+                }),
+                KeyCode::Char('u') => app.configure_sort(SortingConfig {
+                    by: SortingVariant::Status,
+                    ..app.display.sort.clone()
+                }),
+                KeyCode::Char('l') => app.configure_sort(SortingConfig {
+                    by: SortingVariant::Calendar,
+                    ..app.display.sort.clone()
                 }),
                 KeyCode::Char('a') => app.configure_sort(SortingConfig {
-                    by: app.display.sort.by.clone(),
                     ascending: !app.display.sort.ascending,
-                    ignore_done: app.display.sort.ignore_done,
+                    ..app.display.sort.clone()
                 }),
-                _ => Ok(app.escape()),
+                KeyCode::Char('o') => app.configure_sort(SortingConfig {
+                    ignore_done: !app.display.sort.ignore_done,
+                    ..app.display.sort.clone()
+                }),
+                _ => {
+                    app.escape();
+                    Ok(())
+                }
             },
             State::ConfigFilter => match key_event.code {
                 KeyCode::Char('d') => app.configure_filter(FilterConfig {
                     show_done: app.display.filter.show_done.next(),
                     show_done_for: app.display.filter.show_done_for,
+                    hide_deferred: app.display.filter.hide_deferred,
                 }),
-                _ => Ok(app.escape()),
+                KeyCode::Char('s') => app.configure_filter(FilterConfig {
+                    show_done: app.display.filter.show_done.clone(),
+                    show_done_for: app.display.filter.show_done_for,
+                    hide_deferred: !app.display.filter.hide_deferred,
+                }),
+                _ => {
+                    app.escape();
+                    Ok(())
+                }
             },
             _ => Ok(()),
         }
     }
-}
 
-impl View for MainView {
-    fn handle_events(&self, app: &mut App) -> Result<()> {
-        // Process keyboard events
-        match event::read()? {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.handle_key_event(app, key_event)?
+    /// Handle mouse events for selecting and toggling tasks, scrolling and status bar hints
+    ///
+    /// Tasks are only clicked and scrolled in normal and visual mode, otherwise a click
+    /// closes the open menu or prompt first.
+    fn handle_mouse_event(&self, app: &mut App, mouse_event: MouseEvent) -> Result<()> {
+        let (column, row) = (mouse_event.column, mouse_event.row);
+        let on_tasks = matches!(app.state, State::Normal | State::Visual(_));
+
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(res) = click_status_bar(self, app, self.keybinds(app), mouse_event) {
+                    return res;
+                }
+
+                if !on_tasks {
+                    app.switch_state(State::Normal);
+                    return Ok(());
+                }
+
+                let hit = match app.display.layout {
+                    LayoutVariant::List => ToDoList::hit(app, app.task_area, column, row),
+                    LayoutVariant::Table => ToDoTable::hit(app, app.task_area, column, row),
+                };
+
+                match hit {
+                    Some((idx, on_progress)) => app.click_task(idx, on_progress),
+                    None => Ok(()),
+                }
+            }
+            MouseEventKind::ScrollDown if on_tasks => {
+                app.list_state.select_next();
+                Ok(())
             }
-            _ => {}
+            MouseEventKind::ScrollUp if on_tasks => {
+                app.list_state.select_previous();
+                Ok(())
+            }
+            _ => Ok(()),
         }
-
-        Ok(())
     }
 
-    fn draw(&self, app: &mut App, frame: &mut Frame) {
-        // Create title with bold styling
-        let title = Line::from(" ISEK ".bold());
+    fn draw(&self, app: &mut App, frame: &mut Frame, area: Rect) {
+        // Create title with themed styling
+        let title = Line::from(" ISEK ".set_style(app.theme.title));
 
         // Configure the block border and padding for the todo list display
         let block = Block::bordered()
             .title(title.centered())
             .border_set(border::ROUNDED)
+            .border_style(app.theme.border)
             .padding(Padding::symmetric(1, 1));
 
-        let area = frame.area();
-        let status_bar_area = Rect::new(0, area.height - 1, area.width, 1);
-        let main_area = Rect::new(0, 0, area.width, area.height - status_bar_area.height);
+        let (main_area, status_bar_area) = split_status_bar(area);
 
-        // Render the todo list widget in the main frame area
-        frame.render_stateful_widget(ToDoList::default().block(block), main_area, app);
+        // Remember areas for mouse interaction
+        app.task_area = block.inner(main_area);
+        app.status_bar_area = status_bar_area;
 
-        // Render status bar
-        let keybinds = match app.state {
-            State::Normal => vec![("q", "Exit"), ("s", "Sort"), ("f", "Filter"), ("x", "Toggle done")],
-            State::ConfigSort => vec![
-                ("d", "By Date"),
-                ("p", "By Priority"),
-                ("i", "By Index"),
-                ("a", "Toggle Ascending"),
-            ],
-            State::ConfigFilter => {
-                vec![("d", "Rotate show done")]
+        // Render the todo list or table widget in the main frame area
+        match app.display.layout {
+            LayoutVariant::List => {
+                frame.render_stateful_widget(ToDoList::default().block(block), main_area, app)
             }
-            _ => vec![],
-        };
+            LayoutVariant::Table => {
+                frame.render_stateful_widget(ToDoTable::default().block(block), main_area, app)
+            }
+        }
 
-        frame.render_widget(
-            StatusBar::new(
-                keybinds
-                    .into_iter()
-                    .map(|(a, b)| (String::from(a), String::from(b)))
-                    .collect(),
-            ),
-            status_bar_area,
-        );
+        // Render prompt, message or keybinds in the status bar
+        if let State::Prompt(prompt) = &app.state {
+            let line = Line::from(vec![
+                format!(" {}: ", prompt.title()).set_style(app.theme.status_bar_key),
+                app.input.clone().into(),
+                "█".into(),
+            ])
+            .style(app.theme.status_bar);
+            frame.render_widget(line, status_bar_area);
+        } else {
+            render_status_bar(app, self.keybinds(app), frame, status_bar_area);
+        }
     }
 }
@@ -0,0 +1,109 @@
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use eyre::Result;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Styled,
+    symbols::border,
+    text::Line,
+    widgets::{Block, Padding},
+};
+
+use crate::{App, widgets::Agenda};
+
+use super::{MainView, View, click_status_bar, keybinds_from, render_status_bar, split_status_bar};
+
+/// View listing events and due tasks of today or the next 7 days
+#[derive(Default)]
+pub struct AgendaView;
+
+impl AgendaView {
+    /// Keybinds shown in the status bar
+    fn keybinds(&self, app: &App) -> Vec<(String, String)> {
+        let range = match app.agenda_range.toggle().days() {
+            1 => "Today",
+            _ => "Week",
+        };
+
+        keybinds_from([
+            ("q", "Exit"),
+            ("a", "Tasks"),
+            ("j/k", "Scroll"),
+            ("w", range),
+            ("r", "Reload"),
+        ])
+    }
+}
+
+impl View for AgendaView {
+    fn title(&self) -> &'static str {
+        "Agenda"
+    }
+
+    /// Handle key press events for scrolling, switching the range and leaving the agenda
+    fn handle_key_event(&self, app: &mut App, key_event: KeyEvent) -> Result<()> {
+        match key_event.code {
+            KeyCode::Char('q') => {
+                app.exit();
+                Ok(())
+            }
+            KeyCode::Char('j') => {
+                app.agenda_state.select_next();
+                Ok(())
+            }
+            KeyCode::Char('k') => {
+                app.agenda_state.select_previous();
+                Ok(())
+            }
+            KeyCode::Char('w') => {
+                app.agenda_range = app.agenda_range.toggle();
+                app.agenda_state.select(None);
+                Ok(())
+            }
+            KeyCode::Char('r') => app.reload(),
+            KeyCode::Char('a') | KeyCode::Esc => {
+                app.views.show::<MainView>();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle mouse events for scrolling and status bar hints
+    fn handle_mouse_event(&self, app: &mut App, mouse_event: MouseEvent) -> Result<()> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                click_status_bar(self, app, self.keybinds(app), mouse_event).unwrap_or(Ok(()))
+            }
+            MouseEventKind::ScrollDown => {
+                app.agenda_state.select_next();
+                Ok(())
+            }
+            MouseEventKind::ScrollUp => {
+                app.agenda_state.select_previous();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn draw(&self, app: &mut App, frame: &mut Frame, area: Rect) {
+        let title = Line::from(
+            format!(" AGENDA · {} ", app.agenda_range.title()).set_style(app.theme.title),
+        );
+
+        let block = Block::bordered()
+            .title(title.centered())
+            .border_set(border::ROUNDED)
+            .border_style(app.theme.border)
+            .padding(Padding::symmetric(1, 1));
+
+        let (main_area, status_bar_area) = split_status_bar(area);
+
+        app.status_bar_area = status_bar_area;
+
+        frame.render_stateful_widget(Agenda::default().block(block), main_area, app);
+
+        render_status_bar(app, self.keybinds(app), frame, status_bar_area);
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{Datelike, Months, TimeDelta};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use eyre::Result;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::Styled,
+    symbols::border,
+    text::Line,
+    widgets::{Block, Padding},
+};
+
+use crate::{
+    App,
+    app::CalendarMode,
+    helper::local_today,
+    widgets::{CalendarGrid, DayTasks},
+};
+
+use super::{MainView, View, click_status_bar, keybinds_from, render_status_bar, split_status_bar};
+
+/// View showing the due tasks in a month or week grid with the tasks of the selected day
+#[derive(Default)]
+pub struct CalendarView;
+
+impl CalendarView {
+    /// Keybinds shown in the status bar
+    fn keybinds(&self, app: &App) -> Vec<(String, String)> {
+        let keybinds = match (&app.rescheduling, app.calendar_mode) {
+            (Some(_), _) => vec![
+                ("h/j/k/l", "Choose day"),
+                ("m", "Move here"),
+                ("Esc", "Cancel"),
+            ],
+            (None, mode) => vec![
+                ("q", "Exit"),
+                ("c", "Tasks"),
+                ("h/j/k/l", "Day"),
+                ("H/L", "Page"),
+                ("J/K", "Select task"),
+                ("m", "Reschedule"),
+                ("t", "Today"),
+                (
+                    "w",
+                    match mode {
+                        CalendarMode::Month => "Week",
+                        CalendarMode::Week => "Month",
+                    },
+                ),
+                ("u", "Undo"),
+            ],
+        };
+
+        keybinds_from(keybinds)
+    }
+}
+
+impl View for CalendarView {
+    fn title(&self) -> &'static str {
+        "Calendar"
+    }
+
+    /// Handle key press events for moving the cursor, selecting and rescheduling tasks
+    fn handle_key_event(&self, app: &mut App, key_event: KeyEvent) -> Result<()> {
+        let cursor = app.calendar_cursor;
+
+        // Length of the period shown in the grid (used by H/L)
+        let period = |forward: bool| match (app.calendar_mode, forward) {
+            (CalendarMode::Month, true) => cursor.checked_add_months(Months::new(1)),
+            (CalendarMode::Month, false) => cursor.checked_sub_months(Months::new(1)),
+            (CalendarMode::Week, true) => Some(cursor + TimeDelta::weeks(1)),
+            (CalendarMode::Week, false) => Some(cursor - TimeDelta::weeks(1)),
+        };
+
+        match key_event.code {
+            KeyCode::Char('q') => {
+                app.exit();
+                Ok(())
+            }
+            KeyCode::Char('h') => {
+                app.move_calendar_cursor(cursor - TimeDelta::days(1));
+                Ok(())
+            }
+            KeyCode::Char('l') => {
+                app.move_calendar_cursor(cursor + TimeDelta::days(1));
+                Ok(())
+            }
+            KeyCode::Char('k') => {
+                app.move_calendar_cursor(cursor - TimeDelta::weeks(1));
+                Ok(())
+            }
+            KeyCode::Char('j') => {
+                app.move_calendar_cursor(cursor + TimeDelta::weeks(1));
+                Ok(())
+            }
+            KeyCode::Char(c @ ('H' | 'L')) => {
+                if let Some(date) = period(c == 'L') {
+                    app.move_calendar_cursor(date);
+                }
+                Ok(())
+            }
+            KeyCode::Char('J') => {
+                app.calendar_list.select_next();
+                Ok(())
+            }
+            KeyCode::Char('K') => {
+                app.calendar_list.select_previous();
+                Ok(())
+            }
+            KeyCode::Char('t') => {
+                app.move_calendar_cursor(local_today());
+                Ok(())
+            }
+            KeyCode::Char('w') => {
+                app.calendar_mode = match app.calendar_mode {
+                    CalendarMode::Month => CalendarMode::Week,
+                    CalendarMode::Week => CalendarMode::Month,
+                };
+                Ok(())
+            }
+            KeyCode::Char('m') | KeyCode::Enter => app.reschedule(),
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.redo(),
+            KeyCode::Char('u') => app.undo(),
+            KeyCode::Char('r') => app.reload(),
+            // First escape cancels rescheduling, the second one returns to the tasks
+            KeyCode::Esc if app.rescheduling.is_some() => {
+                app.rescheduling = None;
+                Ok(())
+            }
+            KeyCode::Char('c') | KeyCode::Esc => {
+                app.views.show::<MainView>();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle mouse events for scrolling the day list and status bar hints
+    fn handle_mouse_event(&self, app: &mut App, mouse_event: MouseEvent) -> Result<()> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                click_status_bar(self, app, self.keybinds(app), mouse_event).unwrap_or(Ok(()))
+            }
+            MouseEventKind::ScrollDown => {
+                app.calendar_list.select_next();
+                Ok(())
+            }
+            MouseEventKind::ScrollUp => {
+                app.calendar_list.select_previous();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn draw(&self, app: &mut App, frame: &mut Frame, area: Rect) {
+        let cursor = app.calendar_cursor;
+        let period = match app.calendar_mode {
+            CalendarMode::Month => cursor.format("%B %Y").to_string(),
+            CalendarMode::Week => format!(
+                "Week {}, {}",
+                cursor.iso_week().week(),
+                cursor.iso_week().year()
+            ),
+        };
+
+        let block = |title: String| {
+            Block::bordered()
+                .title(Line::from(title.set_style(app.theme.title)).centered())
+                .border_set(border::ROUNDED)
+                .border_style(app.theme.border)
+                .padding(Padding::symmetric(1, 0))
+        };
+        let grid_block = block(format!(" CALENDAR · {period} "));
+        let list_block = block(format!(" {} ", cursor.format("%a %d %b")));
+
+        let (main_area, status_bar_area) = split_status_bar(area);
+        let [grid_area, list_area] =
+            Layout::horizontal([Constraint::Percentage(62), Constraint::Percentage(38)])
+                .areas(main_area);
+
+        app.status_bar_area = status_bar_area;
+
+        frame.render_stateful_widget(CalendarGrid::default().block(grid_block), grid_area, app);
+        frame.render_stateful_widget(DayTasks::default().block(list_block), list_area, app);
+
+        render_status_bar(app, self.keybinds(app), frame, status_bar_area);
+    }
+}
@@ -0,0 +1,126 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use eyre::Result;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Styled,
+    symbols::border,
+    text::Line,
+    widgets::{Block, Padding},
+};
+
+use crate::{App, widgets::TaskDetail};
+
+use super::{MainView, View, click_status_bar, keybinds_from, render_status_bar, split_status_bar};
+
+/// Lines scrolled with one step of the mouse wheel
+const SCROLL_LINES: i16 = 3;
+
+/// View showing all fields of the task selected in the task list
+#[derive(Default)]
+pub struct DetailView;
+
+impl DetailView {
+    /// Keybinds shown in the status bar
+    fn keybinds(&self) -> Vec<(String, String)> {
+        keybinds_from([
+            ("q", "Exit"),
+            ("Esc", "Tasks"),
+            ("j/k", "Next/previous task"),
+            ("PgDn/PgUp", "Scroll"),
+            ("x", "Toggle done"),
+            ("+/-", "Priority"),
+            ("u", "Undo"),
+        ])
+    }
+
+    /// Scroll the task detail by some lines (up if negative)
+    fn scroll(&self, app: &mut App, lines: i16) {
+        app.detail_scroll = app.detail_scroll.saturating_add_signed(lines);
+    }
+}
+
+impl View for DetailView {
+    fn title(&self) -> &'static str {
+        "Detail"
+    }
+
+    /// Handle key press events for switching tasks and modifying the shown task
+    fn handle_key_event(&self, app: &mut App, key_event: KeyEvent) -> Result<()> {
+        match key_event.code {
+            KeyCode::Char('q') => {
+                app.exit();
+                Ok(())
+            }
+            KeyCode::Char('j') => {
+                app.list_state.select_next();
+                app.detail_scroll = 0;
+                Ok(())
+            }
+            KeyCode::Char('k') => {
+                app.list_state.select_previous();
+                app.detail_scroll = 0;
+                Ok(())
+            }
+            KeyCode::PageDown => {
+                self.scroll(app, app.detail_area.height.max(1) as i16);
+                Ok(())
+            }
+            KeyCode::PageUp => {
+                self.scroll(app, -(app.detail_area.height.max(1) as i16));
+                Ok(())
+            }
+            KeyCode::Down => {
+                self.scroll(app, 1);
+                Ok(())
+            }
+            KeyCode::Up => {
+                self.scroll(app, -1);
+                Ok(())
+            }
+            KeyCode::Char('x') => app.toggle_done(),
+            KeyCode::Char('+') => app.step_priority(true),
+            KeyCode::Char('-') => app.step_priority(false),
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.redo(),
+            KeyCode::Char('u') => app.undo(),
+            KeyCode::Esc | KeyCode::Enter => {
+                app.views.show::<MainView>();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle mouse events for scrolling and status bar hints
+    fn handle_mouse_event(&self, app: &mut App, mouse_event: MouseEvent) -> Result<()> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                click_status_bar(self, app, self.keybinds(), mouse_event).unwrap_or(Ok(()))
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll(app, SCROLL_LINES);
+                Ok(())
+            }
+            MouseEventKind::ScrollUp => {
+                self.scroll(app, -SCROLL_LINES);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn draw(&self, app: &mut App, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered()
+            .title(Line::from(" TASK ".set_style(app.theme.title)).centered())
+            .border_set(border::ROUNDED)
+            .border_style(app.theme.border)
+            .padding(Padding::symmetric(1, 1));
+
+        let (main_area, status_bar_area) = split_status_bar(area);
+        app.detail_area = block.inner(main_area);
+        app.status_bar_area = status_bar_area;
+
+        frame.render_stateful_widget(TaskDetail::default().block(block), main_area, app);
+        render_status_bar(app, self.keybinds(), frame, status_bar_area);
+    }
+}
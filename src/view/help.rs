@@ -0,0 +1,208 @@
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use eyre::Result;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Styled,
+    symbols::border,
+    text::Line,
+    widgets::{Block, Padding, Paragraph},
+};
+
+use crate::App;
+
+use super::{MainView, View, click_status_bar, keybinds_from, render_status_bar, split_status_bar};
+
+/// Lines scrolled with one step of the mouse wheel
+const SCROLL_LINES: i16 = 3;
+
+/// Keys of the task list by section (only the essential ones are shown in its status bar)
+const SECTIONS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Tasks",
+        &[
+            ("j/k", "Next/previous task"),
+            ("Enter", "Show details"),
+            ("n", "New task"),
+            ("x", "Toggle done"),
+            ("+/-", "Raise/lower priority"),
+            ("S", "Set start date"),
+            ("?", "Explain index"),
+            ("u", "Undo"),
+            ("Ctrl+r", "Redo"),
+            ("r", "Reload"),
+            ("Esc", "Clear marks, then the selection"),
+            ("q", "Exit"),
+        ],
+    ),
+    (
+        "Selection",
+        &[
+            ("Space", "Mark task"),
+            ("v", "Select range (j/k extend, v marks)"),
+            ("A", "Mark all"),
+            ("b", "Batch menu for the marked tasks"),
+        ],
+    ),
+    (
+        "Batch menu",
+        &[
+            ("c/o", "Complete/uncomplete"),
+            ("d", "Delete"),
+            ("p", "Set priority"),
+            ("D", "Set due date"),
+            ("S", "Set start date"),
+            ("t", "Add tag"),
+            ("n/N", "Add alarm/remove alarms"),
+            ("m/y", "Move/copy to calendar"),
+        ],
+    ),
+    (
+        "Snooze",
+        &[
+            ("z", "Snooze task (1, 3 days, w next Monday, c custom)"),
+            ("Z", "Snooze all overdue tasks"),
+        ],
+    ),
+    (
+        "Sort and filter",
+        &[("s", "Sort menu"), ("f", "Filter menu")],
+    ),
+    (
+        "Table",
+        &[
+            ("t", "Toggle table/list"),
+            ("h/l", "Select column"),
+            ("</>", "Resize column"),
+            ("1-9", "Sort by column"),
+        ],
+    ),
+    (
+        "Views",
+        &[
+            ("a", "Agenda"),
+            ("c", "Calendar"),
+            ("Tab/Shift+Tab", "Next/previous tab"),
+            ("H", "Help"),
+        ],
+    ),
+    (
+        "Mouse",
+        &[
+            ("Click", "Select task (closes menus and prompts first)"),
+            ("Double click", "Toggle done (also a click on the checkbox)"),
+            ("Wheel", "Next/previous task"),
+        ],
+    ),
+];
+
+/// View listing all keys of the task list
+#[derive(Default)]
+pub struct HelpView;
+
+impl HelpView {
+    /// Keybinds shown in the status bar
+    fn keybinds(&self) -> Vec<(String, String)> {
+        keybinds_from([
+            ("q", "Exit"),
+            ("Esc", "Tasks"),
+            ("j/k", "Scroll"),
+            ("PgDn/PgUp", "Scroll page"),
+        ])
+    }
+
+    /// Scroll the help by some lines (up if negative)
+    fn scroll(&self, app: &mut App, lines: i16) {
+        app.help_scroll = app.help_scroll.saturating_add_signed(lines);
+    }
+
+    /// Lines of all sections, keys aligned in a column
+    fn lines(app: &App) -> Vec<Line<'static>> {
+        let width = SECTIONS
+            .iter()
+            .flat_map(|(_, keys)| keys.iter())
+            .map(|(key, _)| key.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = vec![];
+        for (section, keys) in SECTIONS {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+
+            lines.push(Line::from(section.set_style(app.theme.title)));
+            lines.extend(keys.iter().map(|(key, description)| {
+                Line::from(vec![
+                    format!("{key:<width$}").set_style(app.theme.status_bar_key),
+                    format!("  {description}").into(),
+                ])
+            }));
+        }
+
+        lines
+    }
+}
+
+impl View for HelpView {
+    fn title(&self) -> &'static str {
+        "Help"
+    }
+
+    fn handle_key_event(&self, app: &mut App, key_event: KeyEvent) -> Result<()> {
+        match key_event.code {
+            KeyCode::Char('q') => app.exit(),
+            KeyCode::Char('j') | KeyCode::Down => self.scroll(app, 1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll(app, -1),
+            KeyCode::PageDown => self.scroll(app, app.help_area.height.max(1) as i16),
+            KeyCode::PageUp => self.scroll(app, -(app.help_area.height.max(1) as i16)),
+            KeyCode::Esc => app.views.show::<MainView>(),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Handle mouse events for scrolling and status bar hints
+    fn handle_mouse_event(&self, app: &mut App, mouse_event: MouseEvent) -> Result<()> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                click_status_bar(self, app, self.keybinds(), mouse_event).unwrap_or(Ok(()))
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll(app, SCROLL_LINES);
+                Ok(())
+            }
+            MouseEventKind::ScrollUp => {
+                self.scroll(app, -SCROLL_LINES);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn draw(&self, app: &mut App, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered()
+            .title(Line::from(" HELP ".set_style(app.theme.title)).centered())
+            .border_set(border::ROUNDED)
+            .border_style(app.theme.border)
+            .padding(Padding::symmetric(1, 1));
+
+        let (main_area, status_bar_area) = split_status_bar(area);
+        app.help_area = block.inner(main_area);
+        app.status_bar_area = status_bar_area;
+
+        // Scrolled no further than to the last line
+        let lines = Self::lines(app);
+        let max_scroll = lines.len().saturating_sub(app.help_area.height as usize);
+        app.help_scroll = app.help_scroll.min(max_scroll as u16);
+
+        let paragraph = Paragraph::new(lines)
+            .style(app.theme.list)
+            .scroll((app.help_scroll, 0))
+            .block(block);
+
+        frame.render_widget(paragraph, main_area);
+        render_status_bar(app, self.keybinds(), frame, status_bar_area);
+    }
+}
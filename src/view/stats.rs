@@ -0,0 +1,69 @@
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use eyre::Result;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Styled,
+    symbols::border,
+    text::Line,
+    widgets::{Block, Padding},
+};
+
+use crate::{App, widgets::Stats};
+
+use super::{MainView, View, click_status_bar, keybinds_from, render_status_bar, split_status_bar};
+
+/// View showing task counts per calendar and by priority
+#[derive(Default)]
+pub struct StatsView;
+
+impl StatsView {
+    /// Keybinds shown in the status bar
+    fn keybinds(&self) -> Vec<(String, String)> {
+        keybinds_from([("q", "Exit"), ("Esc", "Tasks"), ("r", "Reload")])
+    }
+}
+
+impl View for StatsView {
+    fn title(&self) -> &'static str {
+        "Stats"
+    }
+
+    fn handle_key_event(&self, app: &mut App, key_event: KeyEvent) -> Result<()> {
+        match key_event.code {
+            KeyCode::Char('q') => {
+                app.exit();
+                Ok(())
+            }
+            KeyCode::Char('r') => app.reload(),
+            KeyCode::Esc => {
+                app.views.show::<MainView>();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle mouse events for status bar hints
+    fn handle_mouse_event(&self, app: &mut App, mouse_event: MouseEvent) -> Result<()> {
+        if mouse_event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return Ok(());
+        }
+
+        click_status_bar(self, app, self.keybinds(), mouse_event).unwrap_or(Ok(()))
+    }
+
+    fn draw(&self, app: &mut App, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered()
+            .title(Line::from(" STATISTICS ".set_style(app.theme.title)).centered())
+            .border_set(border::ROUNDED)
+            .border_style(app.theme.border)
+            .padding(Padding::symmetric(1, 1));
+
+        let (main_area, status_bar_area) = split_status_bar(area);
+        app.status_bar_area = status_bar_area;
+
+        frame.render_stateful_widget(Stats::default().block(block), main_area, app);
+        render_status_bar(app, self.keybinds(), frame, status_bar_area);
+    }
+}
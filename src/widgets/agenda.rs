@@ -0,0 +1,131 @@
+use chrono::{NaiveDateTime, Utc};
+use ratatui::{
+    style::{Style, Styled},
+    text::{Line, Span},
+    widgets::{Block, List, StatefulWidget},
+};
+
+use crate::{
+    App,
+    agenda::{AgendaEntry, AgendaItem, agenda},
+    helper::local_today,
+};
+
+/// Width of the time column (e.g. "09:00-10:30")
+const TIME_WIDTH: usize = 11;
+
+/// Agenda Widget listing events and due tasks grouped by day
+#[derive(Default)]
+pub struct Agenda<'a> {
+    block: Option<Block<'a>>,
+}
+
+impl<'a> Agenda<'a> {
+    /// Configure the block (border and title) for this widget
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Line of an entry: time, summary and calendar
+    fn entry_line(entry: &AgendaEntry, state: &App) -> Line<'static> {
+        let theme = &state.theme;
+        let now = Utc::now();
+
+        let (time, time_style) = match &entry.item {
+            AgendaItem::Task(t) if t.is_overdue(&now) => ("overdue".to_owned(), theme.date_overdue),
+            AgendaItem::Task(_) if entry.all_day => ("due".to_owned(), theme.date),
+            AgendaItem::Task(_) => (entry.start.format("%H:%M").to_string(), theme.date),
+            AgendaItem::Event(_) if entry.all_day => ("all day".to_owned(), theme.date),
+            AgendaItem::Event(_) if entry.start == entry.end => {
+                (entry.start.format("%H:%M").to_string(), theme.date)
+            }
+            AgendaItem::Event(_) => (
+                format!(
+                    "{}-{}",
+                    Self::time_on(entry.start, entry),
+                    Self::time_on(entry.end, entry)
+                ),
+                theme.date,
+            ),
+        };
+
+        let (marker, marker_style, (display_name, name, color)) = match &entry.item {
+            // Events are marked with the color of their calendar
+            AgendaItem::Event(e) => (
+                "●".to_owned(),
+                match theme.calendar_chip(e.color).bg {
+                    Some(color) => Style::new().fg(color),
+                    None => Style::new(),
+                },
+                (e.cal_display_name, e.cal_name, e.color),
+            ),
+            AgendaItem::Task(t) => (
+                state.display.progress.none.trim_end().to_owned(),
+                theme.pending,
+                (t.cal_display_name, t.cal_name, t.color),
+            ),
+        };
+
+        Line::from(vec![
+            Span::raw("  "),
+            format!("{time:<TIME_WIDTH$}").set_style(time_style),
+            Span::raw(" "),
+            marker.set_style(marker_style),
+            Span::raw(" "),
+            Span::raw(entry.summary()),
+            Span::raw("  "),
+            format!(" {} ", display_name.as_ref().unwrap_or(name))
+                .set_style(theme.calendar_chip(color)),
+        ])
+    }
+
+    /// Time of day, or "…" if the time is on another day than the entry
+    fn time_on(dt: NaiveDateTime, entry: &AgendaEntry) -> String {
+        match dt.date() == entry.date {
+            true => dt.format("%H:%M").to_string(),
+            false => "…".to_owned(),
+        }
+    }
+}
+
+impl StatefulWidget for Agenda<'_> {
+    type State = App;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let today = local_today();
+        let entries = agenda(&state.calendars, today, state.agenda_range.days());
+
+        let mut items = vec![];
+        for date in today.iter_days().take(state.agenda_range.days() as usize) {
+            let heading = match (date - today).num_days() {
+                0 => format!("{} (today)", date.format("%a %d %b")),
+                1 => format!("{} (tomorrow)", date.format("%a %d %b")),
+                _ => date.format("%a %d %b").to_string(),
+            };
+            items.push(Line::from(heading.set_style(state.theme.title)));
+
+            let day: Vec<&AgendaEntry> = entries.iter().filter(|e| e.date == date).collect();
+            if day.is_empty() {
+                items.push(Line::from("  Nothing planned".set_style(state.theme.done)));
+            }
+
+            items.extend(day.into_iter().map(|e| Self::entry_line(e, state)));
+        }
+
+        // Configure and render the list widget
+        let mut list = List::new(items).style(state.theme.list);
+
+        if let Some(block) = self.block {
+            list = list.block(block)
+        }
+
+        list.highlight_style(state.theme.highlight)
+            .render(area, buf, &mut state.agenda_state);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use icalendar::Component;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Styled},
+    text::{Line, Span},
+    widgets::{Block, List, StatefulWidget, Widget},
+};
+
+use crate::{
+    App,
+    app::{CalendarMode, IsekTodo},
+    helper::local_today,
+    line_format::truncate,
+};
+
+/// Width of the highlight symbol in front of every task in the day list
+const HIGHLIGHT_WIDTH: u16 = 2;
+
+/// Calendar grid Widget showing a month or a week with the tasks due on each day
+#[derive(Default)]
+pub struct CalendarGrid<'a> {
+    block: Option<Block<'a>>,
+}
+
+impl<'a> CalendarGrid<'a> {
+    /// Configure the block (border and title) for this widget
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Days shown for the given mode and cursor (always whole weeks starting on Monday)
+    pub fn visible_days(mode: CalendarMode, cursor: NaiveDate) -> Vec<NaiveDate> {
+        let monday =
+            |date: NaiveDate| date - TimeDelta::days(date.weekday().num_days_from_monday() as i64);
+
+        match mode {
+            CalendarMode::Week => monday(cursor).iter_days().take(7).collect(),
+            CalendarMode::Month => {
+                let first = cursor.with_day(1).unwrap_or(cursor);
+                let last = first
+                    .iter_days()
+                    .take_while(|d| d.month() == first.month())
+                    .last()
+                    .unwrap_or(first);
+
+                monday(first)
+                    .iter_days()
+                    .take_while(|d| *d <= last || d.weekday().num_days_from_monday() != 0)
+                    .collect()
+            }
+        }
+    }
+
+    /// Style of a task marker in the color of its calendar
+    fn calendar_style(state: &App, todo: &IsekTodo) -> Style {
+        match state.theme.calendar_chip(todo.color).bg {
+            Some(color) => Style::new().fg(color),
+            None => Style::new(),
+        }
+    }
+
+    /// Render a single day: its number and the tasks due on it
+    fn render_day(state: &App, date: NaiveDate, todos: &[&IsekTodo], area: Rect, buf: &mut Buffer) {
+        let theme = &state.theme;
+        let cursor = state.calendar_cursor;
+
+        let number_style = if date == cursor && state.rescheduling.is_some() {
+            theme.marked
+        } else if date == cursor {
+            theme.highlight
+        } else if date == local_today() {
+            theme.title
+        } else if state.calendar_mode == CalendarMode::Month && date.month() != cursor.month() {
+            theme.done
+        } else {
+            theme.list
+        };
+
+        let width = area.width.saturating_sub(1) as usize;
+        let mut lines = vec![Line::from(
+            format!("{:>2}", date.day()).set_style(number_style),
+        )];
+
+        match state.calendar_mode {
+            // One marker per task in the color of its calendar
+            CalendarMode::Month => {
+                let markers = todos
+                    .iter()
+                    .map(|t| "●".set_style(Self::calendar_style(state, t)))
+                    .collect::<Vec<Span>>();
+
+                let mut first = true;
+                for chunk in markers.chunks(width.saturating_sub(3).max(1)) {
+                    match first {
+                        true => {
+                            lines[0].push_span(Span::raw(" "));
+                            chunk.iter().for_each(|m| lines[0].push_span(m.clone()));
+                            first = false;
+                        }
+                        false => lines.push(Line::from(chunk.to_vec())),
+                    }
+                }
+            }
+            // Summaries of the tasks
+            CalendarMode::Week => {
+                lines.extend(todos.iter().map(|t| {
+                    let summary = t.get().get_summary().unwrap_or_default();
+                    Line::from(vec![
+                        "▌".set_style(Self::calendar_style(state, t)),
+                        truncate(summary, width.saturating_sub(1)).into(),
+                    ])
+                }));
+            }
+        }
+
+        for (idx, line) in lines.into_iter().take(area.height as usize).enumerate() {
+            buf.set_line(
+                area.x,
+                area.y + idx as u16,
+                &line,
+                area.width.saturating_sub(1),
+            );
+        }
+    }
+}
+
+impl StatefulWidget for CalendarGrid<'_> {
+    type State = App;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let inner = match &self.block {
+            Some(block) => block.inner(area),
+            None => area,
+        };
+        if let Some(block) = self.block {
+            block.render(area, buf);
+        }
+
+        let days = Self::visible_days(state.calendar_mode, state.calendar_cursor);
+        let todos = state
+            .calendars
+            .get_todos(Some(&state.display.sort), Some(&state.display.filter));
+
+        let mut due: HashMap<NaiveDate, Vec<&IsekTodo>> = HashMap::new();
+        for todo in &todos {
+            if let Some(date) = todo.due_date() {
+                due.entry(date).or_default().push(todo);
+            }
+        }
+
+        let column_width = inner.width / 7;
+        let rows = (days.len() / 7).max(1) as u16;
+        let row_height = inner.height.saturating_sub(1) / rows;
+
+        if column_width == 0 || row_height == 0 {
+            return;
+        }
+
+        // Weekday names above the columns
+        for (idx, date) in days.iter().take(7).enumerate() {
+            let x = inner.x + idx as u16 * column_width;
+            buf.set_string(x, inner.y, date.format("%a").to_string(), state.theme.title);
+        }
+
+        for (idx, date) in days.iter().enumerate() {
+            let cell = Rect::new(
+                inner.x + (idx % 7) as u16 * column_width,
+                inner.y + 1 + (idx / 7) as u16 * row_height,
+                column_width,
+                row_height,
+            );
+
+            let todos = due.get(date).map(Vec::as_slice).unwrap_or_default();
+            Self::render_day(state, *date, todos, cell, buf);
+        }
+    }
+}
+
+/// List Widget with the tasks due on the day selected in the calendar grid
+#[derive(Default)]
+pub struct DayTasks<'a> {
+    block: Option<Block<'a>>,
+}
+
+impl<'a> DayTasks<'a> {
+    /// Configure the block (border and title) for this widget
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl StatefulWidget for DayTasks<'_> {
+    type State = App;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        // Space left for the text after border, padding and highlight symbol
+        let width = match &self.block {
+            Some(block) => block.inner(area).width,
+            None => area.width,
+        }
+        .saturating_sub(HIGHLIGHT_WIDTH) as usize;
+
+        let todos = state.tasks_due_on(state.calendar_cursor);
+        let items = todos
+            .iter()
+            .map(|t| {
+                let line = t.format(state, width);
+
+                match state.rescheduling.is_some() && state.rescheduling == t.id() {
+                    true => line.patch_style(state.theme.marked),
+                    false => line,
+                }
+            })
+            .collect::<Vec<Line>>();
+
+        let mut list = List::new(items).style(state.theme.list);
+
+        if let Some(block) = self.block {
+            list = list.block(block)
+        }
+
+        let list = list
+            .highlight_style(state.theme.highlight)
+            .highlight_symbol("> ")
+            .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        StatefulWidget::render(list, area, buf, &mut state.calendar_list);
+    }
+}
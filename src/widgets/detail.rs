@@ -0,0 +1,178 @@
+use chrono::Utc;
+use icalendar::{Component, DatePerhapsTime, TodoStatus};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Styled,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::{
+    App,
+    app::IsekTodo,
+    helper::{
+        INPUT_DATE_FORMAT, INPUT_DATE_TIME_FORMAT, format_ical_datetime,
+        format_ical_datetime_relative,
+    },
+    index::IndexScore,
+    remind::describe_alarm,
+};
+
+/// Width of the labels in front of the values
+const LABEL_WIDTH: usize = 14;
+
+/// Detail Widget showing all fields of the task selected in the task list
+#[derive(Default)]
+pub struct TaskDetail<'a> {
+    block: Option<Block<'a>>,
+}
+
+impl<'a> TaskDetail<'a> {
+    /// Configure the block (border and title) for this widget
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Labeled lines with the fields of a task
+    fn lines(todo: &IsekTodo, state: &App) -> Vec<Line<'static>> {
+        let theme = &state.theme;
+        let t = todo.get();
+        let now = Utc::now();
+
+        let date = |dt: DatePerhapsTime| {
+            format!(
+                "{} ({})",
+                format_ical_datetime(dt.clone(), INPUT_DATE_FORMAT, INPUT_DATE_TIME_FORMAT),
+                format_ical_datetime_relative(dt, &now)
+            )
+        };
+
+        let status = match (t.get_completed(), t.get_status()) {
+            (Some(_), _) | (None, Some(TodoStatus::Completed)) => "Completed",
+            (None, Some(TodoStatus::InProcess)) => "In progress",
+            (None, Some(TodoStatus::Cancelled)) => "Cancelled",
+            (None, _) => "Needs action",
+        };
+
+        let priority = t.get_priority().filter(|p| *p > 0).map(|p| {
+            let level = match p {
+                1..=4 => "high",
+                5 => "medium",
+                _ => "low",
+            };
+            format!("{p} ({level})")
+        });
+
+        let tags = todo.tags();
+        let alarms: Vec<String> = todo
+            .id()
+            .map(|id| state.calendars.get_alarms(&id))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|alarm| describe_alarm(alarm, "due"))
+            .collect();
+
+        let subscription = state
+            .calendars
+            .get_calendar(todo.cal_name)
+            .and_then(|cal| cal.subscription_source());
+
+        let fields = [
+            ("Status", Some(status.to_owned())),
+            ("Priority", priority),
+            ("Due", t.get_due().map(date)),
+            ("Start", t.get_start().map(date)),
+            ("Completed", t.get_completed().map(|dt| date(dt.into()))),
+            ("Tags", (!tags.is_empty()).then(|| tags.join(", "))),
+            ("Alarms", (!alarms.is_empty()).then(|| alarms.join(", "))),
+            (
+                "Read-only",
+                subscription.map(|source| format!("subscribed from {source}")),
+            ),
+            ("Location", t.property_value("LOCATION").map(str::to_owned)),
+            ("URL", t.get_url().map(str::to_owned)),
+            ("Created", t.get_created().map(|dt| date(dt.into()))),
+            ("Modified", t.get_last_modified().map(|dt| date(dt.into()))),
+            (
+                "Index",
+                Some(format!(
+                    "{:.2}",
+                    IndexScore::new(todo, &state.calendars.index, &now).total()
+                )),
+            ),
+            ("UID", t.get_uid().map(str::to_owned)),
+        ];
+
+        let mut lines = vec![
+            Line::from(vec![
+                t.get_summary()
+                    .unwrap_or_default()
+                    .to_owned()
+                    .set_style(theme.title),
+                Span::raw("  "),
+                format!(
+                    " {} ",
+                    todo.cal_display_name.as_ref().unwrap_or(todo.cal_name)
+                )
+                .set_style(theme.calendar_chip(todo.color)),
+            ]),
+            Line::default(),
+        ];
+
+        lines.extend(fields.into_iter().filter_map(|(label, value)| {
+            let style = match (label, todo.is_overdue(&now)) {
+                ("Due", true) => theme.date_overdue,
+                _ => theme.list,
+            };
+
+            Some(Line::from(vec![
+                format!("{label:<LABEL_WIDTH$}").set_style(theme.status_bar_key),
+                value?.set_style(style),
+            ]))
+        }));
+
+        if let Some(description) = t.get_description() {
+            lines.push(Line::default());
+            lines.extend(description.lines().map(|l| Line::from(l.to_owned())));
+        }
+
+        lines
+    }
+}
+
+impl StatefulWidget for TaskDetail<'_> {
+    type State = App;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let todos = state
+            .calendars
+            .get_todos(Some(&state.display.sort), Some(&state.display.filter));
+
+        let lines = match state.list_state.selected().and_then(|idx| todos.get(idx)) {
+            Some(todo) => Self::lines(todo, state),
+            None => vec![Line::from("No task selected".set_style(state.theme.done))],
+        };
+
+        // Scrolled no further than to the last line (lines are counted after wrapping)
+        let inner = self.block.as_ref().map_or(area, |block| block.inner(area));
+        let rows: usize = lines
+            .iter()
+            .map(|line| line.width().max(1).div_ceil(inner.width.max(1) as usize))
+            .sum();
+        let max_scroll = rows.saturating_sub(inner.height as usize);
+        state.detail_scroll = state.detail_scroll.min(max_scroll as u16);
+
+        let mut paragraph = Paragraph::new(lines)
+            .style(state.theme.list)
+            .wrap(Wrap { trim: false })
+            .scroll((state.detail_scroll, 0));
+
+        if let Some(block) = self.block {
+            paragraph = paragraph.block(block)
+        }
+
+        paragraph.render(area, buf);
+    }
+}
@@ -1,10 +1,14 @@
 use ratatui::{
-    style::{Style, Stylize},
+    layout::{Position, Rect},
     text::Line,
     widgets::{Block, List, ListState, StatefulWidget},
 };
+use unicode_width::UnicodeWidthStr;
 
-use crate::{App, app::IsekCalendar};
+use crate::{App, app::IsekCalendar, line_format::Field};
+
+/// Width of the highlight symbol in front of every entry
+const HIGHLIGHT_WIDTH: u16 = 2;
 
 /// State for the todo list widget that tracks which calendars are displayed and list navigation state
 pub struct ToDoListState {
@@ -24,6 +28,30 @@ impl<'a> ToDoList<'a> {
         self.block = Some(block);
         self
     }
+
+    /// Find the entry at a position inside the inner area (without block) of the list
+    ///
+    /// Returns the index of the entry and whether the position is on the progress indicator
+    pub fn hit(state: &App, inner: Rect, column: u16, row: u16) -> Option<(usize, bool)> {
+        if !inner.contains(Position::new(column, row)) {
+            return None;
+        }
+
+        let idx = state.list_state.offset() + (row - inner.y) as usize;
+
+        let progress = &state.display.progress;
+        let progress_width = [&progress.none, &progress.done, &progress.in_progress]
+            .iter()
+            .map(|p| p.width())
+            .max()
+            .unwrap_or_default() as u16;
+
+        let x = column - inner.x;
+        let on_progress = state.line_format.starts_with(Field::Progress)
+            && (HIGHLIGHT_WIDTH..HIGHLIGHT_WIDTH + progress_width).contains(&x);
+
+        Some((idx, on_progress))
+    }
 }
 
 impl StatefulWidget for ToDoList<'_> {
@@ -35,6 +63,13 @@ impl StatefulWidget for ToDoList<'_> {
         buf: &mut ratatui::prelude::Buffer,
         state: &mut Self::State,
     ) {
+        // Space left for the text after border, padding and highlight symbol
+        let width = match &self.block {
+            Some(block) => block.inner(area).width,
+            None => area.width,
+        }
+        .saturating_sub(HIGHLIGHT_WIDTH) as usize;
+
         let sort = Some(&state.display.sort);
         let filter = Some(&state.display.filter);
 
@@ -43,17 +78,25 @@ impl StatefulWidget for ToDoList<'_> {
 
         let items = todos
             .iter()
-            .map(|t| Line::from(t.format(state)))
+            .enumerate()
+            .map(|(idx, t)| {
+                let line = t.format(state, width);
+
+                match state.is_marked(idx, t.id()) {
+                    true => line.patch_style(state.theme.marked),
+                    false => line,
+                }
+            })
             .collect::<Vec<Line>>();
 
         // Configure and render the list widget
-        let mut list = List::new(items);
+        let mut list = List::new(items).style(state.theme.list);
 
         if let Some(block) = self.block {
             list = list.block(block)
         }
 
-        list.highlight_style(Style::new().bold())
+        list.highlight_style(state.theme.highlight)
             .highlight_symbol("> ")
             .repeat_highlight_symbol(true)
             .highlight_spacing(ratatui::widgets::HighlightSpacing::Always)
@@ -1,6 +1,20 @@
 mod list;
 pub use list::{ToDoList, ToDoListState};
 
+mod table;
+pub use table::ToDoTable;
+
 mod status_bar;
 pub use status_bar::StatusBar;
 
+mod agenda;
+pub use agenda::Agenda;
+
+mod calendar;
+pub use calendar::{CalendarGrid, DayTasks};
+
+mod detail;
+pub use detail::TaskDetail;
+
+mod stats;
+pub use stats::Stats;
@@ -0,0 +1,168 @@
+use chrono::{TimeDelta, Utc};
+use icalendar::Component;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::Styled,
+    text::{Line, Span},
+    widgets::{Block, Row, StatefulWidget, Table, Widget},
+};
+
+use crate::{App, app::IsekTodo, helper::local_today};
+
+/// Names of the counted columns
+const COLUMNS: [&str; 6] = ["Open", "Overdue", "Today", "7 days", "Done 7d", "Total"];
+
+/// Statistics Widget with task counts per calendar and by priority
+#[derive(Default)]
+pub struct Stats<'a> {
+    block: Option<Block<'a>>,
+}
+
+/// Task counts of a calendar (or all calendars)
+#[derive(Default)]
+struct Counts {
+    open: usize,
+    overdue: usize,
+    today: usize,
+    week: usize,
+    done_recently: usize,
+    total: usize,
+}
+
+impl Counts {
+    fn add(&mut self, todo: &IsekTodo) {
+        let now = Utc::now();
+        let today = local_today();
+
+        self.total += 1;
+
+        if let Some(completed) = todo.get().get_completed() {
+            if now - completed < TimeDelta::days(7) {
+                self.done_recently += 1;
+            }
+            return;
+        }
+        if todo.is_closed() {
+            return;
+        }
+
+        self.open += 1;
+        if todo.is_overdue(&now) {
+            self.overdue += 1;
+        }
+
+        match todo.due_date().map(|date| (date - today).num_days()) {
+            Some(0) => {
+                self.today += 1;
+                self.week += 1;
+            }
+            Some(1..7) => self.week += 1,
+            _ => {}
+        }
+    }
+
+    fn values(&self) -> [usize; 6] {
+        [
+            self.open,
+            self.overdue,
+            self.today,
+            self.week,
+            self.done_recently,
+            self.total,
+        ]
+    }
+}
+
+impl<'a> Stats<'a> {
+    /// Configure the block (border and title) for this widget
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Table row with the name and the counts
+    fn row(name: String, counts: &Counts) -> Row<'static> {
+        Row::new(std::iter::once(name).chain(counts.values().into_iter().map(|v| v.to_string())))
+    }
+}
+
+impl StatefulWidget for Stats<'_> {
+    type State = App;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let inner = match &self.block {
+            Some(block) => block.inner(area),
+            None => area,
+        };
+        if let Some(block) = self.block {
+            block.render(area, buf);
+        }
+
+        let theme = &state.theme;
+        let todos = state.calendars.get_todos(None, None);
+
+        // One row per calendar and a sum of all calendars
+        let mut names = state.calendars.names();
+        names.sort();
+
+        let mut all = Counts::default();
+        let mut rows = vec![];
+        for name in names {
+            let mut counts = Counts::default();
+            let calendar = todos.iter().filter(|t| t.cal_name == name);
+            calendar.for_each(|t| {
+                counts.add(t);
+                all.add(t);
+            });
+
+            let display_name = state
+                .calendars
+                .get_calendar(name)
+                .and_then(|cal| cal.config.display_name.clone())
+                .unwrap_or_else(|| name.clone());
+
+            rows.push(Self::row(display_name, &counts));
+        }
+        rows.push(Self::row(String::from("All"), &all).style(theme.title));
+
+        let header = Row::new(
+            std::iter::once("Calendar")
+                .chain(COLUMNS)
+                .map(|c| c.set_style(theme.status_bar_key)),
+        );
+
+        let widths = std::iter::once(Constraint::Fill(1))
+            .chain(COLUMNS.iter().map(|_| Constraint::Length(9)));
+        let table = Table::new(rows, widths).header(header).style(theme.list);
+
+        // Open tasks by priority below the table
+        let open: Vec<&IsekTodo> = todos.iter().filter(|t| !t.is_closed()).collect();
+        let priority = |range: std::ops::RangeInclusive<u32>| {
+            open.iter()
+                .filter(|t| range.contains(&t.get().get_priority().unwrap_or(0)))
+                .count()
+        };
+
+        let priorities = Line::from(vec![
+            Span::styled("Open by priority  ", theme.status_bar_key),
+            Span::styled(format!("high {}", priority(1..=4)), theme.priority_high),
+            Span::raw("  "),
+            Span::styled(format!("medium {}", priority(5..=5)), theme.priority_medium),
+            Span::raw("  "),
+            Span::styled(format!("low {}", priority(6..=9)), theme.priority_low),
+            Span::raw("  "),
+            Span::raw(format!("none {}", priority(0..=0))),
+        ]);
+
+        let [table_area, _, priority_area] = Layout::vertical([
+            Constraint::Length(state.calendars.names().len() as u16 + 2),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        Widget::render(table, table_area, buf);
+        priorities.render(priority_area, buf);
+    }
+}
@@ -1,4 +1,6 @@
 use ratatui::{
+    layout::{Position, Rect},
+    style::{Style, Styled},
     text::{Line, Span},
     widgets::{Block, Padding, Paragraph, Widget},
 };
@@ -9,6 +11,9 @@ pub struct StatusBar<'a> {
     block: Block<'a>,
 
     keybinds: Vec<(String, String)>,
+
+    style: Style,
+    key_style: Style,
 }
 
 impl<'a> StatusBar<'a> {
@@ -16,7 +21,45 @@ impl<'a> StatusBar<'a> {
         Self {
             keybinds,
             block: Block::new().padding(Padding::symmetric(2, 0)),
+            style: Style::new(),
+            key_style: Style::new(),
+        }
+    }
+
+    /// Configure the styles for keybind descriptions and keys
+    pub fn styles(mut self, style: Style, key_style: Style) -> Self {
+        self.style = style;
+        self.key_style = key_style;
+        self
+    }
+
+    /// Find the key of the keybind displayed at a position
+    pub fn hint_at(&self, area: Rect, column: u16, row: u16) -> Option<&str> {
+        let inner = self.block.inner(area);
+
+        if !inner.contains(Position::new(column, row)) {
+            return None;
         }
+
+        let mut start = inner.x;
+        for (key, description) in &self.keybinds {
+            let end = start + Self::hint(key, description).width() as u16;
+
+            if (start..end).contains(&column) {
+                return Some(key);
+            }
+
+            start = end;
+        }
+
+        None
+    }
+
+    fn hint<'b>(key: &str, description: &str) -> Line<'b> {
+        Line::from(vec![
+            format!("<{}>", key).into(),
+            format!(" {}  ", description).into(),
+        ])
     }
 
     /// Configure the block (border and title) for this widget
@@ -31,10 +74,17 @@ impl Widget for StatusBar<'_> {
         let line = Line::from(
             self.keybinds
                 .iter()
-                .map(|(key, description)| format!("<{}> {}  ", key, description).into())
+                .flat_map(|(key, description)| {
+                    let mut hint = Self::hint(key, description).spans;
+                    hint[0] = hint[0].clone().set_style(self.key_style);
+                    hint
+                })
                 .collect::<Vec<Span>>(),
         );
 
-        Paragraph::new(line).block(self.block).render(area, buf);
+        Paragraph::new(line)
+            .style(self.style)
+            .block(self.block)
+            .render(area, buf);
     }
 }
@@ -0,0 +1,182 @@
+use ratatui::{
+    layout::{Constraint, Position, Rect},
+    style::{Modifier, Style, Styled},
+    text::Line,
+    widgets::{Block, Cell, HighlightSpacing, Row, StatefulWidget, Table, TableState},
+};
+
+use crate::{
+    App,
+    config::SortingVariant,
+    line_format::{Field, FieldFormat, truncate},
+};
+
+const COLUMN_SPACING: u16 = 1;
+
+/// Width of the highlight symbol in front of every row
+const HIGHLIGHT_WIDTH: u16 = 2;
+
+/// ToDo Table Widget
+///
+/// Alternative to [`super::ToDoList`] with aligned columns. Shares the selection with `App::list_state`.
+#[derive(Default)]
+pub struct ToDoTable<'a> {
+    block: Option<Block<'a>>,
+}
+
+impl<'a> ToDoTable<'a> {
+    /// Configure the block (border and title) for this widget
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Width of all columns for a table with the given inner width (without block)
+    ///
+    /// Columns without fixed width share the remaining space
+    fn column_widths(state: &App, inner_width: u16) -> Vec<u16> {
+        let columns = &state.display.table_columns;
+
+        let fixed: u16 = columns.iter().filter_map(|c| c.width).sum();
+        let fill_count = columns.iter().filter(|c| c.width.is_none()).count().max(1) as u16;
+        let fill_width = inner_width
+            .saturating_sub(HIGHLIGHT_WIDTH)
+            .saturating_sub(fixed)
+            .saturating_sub(COLUMN_SPACING * columns.len().saturating_sub(1) as u16)
+            / fill_count;
+
+        columns
+            .iter()
+            .map(|c| c.width.unwrap_or(fill_width))
+            .collect()
+    }
+
+    /// Find the row at a position inside the inner area (without block) of the table
+    ///
+    /// Returns the index of the row and whether the position is on the progress column
+    pub fn hit(state: &App, inner: Rect, column: u16, row: u16) -> Option<(usize, bool)> {
+        // First line is the header
+        if !inner.contains(Position::new(column, row)) || row == inner.y {
+            return None;
+        }
+
+        let idx = state.list_state.offset() + (row - inner.y - 1) as usize;
+
+        let x = column - inner.x;
+        let mut start = HIGHLIGHT_WIDTH;
+        let mut on_progress = false;
+
+        for (c, width) in state
+            .display
+            .table_columns
+            .iter()
+            .zip(Self::column_widths(state, inner.width))
+        {
+            if (start..start + width).contains(&x) {
+                on_progress = c.field == Field::Progress;
+                break;
+            }
+            start += width + COLUMN_SPACING;
+        }
+
+        Some((idx, on_progress))
+    }
+}
+
+impl StatefulWidget for ToDoTable<'_> {
+    type State = App;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let columns = &state.display.table_columns;
+
+        let inner_width = match &self.block {
+            Some(block) => block.inner(area).width,
+            None => area.width,
+        };
+        let widths = Self::column_widths(state, inner_width);
+
+        // Mark focused and sorted column in the header
+        let header = Row::new(columns.iter().enumerate().map(|(idx, column)| {
+            let mut title = column.field.title().to_owned();
+
+            if SortingVariant::from_field(column.field).as_ref() == Some(&state.display.sort.by) {
+                title.push(if state.display.sort.ascending {
+                    '▲'
+                } else {
+                    '▼'
+                });
+            }
+
+            let mut style = state.theme.title;
+            if idx == state.table_column {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+
+            Cell::from(title.set_style(style))
+        }));
+
+        let sort = Some(&state.display.sort);
+        let filter = Some(&state.display.filter);
+
+        // Generate table rows from the application's calendar data
+        let todos = state.calendars.get_todos(sort, filter);
+
+        let rows = todos
+            .iter()
+            .enumerate()
+            .map(|(idx, t)| {
+                let style = match state.is_marked(idx, t.id()) {
+                    true => state.theme.marked,
+                    false => Style::new(),
+                };
+
+                Row::new(columns.iter().zip(&widths).map(|(column, width)| {
+                    let mut span = t.field(
+                        &FieldFormat {
+                            field: column.field,
+                            relative: false,
+                            width: None,
+                        },
+                        state,
+                    );
+
+                    span.content = truncate(&span.content, *width as usize).into();
+
+                    Cell::from(Line::from(span))
+                }))
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let widths = widths.iter().map(|width| Constraint::Length(*width));
+
+        // Configure and render the table widget
+        let mut table = Table::new(rows, widths)
+            .header(header)
+            .column_spacing(COLUMN_SPACING)
+            .style(state.theme.list);
+
+        if let Some(block) = self.block {
+            table = table.block(block)
+        }
+
+        let mut table_state = TableState::new()
+            .with_offset(state.list_state.offset())
+            .with_selected(state.list_state.selected());
+
+        table
+            .row_highlight_style(state.theme.highlight)
+            .highlight_symbol("> ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .render(area, buf, &mut table_state);
+
+        // Write back selection and scroll position
+        *state.list_state.offset_mut() = table_state.offset();
+        state.list_state.select(table_state.selected());
+    }
+}